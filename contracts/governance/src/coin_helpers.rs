@@ -1,6 +1,54 @@
 use crate::error::ContractError;
 use cosmwasm_std::Coin;
 use cw3::Status;
+
+/// total amount of `denom` in `funds`
+pub fn denom_amount(funds: &[Coin], denom: &str) -> u128 {
+    funds
+        .iter()
+        .filter(|coin| coin.denom == denom)
+        .map(|coin| coin.amount.u128())
+        .sum()
+}
+
+/// splits the funds sent with a proposal into the submission fee and the deposit. The fee is paid
+/// with the first accepted fee whose denom is sent besides the deposit, or else in the deposit denom.
+pub fn split_submission_fee(
+    funds: &[Coin],
+    deposit_denom: &str,
+    fees: &[Coin],
+) -> Result<(Coin, Vec<Coin>), ContractError> {
+    let fee = fees
+        .iter()
+        .filter(|fee| fee.denom != deposit_denom)
+        .find(|fee| funds.iter().any(|coin| coin.denom == fee.denom))
+        .or_else(|| fees.iter().find(|fee| fee.denom == deposit_denom))
+        .ok_or(ContractError::InsufficientSubmissionFee {})?;
+    if denom_amount(funds, &fee.denom) < fee.amount.u128() {
+        return Err(ContractError::InsufficientSubmissionFee {});
+    }
+
+    let mut deposit = vec![];
+    for coin in funds {
+        let amount = if coin.denom == fee.denom {
+            coin.amount - fee.amount
+        } else {
+            coin.amount
+        };
+        if amount.is_zero() {
+            continue;
+        }
+        if coin.denom != deposit_denom {
+            return Err(ContractError::AdditionalDenomDeposit {});
+        }
+        deposit.push(Coin {
+            denom: coin.denom.clone(),
+            amount,
+        });
+    }
+    Ok((fee.clone(), deposit))
+}
+
 pub fn assert_sent_sufficient_coin_deposit(
     sent: &[Coin],
     required: Option<Coin>,
@@ -1,35 +1,50 @@
-use crate::coin_helpers::assert_sent_sufficient_coin_deposit;
+use crate::coin_helpers::{denom_amount, split_submission_fee};
 use crate::error::ContractError;
 use crate::msg::{
-    AppProposalResponse, ExecuteMsg, ExtendedPair, InstantiateMsg, MigrateMsg,
-    ProposalResponseTotal, Propose, QueryMsg, SudoMsg,
+    AppProposalResponse, EffectiveWeightResponse, ExecuteMsg, GovStatsResponse, InstantiateMsg,
+    LockEndResponse, MigrateMsg, ProposalProblem, ProposalResponseTotal, Propose, QueryMsg,
+    SignedBallot, SudoMsg, TallyResponse, ValidateProposalResponse, VoterRewardsResponse,
+    VoterStatsResponse,
 };
-use std::str::FromStr;
 use crate::state::{
-    next_id, AppGovConfig, Ballot, Config, Proposal, TokenSupply, Votes, APPGOVCONFIG,
-    APPPROPOSALS, BALLOTS, CONFIG, PROPOSALS, PROPOSALSBYAPP, VOTERDEPOSIT,
+    commitment_hash, next_id, queue_proposal, save_ballot, stats_period, AppGovConfig, AppSettings,
+    AppTally, Ballot, Cancellation, Category, CommitRevealSettings, Commitment, Config,
+    ExpeditedPhase, Guardian, MessageResult, PeriodStats, Proposal, ProposalOutcome,
+    ProposalRevision, ProposalRewards, TallyMode, TokenSupply, VoteRecord, VoterStats, Votes,
+    ACTIVEPROPOSALS, APPGOVCONFIG, APPPROPOSALS, APPSETTINGS, APPVOTERS, BALLOTS, CANCELLATIONS,
+    CATEGORIES, COMMITMENTS, CONFIG, DEPOSITREVISION, EXECUTIONRESULTS, EXPIRYQUEUE, PERIODSTATS,
+    PROPOSALREVISIONS, PROPOSALREWARDS, PROPOSALS, PROPOSALSBYAPP, PROPOSERSLASHED, REWARDSCLAIMED,
+    VOTEHISTORY, VOTENONCES, VOTERAPPBALLOTS, VOTERAPPSTATS, VOTERBALLOTS, VOTERDEPOSIT,
+    VOTERREWARDPOOL, VOTERSTATS,
 };
 use crate::validation::{
-    add_extended_pair_vault, auction_mapping_for_app, collector_lookup_table, get_token_supply,
-    query_app_exists, query_get_asset_data, remove_whitelist_app_id_liquidation,
-    remove_whitelist_app_id_vault_interest, remove_whitelist_asset_locker, set_esm_params,
-    update_locker_lsr, update_pairvault_stability, validate_threshold,
-    whitelist_app_id_liquidation, whitelist_app_id_vault_interest, whitelist_asset_locker_eligible,
-    whitelist_asset_locker_rewards,
+    get_token_supply, msg_kind, query_app_exists, query_get_asset_data, validate_optimistic_msgs,
+    validate_participating_msg, validate_threshold, verify_signed_ballot,
 };
-use fury_bindings::{FuryMessages, FuryQuery};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::{
-    entry_point, to_binary, BankMsg, Binary, BlockInfo, Coin, Deps, DepsMut, Env, MessageInfo,
-    Order, QueryRequest, Response, StdError, StdResult, Uint128, WasmQuery,
+    entry_point, to_json_binary, Addr, BankMsg, Binary, BlockInfo, Coin, CustomQuery, Decimal,
+    Deps, DepsMut, Empty, Env, MessageInfo, Order, QueryRequest, Reply, Response, StdError,
+    StdResult, Storage, SubMsg, SubMsgResult, Timestamp, Uint128, WasmQuery,
 };
 use cw2::set_contract_version;
 use cw3::{
     ProposalListResponse, ProposalResponse, Status, Vote, VoteInfo, VoteListResponse, VoteResponse,
 };
 use cw_storage_plus::Bound;
-use cw_utils::{Duration, Threshold, ThresholdResponse};
+use cw_utils::{
+    parse_reply_execute_data, Duration, Expiration, Scheduled, Threshold, ThresholdResponse,
+};
+use fury_bindings::{FuryMessages, FuryQuery};
 use std::cmp::Ordering;
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+
+// maximum length of a vote memo
+const MAX_MEMO_LENGTH: usize = 256;
+// seconds after the end of voting a failed execution can be retried, unless the app sets its own
+const DEFAULT_EXECUTION_WINDOW: u64 = 14 * 24 * 60 * 60;
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:governance";
@@ -70,7 +85,11 @@ pub fn instantiate(
     Ok(Response::default())
 }
 #[entry_point]
-pub fn sudo(deps: DepsMut, _env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+pub fn sudo(
+    deps: DepsMut<FuryQuery>,
+    env: Env,
+    msg: SudoMsg,
+) -> Result<Response<FuryMessages>, ContractError> {
     match msg {
         SudoMsg::UpdateLockingContract { address } => {
             let mut cfg = CONFIG.load(deps.storage)?;
@@ -97,7 +116,211 @@ pub fn sudo(deps: DepsMut, _env: Env, msg: SudoMsg) -> Result<Response, Contract
             CONFIG.save(deps.storage, &cfg)?;
             Ok(Response::new())
         }
+        SudoMsg::UpdateAppSettings { app_id, settings } => {
+            if let Some(guardian) = &settings.guardian {
+                validate_guardian(deps.as_ref(), guardian)?;
+            }
+            if let Some(rewards) = &settings.voter_rewards {
+                if rewards.pool_share > Decimal::one() || rewards.payout_share > Decimal::one() {
+                    return Err(ContractError::InvalidRewardShare {});
+                }
+            }
+            if let Some(fee) = &settings.submission_fee {
+                if fee.amounts.is_empty() || fee.amounts.iter().any(|fee| fee.amount.is_zero()) {
+                    return Err(ContractError::InvalidSubmissionFee {});
+                }
+                if let Some(collector) = &fee.collector {
+                    deps.api.addr_validate(collector.as_str())?;
+                }
+            }
+            if settings.required_apps == Some(0) {
+                return Err(ContractError::InvalidRequiredApps {});
+            }
+            if let Some(slashing) = &settings.slashing {
+                if [
+                    slashing.vetoed,
+                    slashing.quorum_missed,
+                    slashing.deposit_missed,
+                ]
+                .iter()
+                .any(|fraction| *fraction > Decimal::one())
+                {
+                    return Err(ContractError::InvalidSlashFraction {});
+                }
+            }
+            APPSETTINGS.save(deps.storage, app_id, &settings)?;
+            Ok(Response::new()
+                .add_attribute("action", "update_app_settings")
+                .add_attribute("app_id", app_id.to_string()))
+        }
+        SudoMsg::UpdateCategories { categories } => {
+            validate_categories(&categories)?;
+            CATEGORIES.save(deps.storage, &categories)?;
+            Ok(Response::new().add_attribute("action", "update_categories"))
+        }
+        SudoMsg::UpdateGuardian { app_id, guardian } => {
+            if let Some(guardian) = &guardian {
+                validate_guardian(deps.as_ref(), guardian)?;
+            }
+            let mut settings = APPSETTINGS
+                .may_load(deps.storage, app_id)?
+                .unwrap_or_default();
+            settings.guardian = guardian;
+            APPSETTINGS.save(deps.storage, app_id, &settings)?;
+            Ok(Response::new()
+                .add_attribute("action", "update_guardian")
+                .add_attribute("app_id", app_id.to_string()))
+        }
+        SudoMsg::ProcessExpired { limit } => sudo_process_expired(deps, env, limit),
+    }
+}
+
+/// next time to look at a passed proposal that cannot be executed yet, None if it can be executed
+/// or is blocked for good by a dependency that will never be executed
+fn execution_blocked_until(
+    deps: Deps<FuryQuery>,
+    env: &Env,
+    prop: &Proposal,
+) -> StdResult<Option<Expiration>> {
+    let waiting = vec![prop.review_ends()?, prop.timelock_ends()?]
+        .into_iter()
+        .flatten()
+        .find(|ends| !ends.is_expired(&env.block));
+    if waiting.is_some() {
+        return Ok(waiting);
+    }
+    // height based schedules and undecided dependencies are checked again in the next run
+    let next_run = Expiration::AtTime(env.block.time.plus_seconds(1));
+    if let Some(execute_at) = &prop.execute_at {
+        if !execute_at.is_triggered(&env.block) {
+            return Ok(Some(match execute_at {
+                Scheduled::AtTime(time) => Expiration::AtTime(*time),
+                Scheduled::AtHeight(_) => next_run,
+            }));
+        }
+    }
+    for dependency in &prop.depends_on {
+        let dependency = PROPOSALS.load(deps.storage, *dependency)?;
+        match dependency.current_status(&env.block) {
+            Status::Pending | Status::Open | Status::Revealing => {
+                return Ok(Some(dependency.voting_ends()));
+            }
+            Status::Passed => return Ok(Some(next_run)),
+            _ => {}
+        }
+    }
+    Ok(None)
+}
+
+/// persists the final status of the proposals due in the expiry queue, executes passed proposals
+/// and refunds or slashes the deposits
+fn sudo_process_expired(
+    mut deps: DepsMut<FuryQuery>,
+    env: Env,
+    limit: u32,
+) -> Result<Response<FuryMessages>, ContractError> {
+    let due = EXPIRYQUEUE
+        .keys(
+            deps.storage,
+            None,
+            Some(Bound::inclusive((env.block.time.seconds(), u64::MAX))),
+            Order::Ascending,
+        )
+        .take(limit as usize)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut response = Response::new().add_attribute("action", "process_expired");
+    for (time, proposal_id) in due {
+        EXPIRYQUEUE.remove(deps.storage, (time, proposal_id));
+        let mut prop = PROPOSALS.load(deps.storage, proposal_id)?;
+        match prop.current_status(&env.block) {
+            Status::Pending | Status::Open | Status::Revealing => {
+                // the voting period got extended, e.g. by an escalation or an expedited fallback
+                queue_proposal(deps.storage, proposal_id, prop.voting_ends())?;
+            }
+            Status::Passed => {
+                if let Some(ends) = execution_blocked_until(deps.as_ref(), &env, &prop)? {
+                    // the proposal stops counting as active while it waits for its execution
+                    prop.status = Status::Passed;
+                    save_proposal(deps.storage, proposal_id, &prop)?;
+                    queue_proposal(deps.storage, proposal_id, ends)?;
+                    continue;
+                }
+                let sender = env.contract.address.to_string();
+                match execute_passed_proposal(deps.branch(), &env, &sender, proposal_id) {
+                    Ok(executed) => {
+                        response.messages.extend(executed.messages);
+                        response.attributes.extend(executed.attributes);
+                        // re-validating the messages may have failed the execution already
+                        let prop = PROPOSALS.load(deps.storage, proposal_id)?;
+                        response = match prop.execution_error {
+                            Some(err) if prop.status == Status::ExecutionFailed => response
+                                .add_attribute(
+                                    "execute_error",
+                                    format!("{}: {}", proposal_id, err),
+                                ),
+                            _ => response.add_attribute("executed", proposal_id.to_string()),
+                        };
+                    }
+                    Err(err) => {
+                        // the proposal can no longer be executed, e.g. its execution window
+                        // closed or a dependency was rejected
+                        prop.status = Status::ExecutionFailed;
+                        prop.execution_error = Some(err.to_string());
+                        record_outcome(
+                            deps.storage,
+                            &env.block,
+                            proposal_id,
+                            &mut prop,
+                            ProposalOutcome::ExecutionFailed,
+                        )?;
+                        save_proposal(deps.storage, proposal_id, &prop)?;
+                        response = response
+                            .add_attribute("execute_error", format!("{}: {}", proposal_id, err));
+                    }
+                }
+                let prop = PROPOSALS.load(deps.storage, proposal_id)?;
+                let (refunds, returned) = refund_deposits(deps.storage, proposal_id, &prop)?;
+                response = response
+                    .add_messages(refunds)
+                    .add_attribute("returned", format!("{}: {}", proposal_id, returned));
+            }
+            Status::Rejected => {
+                prop.status = Status::Rejected;
+                save_proposal(deps.storage, proposal_id, &prop)?;
+                if !prop.check_vetoed(&env.block) {
+                    record_outcome(
+                        deps.storage,
+                        &env.block,
+                        proposal_id,
+                        &mut prop,
+                        ProposalOutcome::Rejected,
+                    )?;
+                    let burned = slash_rejected(deps.storage, &env.block, proposal_id, &mut prop)?;
+                    save_proposal(deps.storage, proposal_id, &prop)?;
+                    let (refunds, returned) = refund_deposits(deps.storage, proposal_id, &prop)?;
+                    response = response
+                        .add_messages(burn_msg(&env, &prop, burned))
+                        .add_messages(refunds)
+                        .add_attribute("rejected", proposal_id.to_string())
+                        .add_attribute("burned", format!("{}: {}", proposal_id, burned))
+                        .add_attribute("returned", format!("{}: {}", proposal_id, returned));
+                } else if !prop.is_slashed {
+                    let burned = slash_proposal(deps.branch(), &env, proposal_id, &mut prop)?;
+                    let (refunds, returned) = refund_deposits(deps.storage, proposal_id, &prop)?;
+                    response = response
+                        .add_messages(burn_msg(&env, &prop, burned))
+                        .add_messages(refunds)
+                        .add_attribute("slashed", proposal_id.to_string())
+                        .add_attribute("burned", format!("{}: {}", proposal_id, burned))
+                        .add_attribute("returned", format!("{}: {}", proposal_id, returned));
+                }
+            }
+            // executed, cancelled and failed proposals are settled already
+            _ => {}
+        }
     }
+    Ok(response)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -109,11 +332,47 @@ pub fn execute(
 ) -> Result<Response<FuryMessages>, ContractError> {
     match msg {
         ExecuteMsg::Propose { propose } => execute_propose(deps, env, info, propose),
-        ExecuteMsg::Vote { proposal_id, vote } => execute_vote(deps, env, info, proposal_id, vote),
+        ExecuteMsg::Vote {
+            proposal_id,
+            vote,
+            conviction,
+            option,
+            memo,
+        } => execute_vote(deps, env, info, proposal_id, vote, conviction, option, memo),
         ExecuteMsg::Execute { proposal_id } => execute_execute(deps, env, info, proposal_id),
         ExecuteMsg::Refund { proposal_id } => execute_refund(deps, env, info, proposal_id),
         ExecuteMsg::Deposit { proposal_id } => execute_deposit(deps, env, info, proposal_id),
         ExecuteMsg::Slash { proposal_id } => execute_slash(deps, env, info, proposal_id),
+        ExecuteMsg::AmendProposal {
+            proposal_id,
+            title,
+            description,
+            msgs,
+        } => execute_amend_proposal(deps, env, info, proposal_id, title, description, msgs),
+        ExecuteMsg::WithdrawDeposit { proposal_id } => {
+            execute_withdraw_deposit(deps, env, info, proposal_id)
+        }
+        ExecuteMsg::CancelProposal {
+            proposal_id,
+            reason,
+        } => execute_cancel_proposal(deps, env, info, proposal_id, reason),
+        ExecuteMsg::SubmitSignedVotes { ballots } => {
+            execute_submit_signed_votes(deps, env, info, ballots)
+        }
+        ExecuteMsg::CommitVote {
+            proposal_id,
+            commitment,
+        } => execute_commit_vote(deps, env, info, proposal_id, commitment),
+        ExecuteMsg::RevealVote {
+            proposal_id,
+            vote,
+            salt,
+        } => execute_reveal_vote(deps, env, info, proposal_id, vote, salt),
+        ExecuteMsg::Sponsor { proposal_id } => execute_sponsor(deps, env, info, proposal_id),
+        ExecuteMsg::Unsponsor { proposal_id } => execute_unsponsor(deps, env, info, proposal_id),
+        ExecuteMsg::ClaimVoterRewards { start_after, limit } => {
+            execute_claim_voter_rewards(deps, info, start_after, limit)
+        }
     }
 }
 
@@ -123,223 +382,54 @@ pub fn execute_propose(
     info: MessageInfo,
     propose: Propose,
 ) -> Result<Response<FuryMessages>, ContractError> {
-    // get proposal message length
-    let msg_length = propose.msgs.len();
-
-    //throw empty message error
-    if propose.msgs.is_empty() {
-        return Err(ContractError::NoMessage {});
-    }
-
-    // should be only 1 message
-    if msg_length > 1 {
-        return Err(ContractError::ExtraMessages {});
-    }
-    //get app data for app_id
-    let app_response = query_app_exists(deps.as_ref(), propose.app_id_param)?;
-
-    let voting_time = app_response.gov_time_in_seconds;
-    let min_gov_deposit = app_response.min_gov_deposit;
-    let max_voting_period = Duration::Time(voting_time);
-    let gov_token_id = app_response.gov_token_id;
-
-    //get gov token denom name
-    let gov_token_denom = query_get_asset_data(deps.as_ref(), gov_token_id)?;
-    if gov_token_denom.is_empty() || gov_token_id == 0 {
-        return Err(ContractError::NoGovToken {});
-    }
-
-    let cfg = CONFIG.load(deps.storage)?;
-
-    let query_msg = QueryMsg::Supply {
-        denom: gov_token_denom.clone(),
-    };
-    let query_response: TokenSupply =
-        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-            contract_addr: cfg.locking_contract.to_string(),
-            msg: to_binary(&query_msg).unwrap(),
-        }))?;
-
-    let total_weight = query_response.vtoken as u64;
-    if total_weight == 0 {
-        return Err(ContractError::ZeroSupply {});
-    }
-
-    let cfg = CONFIG.load(deps.storage)?;
-
-    let query_msg = QueryMsg::TotalVTokens {
-        denom: gov_token_denom.clone(),
-        address: info.sender.clone(),
-        height: Some(env.block.height),
-    };
-    let balance_response: Uint128 = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-        contract_addr: cfg.locking_contract.to_string(),
-        msg: to_binary(&query_msg).unwrap(),
-    }))?;
-
-    let voting_power = Coin {
-        amount: balance_response,
-        denom: gov_token_denom.clone(),
-    };
-
-    // max expires also used as default
-    let max_expires = max_voting_period.after(&env.block);
-    let mut expires = propose.latest.unwrap_or(max_expires);
-    let comp = expires.partial_cmp(&max_expires);
-    if let Some(Ordering::Greater) = comp {
-        expires = max_expires;
-    } else if comp.is_none() {
-        return Err(ContractError::WrongExpiration {});
-    }
-
-    //Check if no other deposit provided other than gov token deposit
-    let funds_len = info.funds.len();
-
-    if funds_len > 1 {
-        return Err(ContractError::AdditionalDenomDeposit {});
-    } else if funds_len == 0 {
-        return Err(ContractError::InsufficientFundsSend {});
-    } else if info.funds[0].denom != gov_token_denom {
-        return Err(ContractError::DenomNotFound {});
+    let (mut problems, draft) = validate_propose(
+        deps.as_ref(),
+        &env,
+        &propose,
+        Some(&info.sender),
+        Some(&info.funds),
+    )?;
+    // there is no draft without the problem of the unknown app
+    let draft = match draft {
+        Some(draft) if problems.is_empty() => draft,
+        _ => return Err(problems.remove(0).err),
     };
-
-    let gov_current_deposit = info.funds[0].amount.u128();
-
-    //Handle execution messages
-
-    match propose.msgs[0].clone() {
-        FuryMessages::MsgWhiteListAssetLocker { app_id, asset_id } => {
-            whitelist_asset_locker_eligible(deps.as_ref(), app_id, asset_id, propose.app_id_param)?
-        }
-        FuryMessages::MsgWhitelistAppIdLockerRewards { app_id, asset_id } => {
-            whitelist_asset_locker_rewards(deps.as_ref(), app_id, asset_id, propose.app_id_param)?
-        }
-        FuryMessages::MsgWhitelistAppIdVaultInterest { app_id } => {
-            whitelist_app_id_vault_interest(deps.as_ref(), app_id, propose.app_id_param)?
-        }
-        FuryMessages::MsgAddExtendedPairsVault {
-            app_id,
-            pair_id,
-            stability_fee,
-            closing_fee,
-            liquidation_penalty: _,
-            draw_down_fee,
-            is_vault_active: _,
-            debt_ceiling,
-            debt_floor,
-            is_stable_mint_vault: _,
-            min_cr: _,
-            pair_name,
-            asset_out_oracle_price: _,
-            asset_out_price: _,
-            min_usd_value_left: _,
-        } => add_extended_pair_vault(
-            deps.as_ref(),
-            propose.app_id_param,
-            ExtendedPair {
-                app_mapping_id_param: app_id,
-                pair_id_param: pair_id,
-                stability_fee_param: stability_fee,
-                closing_fee_param: closing_fee,
-                draw_down_fee_param: draw_down_fee,
-                debt_ceiling_param: debt_ceiling,
-                debt_floor_param: debt_floor,
-                pair_name_param: pair_name,
-            },
-        )?,
-        FuryMessages::MsgSetCollectorLookupTable {
-            app_id,
-            collector_asset_id,
-            secondary_asset_id,
-            surplus_threshold: _,
-            debt_threshold: _,
-            locker_saving_rate: _,
-            lot_size: _,
-            bid_factor: _,
-            debt_lot_size: _,
-        } => collector_lookup_table(
-            deps.as_ref(),
-            app_id,
-            collector_asset_id,
-            secondary_asset_id,
-            propose.app_id_param,
-        )?,
-
-        FuryMessages::MsgUpdatePairsVault {
-            app_id,
-            ext_pair_id,
-            stability_fee: _,
-            closing_fee: _,
-            liquidation_penalty: _,
-            draw_down_fee: _,
-            min_cr: _,
-            debt_ceiling: _,
-            debt_floor: _,
-            min_usd_value_left: _,
-            is_vault_active: _,
-        } => update_pairvault_stability(deps.as_ref(), app_id, ext_pair_id, propose.app_id_param)?,
-
-        FuryMessages::MsgSetAuctionMappingForApp {
-            app_id,
-            asset_id: _,
-            is_surplus_auction: _,
-            is_debt_auction: _,
-            asset_out_oracle_price: _,
-            asset_out_price: _,
-            is_distributor: _,
-        } => auction_mapping_for_app(deps.as_ref(), app_id, propose.app_id_param)?,
-
-        FuryMessages::MsgUpdateCollectorLookupTable {
-            app_id,
-            asset_id,
-            lsr: _,
-            debt_threshold: _,
-            surplus_threshold: _,
-            lot_size: _,
-            debt_lot_size: _,
-            bid_factor: _,
-        } => update_locker_lsr(deps.as_ref(), app_id, asset_id, propose.app_id_param)?,
-        FuryMessages::MsgRemoveWhitelistAssetLocker { app_id, asset_id } => {
-            remove_whitelist_asset_locker(deps.as_ref(), app_id, asset_id, propose.app_id_param)?
-        }
-        FuryMessages::MsgRemoveWhitelistAppIdVaultInterest { app_id } => {
-            remove_whitelist_app_id_vault_interest(deps.as_ref(), app_id, propose.app_id_param)?
-        }
-        FuryMessages::MsgWhitelistAppIdLiquidation { app_id } => {
-            whitelist_app_id_liquidation(deps.as_ref(), app_id, propose.app_id_param)?
-        }
-        FuryMessages::MsgRemoveWhitelistAppIdLiquidation { app_id } => {
-            remove_whitelist_app_id_liquidation(deps.as_ref(), app_id, propose.app_id_param)?
-        }
-        FuryMessages::MsgAddAuctionParams {
-            app_id: _,
-            auction_duration_seconds: _,
-            buffer: _,
-            cusp: _,
-            step: _,
-            price_function_type: _,
-            surplus_id: _,
-            debt_id: _,
-            dutch_id: _,
-            bid_duration_seconds: _,
-        } => (),
-        FuryMessages::MsgAddESMTriggerParams {
-            app_id,
-            target_value: _,
-            cool_off_period: _,
-            asset_id: _,
-            rates: _,
-        } => set_esm_params(deps.as_ref(), app_id, propose.app_id_param)?,
-        _ => return Err(ContractError::ProposalNotEligible {}),
-    }
-
-    //check if coins deposited is sufficient to pass minimum deposit
-    //if minimum deposit is achieved ,propsal status becomes "Open" else it becomes "Pending"
-    let min_deposit = Coin {
-        amount: Uint128::from_str(&min_gov_deposit)?,
-        denom: gov_token_denom.clone(),
+    let ProposalDraft {
+        settings,
+        gov_token_denom,
+        max_voting_period,
+        total_weight,
+        tally_weight,
+        voting_power,
+        expires,
+        submission_fee,
+        deposit_funds,
+        optimistic,
+        commit_reveal,
+        reveal_expires,
+        category,
+        threshold,
+        min_deposit_amount,
+        timelock,
+        expedited,
+        review_period,
+        participants,
+        msg_kinds,
+    } = draft;
+    let effective_power = settings.tally_mode.effective_weight(voting_power);
+    let gov_current_deposit = deposit_funds[0].amount.u128();
+    settle_ended_proposals(deps.storage, &env.block, propose.app_id_param)?;
+
+    // the proposer backs a yes/no proposal, a multiple choice proposal starts without preference
+    let initial_vote = if propose.options.is_empty() {
+        Vote::Yes
+    } else {
+        Vote::Abstain
     };
-    let deposit_status = assert_sent_sufficient_coin_deposit(&info.funds, Some(min_deposit))?;
+    let mut votes = Votes::default();
+    votes.add_vote(initial_vote, effective_power);
+    let mut raw_votes = Votes::default();
+    raw_votes.add_vote(initial_vote, voting_power);
 
     // initialize a proposal
     let mut prop = Proposal {
@@ -350,18 +440,54 @@ pub fn execute_propose(
         expires,
         msgs: propose.msgs,
         duration: max_voting_period,
-        status: deposit_status,
-        votes: Votes::yes(voting_power.amount.u128()),
-        threshold: cfg.threshold,
-        total_weight: Uint128::from(total_weight).u128(),
-        deposit: info.funds.clone(),
+        status: Status::Open,
+        votes,
+        threshold,
+        total_weight: tally_weight,
+        deposit: deposit_funds.clone(),
         proposer: info.sender.to_string(),
         token_denom: gov_token_denom,
-        min_deposit: Uint128::from_str(&min_gov_deposit)?,
+        min_deposit: min_deposit_amount,
         current_deposit: gov_current_deposit,
         app_mapping_id: propose.app_id_param,
         is_slashed: false,
+        tally_mode: settings.tally_mode,
+        raw_votes,
+        conviction: settings.conviction,
+        option_votes: vec![0; propose.options.len()],
+        options: propose.options,
+        revision: 0,
+        commit_reveal,
+        reveal_expires,
+        unrevealed_weight: 0,
+        optimistic,
+        escalated: false,
+        review_period,
+        set_guardian: propose.set_guardian,
+        expedited,
+        category: category.map(|category| category.name),
+        timelock,
+        execution_error: None,
+        execution_window: Some(
+            settings
+                .execution_window
+                .unwrap_or(DEFAULT_EXECUTION_WINDOW),
+        ),
+        outcome: None,
+        depends_on: propose.depends_on,
+        execute_at: propose.execute_at,
+        participants,
+        required_apps: settings.required_apps,
+        sponsorship: settings.sponsorship,
+        sponsors: vec![],
+        submission_fee: submission_fee.clone(),
+        raw_total_weight: total_weight,
     };
+    //check if coins deposited is sufficient to pass minimum deposit
+    //if minimum deposit is achieved ,propsal status becomes "Open" else it becomes "Pending"
+    if !prop.can_open() {
+        prop.status = Status::Pending;
+    }
 
     //update proposal status
     prop.update_status(&env.block);
@@ -376,7 +502,14 @@ pub fn execute_propose(
     let id = next_id(deps.storage)?;
 
     // update proposals
-    PROPOSALS.save(deps.storage, id, &prop)?;
+    save_proposal(deps.storage, id, &prop)?;
+    let revisions = vec![ProposalRevision {
+        revision: 0,
+        content_hash: prop.content_hash()?,
+        height: env.block.height,
+        time: env.block.time,
+    }];
+    PROPOSALREVISIONS.save(deps.storage, id, &revisions)?;
     app_proposals.push(crate::state::AppProposalConfig {
         proposal_id: id,
         proposal: prop.clone(),
@@ -385,12 +518,47 @@ pub fn execute_propose(
 
     // add the first yes vote from voter
     let ballot = Ballot {
-        weight: voting_power.amount.u128(),
-        vote: Vote::Yes,
+        weight: effective_power,
+        vote: initial_vote,
+        raw_weight: voting_power,
+        conviction: None,
+        option: None,
+        memo: None,
+        app_weights: prop
+            .participants
+            .iter()
+            .map(|tally| tally.votes.yes)
+            .collect(),
+    };
+
+    // the end blocker looks at the proposal once it may be decided
+    let first_check = match &prop.expedited {
+        Some(phase) => phase.expires,
+        None => prop.voting_ends(),
     };
+    queue_proposal(deps.storage, id, first_check)?;
 
-    BALLOTS.save(deps.storage, (id, &info.sender), &ballot)?;
-    VOTERDEPOSIT.save(deps.storage, (id, &info.sender), &info.funds)?;
+    save_ballot(
+        deps.storage,
+        propose.app_id_param,
+        id,
+        &info.sender,
+        &ballot,
+        &env.block,
+    )?;
+    let mut proposer_stats = VOTERSTATS
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    proposer_stats.proposals_created += 1;
+    VOTERSTATS.save(deps.storage, &info.sender, &proposer_stats)?;
+    let app_key = (&info.sender, propose.app_id_param);
+    let mut proposer_stats = VOTERAPPSTATS
+        .may_load(deps.storage, app_key)?
+        .unwrap_or_default();
+    proposer_stats.proposals_created += 1;
+    VOTERAPPSTATS.save(deps.storage, app_key, &proposer_stats)?;
+    VOTERDEPOSIT.save(deps.storage, (id, &info.sender), &deposit_funds)?;
+    DEPOSITREVISION.save(deps.storage, (id, &info.sender), &0)?;
 
     let propbyapp = match PROPOSALSBYAPP.may_load(deps.storage, propose.app_id_param)? {
         Some(data) => Some(data),
@@ -401,257 +569,890 @@ pub fn execute_propose(
         Some(data) => data,
         None => AppGovConfig {
             proposal_count: 0,
-            current_supply: Uint128::from(total_weight).u128(),
+            current_supply: total_weight,
             active_participation_supply: 0,
+            ..AppGovConfig::default()
         },
     };
 
     //// update proposal count
     app_gov_info.proposal_count += 1;
+    app_gov_info.lock_deposit(denom_amount(&deposit_funds, &prop.token_denom));
     //// update current supply
-    app_gov_info.current_supply = Uint128::from(total_weight).u128();
+    app_gov_info.current_supply = total_weight;
 
     let mut proposals_by_app = propbyapp.unwrap();
 
     proposals_by_app.push(id);
     PROPOSALSBYAPP.save(deps.storage, propose.app_id_param, &proposals_by_app)?;
+    // a cross-app proposal is listed by every participating app
+    for app_id in &propose.participating_apps {
+        let mut proposals_by_app = PROPOSALSBYAPP
+            .may_load(deps.storage, *app_id)?
+            .unwrap_or_default();
+        proposals_by_app.push(id);
+        PROPOSALSBYAPP.save(deps.storage, *app_id, &proposals_by_app)?;
+    }
     APPGOVCONFIG.save(deps.storage, propose.app_id_param, &app_gov_info)?;
+    update_period_stats(
+        deps.storage,
+        propose.app_id_param,
+        env.block.time,
+        |stats| {
+            stats.proposals_created += 1;
+            for kind in msg_kinds {
+                *stats.msg_counts.entry(kind).or_default() += 1;
+            }
+            if prop.status == Status::Open {
+                stats.record_deposit_time(0);
+            }
+        },
+    )?;
+
+    // fees without a collector stay in the contract treasury
+    let collector = settings
+        .submission_fee
+        .and_then(|submission_fee| submission_fee.collector);
+    let fee_transfer = match (submission_fee, collector) {
+        (Some(fee), Some(collector)) => Some(BankMsg::Send {
+            to_address: collector.to_string(),
+            amount: vec![fee],
+        }),
+        _ => None,
+    };
 
     Ok(Response::new()
+        .add_messages(fee_transfer)
         .add_attribute("action", "propose")
         .add_attribute("proposer", info.sender)
         .add_attribute("proposal_id", id.to_string())
         .add_attribute("status", format!("{:?}", prop.status)))
 }
 
-pub fn execute_vote(
-    deps: DepsMut<FuryQuery>,
-    env: Env,
-    info: MessageInfo,
-    proposal_id: u64,
-    vote: Vote,
-) -> Result<Response<FuryMessages>, ContractError> {
-    if !info.funds.is_empty() {
-        return Err(ContractError::CustomError {
-            val: "Funds deposit not allowed".to_string(),
-        });
-    }
-    // ensure proposal exists and can be voted on
-    let mut prop = PROPOSALS.load(deps.storage, proposal_id)?;
-    let status = prop.current_status(&env.block);
+/// problem found in a `Propose`, with the option and the message it concerns if any
+struct ProposeProblem {
+    option: Option<usize>,
+    msg_index: Option<usize>,
+    err: ContractError,
+}
 
-    // only Open voting status is eligible for voting
-    if status != Status::Open {
-        return Err(ContractError::NotOpen {});
+fn problem(err: impl Into<ContractError>) -> ProposeProblem {
+    ProposeProblem {
+        option: None,
+        msg_index: None,
+        err: err.into(),
     }
+}
 
-    let cfg = CONFIG.load(deps.storage)?;
-    let token_denom = &prop.token_denom;
-
-    let query_msg = QueryMsg::TotalVTokens {
-        denom: token_denom.to_string(),
-        address: info.sender.clone(),
-        height: Some(prop.start_height),
-    };
-    let balance_response: Uint128 = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-        contract_addr: cfg.locking_contract.to_string(),
-        msg: to_binary(&query_msg).unwrap(),
-    }))?;
+/// values a proposal is created from, derived while its `Propose` is validated
+struct ProposalDraft {
+    settings: AppSettings,
+    gov_token_denom: String,
+    max_voting_period: Duration,
+    // raw vtoken supply of the gov token
+    total_weight: u128,
+    // snapshot supply in the unit of the app's tally mode
+    tally_weight: u128,
+    // raw vtoken balance of the proposer
+    voting_power: u128,
+    expires: Expiration,
+    submission_fee: Option<Coin>,
+    deposit_funds: Vec<Coin>,
+    optimistic: Option<Decimal>,
+    commit_reveal: Option<CommitRevealSettings>,
+    reveal_expires: Option<Expiration>,
+    category: Option<Category>,
+    threshold: Threshold,
+    // minimum deposit including the category and expedited multipliers
+    min_deposit_amount: Uint128,
+    timelock: u64,
+    expedited: Option<ExpeditedPhase>,
+    review_period: Option<Duration>,
+    participants: Vec<AppTally>,
+    msg_kinds: Vec<String>,
+}
 
-    let voting_power = Coin {
-        amount: balance_response,
-        denom: token_denom.clone(),
+/// runs the checks of a new proposal and derives the values it is created from, None if its app
+/// does not exist. Every problem is collected instead of stopping at the first one, a value whose
+/// check failed falls back to a default. The checks of the proposer only run with a `proposer`,
+/// the deposit is only checked when `funds` are given.
+fn validate_propose(
+    deps: Deps<FuryQuery>,
+    env: &Env,
+    propose: &Propose,
+    proposer: Option<&Addr>,
+    funds: Option<&[Coin]>,
+) -> StdResult<(Vec<ProposeProblem>, Option<ProposalDraft>)> {
+    let mut problems = vec![];
+    // messages of a multiple choice proposal are reported with the index of their option
+    let option_of = |batch: usize| {
+        if propose.options.is_empty() {
+            None
+        } else {
+            Some(batch)
+        }
     };
-    //check previous vote (if any) in order to change previous vote weights
-    let previous_vote = BALLOTS.may_load(deps.storage, (proposal_id, &info.sender))?;
 
-    if let Some(..) = previous_vote {
-        let prev_vote = previous_vote.unwrap();
-        prop.votes
-            .subtract_vote(prev_vote.vote, voting_power.amount.u128())
+    // a multiple choice proposal carries its messages in its options
+    if !propose.options.is_empty() {
+        if !propose.msgs.is_empty() {
+            problems.push(problem(ContractError::ExtraMessages {}));
+        }
+        if propose.options.len() < 2 {
+            problems.push(problem(ContractError::NotEnoughOptions {}));
+        }
     }
 
-    let ballot_new = Ballot {
-        weight: voting_power.amount.u128(),
-        vote,
-    };
-    //update ballot vote
-    BALLOTS.save(deps.storage, (proposal_id, &info.sender), &ballot_new)?;
-
-    // update vote tally
-    prop.votes.add_vote(vote, voting_power.amount.u128());
-    prop.update_status(&env.block);
-    PROPOSALS.save(deps.storage, proposal_id, &prop)?;
+    // a guardian proposal only updates the app settings of this contract
+    if let Some(guardian) = &propose.set_guardian {
+        if !propose.msgs.is_empty() || !propose.options.is_empty() || propose.optimistic {
+            problems.push(problem(ContractError::ExtraMessages {}));
+        }
+        if let Err(err) = validate_guardian(deps, guardian) {
+            problems.push(problem(err));
+        }
+    }
 
-    Ok(Response::new()
-        .add_attribute("action", "vote")
-        .add_attribute("voter", info.sender)
-        .add_attribute("proposal_id", proposal_id.to_string())
-        .add_attribute("status", format!("{:?}", prop.status))
-        .add_attribute("vote", format!("{:?}", vote)))
-}
+    let batches = proposal_msg_batches(propose);
+    for (batch, msgs) in batches.iter().enumerate() {
+        //throw empty message error
+        if msgs.is_empty() {
+            problems.push(ProposeProblem {
+                option: option_of(batch),
+                msg_index: None,
+                err: ContractError::NoMessage {},
+            });
+        }
 
-pub fn execute_execute(
-    deps: DepsMut<FuryQuery>,
-    env: Env,
-    info: MessageInfo,
-    proposal_id: u64,
-) -> Result<Response<FuryMessages>, ContractError> {
-    if !info.funds.is_empty() {
-        return Err(ContractError::CustomError {
-            val: "Funds deposit not allowed".to_string(),
-        });
+        // should be only 1 message
+        for msg_index in 1..msgs.len() {
+            problems.push(ProposeProblem {
+                option: option_of(batch),
+                msg_index: Some(msg_index),
+                err: ContractError::ExtraMessages {},
+            });
+        }
     }
-    //Anyone can trigger the execution if the proposal current status is Passed
-    let mut prop = PROPOSALS.load(deps.storage, proposal_id)?;
-    let status = prop.current_status(&env.block);
 
-    if status != Status::Passed {
-        return Err(ContractError::WrongExecuteStatus {});
+    for dependency in &propose.depends_on {
+        if !PROPOSALS.has(deps.storage, *dependency) {
+            problems.push(problem(ContractError::UnknownDependency {
+                proposal_id: *dependency,
+            }));
+        }
     }
 
-    //Set it to executed
-    prop.status = Status::Executed;
-    PROPOSALS.save(deps.storage, proposal_id, &prop)?;
+    //get app data for app_id, everything else depends on the app
+    let app_response = match query_app_exists(deps, propose.app_id_param) {
+        Ok(app_response) => app_response,
+        Err(err) => {
+            problems.push(problem(err));
+            return Ok((problems, None));
+        }
+    };
+    let max_voting_period = Duration::Time(app_response.gov_time_in_seconds);
+    let gov_token_id = app_response.gov_token_id;
 
-    //Dispatch all proposed messages
-    Ok(Response::new()
-        .add_messages(prop.msgs)
-        .add_attribute("action", "execute")
-        .add_attribute("sender", info.sender)
-        .add_attribute("proposal_id", proposal_id.to_string()))
-}
+    //get gov token denom name
+    let gov_token_denom = match query_get_asset_data(deps, gov_token_id) {
+        Ok(denom) if !denom.is_empty() && gov_token_id != 0 => denom,
+        Ok(_) => {
+            problems.push(problem(ContractError::NoGovToken {}));
+            String::new()
+        }
+        Err(err) => {
+            problems.push(problem(err));
+            String::new()
+        }
+    };
 
-pub fn execute_deposit(
-    deps: DepsMut<FuryQuery>,
-    env: Env,
-    info: MessageInfo,
-    proposal_id: u64,
-) -> Result<Response<FuryMessages>, ContractError> {
-    if info.funds.len() != 1 {
-        return Err(ContractError::CustomError {
-            val: "Only single denom deposit is allowed".to_string(),
-        });
+    let cfg = CONFIG.load(deps.storage)?;
+    let settings = APPSETTINGS
+        .may_load(deps.storage, propose.app_id_param)?
+        .unwrap_or_default();
+
+    let mut total_weight = 0;
+    if !gov_token_denom.is_empty() {
+        let supply: StdResult<TokenSupply> =
+            deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+                contract_addr: cfg.locking_contract.to_string(),
+                msg: to_json_binary(&QueryMsg::Supply {
+                    denom: gov_token_denom.clone(),
+                })?,
+            }));
+        match supply {
+            Ok(supply) if supply.vtoken == 0 => {
+                problems.push(problem(ContractError::ZeroSupply {}))
+            }
+            Ok(supply) => total_weight = supply.vtoken,
+            Err(err) => problems.push(problem(err)),
+        }
     }
-    // Get proposal latest status
-    let mut prop = PROPOSALS.load(deps.storage, proposal_id)?;
-    let status = prop.current_status(&env.block);
 
-    // Validate sent tokens are the currect denom
-    if prop.token_denom != info.funds[0].denom {
-        return Err(ContractError::IncorrectDenomDeposit {});
+    // quadratic proposals need a snapshot supply expressed in the same unit as their ballots
+    let tally_weight = match settings.tally_mode {
+        TallyMode::Linear => total_weight,
+        TallyMode::Quadratic => match settings.quadratic_total_weight {
+            Some(weight) => weight,
+            None => {
+                problems.push(problem(ContractError::NoQuadraticSupply {}));
+                0
+            }
+        },
     };
 
-    // only Open or Pending Proposals are eligible for deposit
-
-    if [Status::Executed, Status::Rejected, Status::Passed]
-        .iter()
-        .any(|x| *x == status)
-    {
-        return Err(ContractError::CannotDeposit {});
+    let mut voting_power = 0;
+    if let Some(proposer) = proposer.filter(|_| !gov_token_denom.is_empty()) {
+        match query_vtoken_balance(deps, &gov_token_denom, proposer, env.block.height) {
+            Ok(balance) => {
+                voting_power = balance;
+                if let Err(err) = check_proposer_limits(
+                    deps,
+                    env,
+                    &settings,
+                    propose.app_id_param,
+                    proposer,
+                    balance,
+                ) {
+                    problems.push(problem(err));
+                }
+            }
+            Err(err) => problems.push(problem(err)),
+        }
     }
 
-    // Get user deposit info for the proposal and update the deposit data
+    // max expires also used as default
+    let max_expires = max_voting_period.after(&env.block);
+    let mut expires = propose.latest.unwrap_or(max_expires);
+    match expires.partial_cmp(&max_expires) {
+        Some(Ordering::Greater) => expires = max_expires,
+        None => {
+            problems.push(problem(ContractError::WrongExpiration {}));
+            expires = max_expires;
+        }
+        _ => {}
+    }
 
-    let mut deposit_info = match VOTERDEPOSIT.may_load(deps.storage, (proposal_id, &info.sender))? {
-        Some(record) => record,
-        None => vec![],
-    };
-    let mut deposit_amount: u128 = 0;
-    if !deposit_info.is_empty() {
-        for mut current_deposit_coin in deposit_info.clone() {
-            if info.funds[0].denom == current_deposit_coin.denom {
-                current_deposit_coin.amount += info.funds[0].amount;
-                deposit_amount = info.funds[0].amount.u128();
+    let mut submission_fee = None;
+    let mut deposit_funds = vec![];
+    if let Some(funds) = funds {
+        match split_proposal_funds(funds, &gov_token_denom, &settings) {
+            Ok((fee, deposit)) => {
+                submission_fee = fee;
+                deposit_funds = deposit;
             }
+            Err(err) => problems.push(problem(err)),
         }
-    } else {
-        let mut is_correct_fund = false;
-        if prop.token_denom == info.funds[0].denom {
-            deposit_amount = info.funds[0].amount.u128();
-            is_correct_fund = true;
+    }
+
+    // optimistic proposals are yes/no proposals made of allowlisted messages only
+    let optimistic = if propose.optimistic {
+        match &settings.optimistic {
+            Some(optimistic) if propose.options.is_empty() => {
+                if let Err(err) = validate_optimistic_msgs(optimistic, &propose.msgs) {
+                    problems.push(problem(err));
+                }
+                Some(optimistic.objection_threshold)
+            }
+            _ => {
+                problems.push(problem(ContractError::OptimisticNotEnabled {}));
+                None
+            }
         }
-        if is_correct_fund {
-            deposit_info = info.funds.clone();
-        } else {
-            return Err(ContractError::IncorrectDenomDeposit {});
+    } else {
+        None
+    };
+
+    // secret ballots are only available for yes/no proposals of a single app, objections to an
+    // optimistic proposal have to be visible
+    let commit_reveal = if propose.options.is_empty()
+        && !propose.optimistic
+        && !propose.expedited
+        && propose.participating_apps.is_empty()
+    {
+        settings.commit_reveal
+    } else {
+        None
+    };
+    let reveal_expires = match commit_reveal
+        .map(|commit_reveal| expires + commit_reveal.reveal_period)
+        .transpose()
+    {
+        Ok(reveal_expires) => reveal_expires,
+        Err(err) => {
+            problems.push(problem(err));
+            None
+        }
+    };
+
+    // the strictest category among the messages sets the pass requirements and timelock
+    let category = match strictest_category(deps, &cfg, &batches) {
+        Ok(category) => category,
+        Err(err) => {
+            problems.push(problem(err));
+            None
         }
+    };
+    let mut threshold = cfg.threshold.clone();
+    let mut min_deposit_amount = match Uint128::from_str(&app_response.min_gov_deposit) {
+        Ok(amount) => amount,
+        Err(err) => {
+            problems.push(problem(err));
+            Uint128::zero()
+        }
+    };
+    let mut timelock = 0;
+    if let Some(category) = &category {
+        threshold = Threshold::ThresholdQuorum {
+            threshold: category.threshold,
+            quorum: category.quorum,
+        };
+        match min_deposit_amount.checked_mul(category.deposit_multiplier.into()) {
+            Ok(amount) => min_deposit_amount = amount,
+            Err(err) => problems.push(problem(StdError::from(err))),
+        }
+        timelock = category.timelock;
     }
 
-    prop.deposit.push(info.funds[0].clone());
-    prop.current_deposit += deposit_amount;
+    // expedited proposals need a higher deposit and pass early with a stricter threshold
+    let expedited = match (&settings.expedited, propose.expedited) {
+        (_, false) => None,
+        (None, true) => {
+            problems.push(problem(ContractError::ExpeditedNotEnabled {}));
+            None
+        }
+        (Some(expedited), true) => {
+            if propose.optimistic || propose.set_guardian.is_some() {
+                problems.push(problem(ContractError::ExpeditedNotAllowed {}));
+            }
+            match min_deposit_amount.checked_mul(expedited.deposit_multiplier.into()) {
+                Ok(amount) => min_deposit_amount = amount,
+                Err(err) => problems.push(problem(StdError::from(err))),
+            }
+            let threshold = match threshold.clone() {
+                Threshold::ThresholdQuorum { threshold, quorum } => Threshold::ThresholdQuorum {
+                    threshold: threshold.max(expedited.threshold),
+                    quorum,
+                },
+                threshold => threshold,
+            };
+            let mut short_expires = Duration::Time(expedited.voting_period).after(&env.block);
+            if let Some(Ordering::Greater) = short_expires.partial_cmp(&expires) {
+                short_expires = expires;
+            }
+            Some(ExpeditedPhase {
+                threshold,
+                expires: short_expires,
+            })
+        }
+    };
+
+    // an active guardian gets a review window before execution, except on its own replacement
+    let review_period = match (&settings.guardian, &propose.set_guardian) {
+        (Some(guardian), None) if !guardian.expires.is_expired(&env.block) => {
+            Some(guardian.review_period)
+        }
+        _ => None,
+    };
 
-    if Uint128::from(prop.current_deposit) > Uint128::from(prop.min_deposit) {
-        prop.status = Status::Open
+    // every participating app tallies with its own gov token, supply and threshold
+    if !propose.participating_apps.is_empty()
+        && (!propose.options.is_empty()
+            || propose.optimistic
+            || propose.expedited
+            || propose.set_guardian.is_some())
+    {
+        problems.push(problem(ContractError::CrossAppNotAllowed {}));
+    }
+    let mut app_ids = vec![propose.app_id_param];
+    let mut participants = vec![];
+    for app_id in &propose.participating_apps {
+        if app_ids.contains(app_id) {
+            problems.push(problem(ContractError::DuplicateApp { app_id: *app_id }));
+            continue;
+        }
+        app_ids.push(*app_id);
+        if let Some(proposer) = proposer {
+            match participant_tally(deps, env, *app_id, proposer, &threshold) {
+                Ok(tally) => participants.push(tally),
+                Err(err) => problems.push(problem(err)),
+            }
+        }
     }
 
-    VOTERDEPOSIT.save(deps.storage, (proposal_id, &info.sender), &deposit_info)?;
-    PROPOSALS.save(deps.storage, proposal_id, &prop)?;
+    //Handle execution messages
+    let mut msg_kinds = vec![];
+    for (batch, msgs) in batches.iter().enumerate() {
+        for (msg_index, msg) in msgs.iter().enumerate() {
+            let located = |err: ContractError| ProposeProblem {
+                option: option_of(batch),
+                msg_index: Some(msg_index),
+                err,
+            };
+            if let Err(err) = validate_participating_msg(deps, msg, &app_ids) {
+                problems.push(located(err));
+            }
+            match msg_kind(msg) {
+                Ok(kind) => msg_kinds.push(kind),
+                Err(err) => problems.push(located(err.into())),
+            }
+        }
+    }
 
-    Ok(Response::new()
-        .add_attribute("action", "deposit")
-        .add_attribute("depositor", info.sender)
-        .add_attribute("proposal_id", proposal_id.to_string()))
+    let draft = ProposalDraft {
+        settings,
+        gov_token_denom,
+        max_voting_period,
+        total_weight,
+        tally_weight,
+        voting_power,
+        expires,
+        submission_fee,
+        deposit_funds,
+        optimistic,
+        commit_reveal,
+        reveal_expires,
+        category,
+        threshold,
+        min_deposit_amount,
+        timelock,
+        expedited,
+        review_period,
+        participants,
+        msg_kinds,
+    };
+    Ok((problems, Some(draft)))
 }
 
-pub fn execute_refund(
+/// splits the funds sent with a proposal into the submission fee and the deposit, which has to be
+/// paid in the gov token
+fn split_proposal_funds(
+    funds: &[Coin],
+    gov_token_denom: &str,
+    settings: &AppSettings,
+) -> Result<(Option<Coin>, Vec<Coin>), ContractError> {
+    // the submission fee is paid on top of the deposit, in the gov denom or any accepted denom
+    let (submission_fee, deposit_funds) = match &settings.submission_fee {
+        Some(fee) => {
+            let (paid, deposit_funds) = split_submission_fee(funds, gov_token_denom, &fee.amounts)?;
+            (Some(paid), deposit_funds)
+        }
+        None => (None, funds.to_vec()),
+    };
+
+    //Check if no other deposit provided other than gov token deposit
+    let funds_len = deposit_funds.len();
+
+    if funds_len > 1 {
+        return Err(ContractError::AdditionalDenomDeposit {});
+    } else if funds_len == 0 {
+        return Err(ContractError::InsufficientFundsSend {});
+    } else if deposit_funds[0].denom != gov_token_denom {
+        return Err(ContractError::DenomNotFound {});
+    };
+    Ok((submission_fee, deposit_funds))
+}
+
+/// tally of an app taking part in a cross-app proposal, starting with the proposer's yes vote
+fn participant_tally(
+    deps: Deps<FuryQuery>,
+    env: &Env,
+    app_id: u64,
+    proposer: &Addr,
+    threshold: &Threshold,
+) -> Result<AppTally, ContractError> {
+    let app_response = query_app_exists(deps, app_id)?;
+    let token_denom = query_get_asset_data(deps, app_response.gov_token_id)?;
+    if token_denom.is_empty() || app_response.gov_token_id == 0 {
+        return Err(ContractError::NoGovToken {});
+    }
+
+    let cfg = CONFIG.load(deps.storage)?;
+    let supply: TokenSupply = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: cfg.locking_contract.to_string(),
+        msg: to_json_binary(&QueryMsg::Supply {
+            denom: token_denom.clone(),
+        })?,
+    }))?;
+    if supply.vtoken == 0 {
+        return Err(ContractError::ZeroSupply {});
+    }
+
+    let settings = APPSETTINGS
+        .may_load(deps.storage, app_id)?
+        .unwrap_or_default();
+    let mut votes = Votes::default();
+    votes.add_vote(
+        Vote::Yes,
+        query_vtoken_balance(deps, &token_denom, proposer, env.block.height)?,
+    );
+    Ok(AppTally {
+        app_id,
+        token_denom,
+        total_weight: supply.vtoken,
+        threshold: settings.threshold.unwrap_or_else(|| threshold.clone()),
+        votes,
+    })
+}
+
+/// message batches of a proposal, one per option for a multiple choice proposal
+fn proposal_msg_batches(propose: &Propose) -> Vec<&Vec<FuryMessages>> {
+    if propose.set_guardian.is_some() {
+        vec![]
+    } else if propose.options.is_empty() {
+        vec![&propose.msgs]
+    } else {
+        propose.options.iter().map(|option| &option.msgs).collect()
+    }
+}
+
+/// strictest of each requirement among the categories of the messages, and the contract threshold
+/// if a message has no category, None if no message has a category
+fn strictest_category(
+    deps: Deps<FuryQuery>,
+    cfg: &Config,
+    batches: &[&Vec<FuryMessages>],
+) -> Result<Option<Category>, ContractError> {
+    let categories = CATEGORIES.may_load(deps.storage)?.unwrap_or_default();
+    let mut strictest: Option<Category> = None;
+    let mut uncategorized = false;
+    for msg in batches.iter().flat_map(|msgs| msgs.iter()) {
+        let kind = msg_kind(msg)?;
+        match categories
+            .iter()
+            .find(|category| category.msgs.contains(&kind))
+        {
+            Some(category) => {
+                strictest = Some(match strictest {
+                    Some(current) => current.strictest_with(category),
+                    None => category.clone(),
+                });
+            }
+            None => uncategorized = true,
+        }
+    }
+
+    // uncategorized messages need at least the contract threshold
+    if let (true, Some(category), Threshold::ThresholdQuorum { threshold, quorum }) =
+        (uncategorized, &mut strictest, &cfg.threshold)
+    {
+        category.threshold = category.threshold.max(*threshold);
+        category.quorum = category.quorum.max(*quorum);
+    }
+    Ok(strictest)
+}
+
+fn validate_categories(categories: &[Category]) -> Result<(), ContractError> {
+    let mut kinds: Vec<&String> = vec![];
+    for category in categories {
+        validate_threshold(&category.threshold, &category.quorum)?;
+        if category.deposit_multiplier == 0 {
+            return Err(ContractError::InvalidDepositMultiplier {});
+        }
+        for kind in &category.msgs {
+            if kinds.contains(&kind) {
+                return Err(ContractError::DuplicateCategoryMsg { kind: kind.clone() });
+            }
+            kinds.push(kind);
+        }
+    }
+    Ok(())
+}
+
+/// the guardian role has to expire, otherwise it would become permanent control
+fn validate_guardian<Q: CustomQuery>(
+    deps: Deps<Q>,
+    guardian: &Guardian,
+) -> Result<(), ContractError> {
+    deps.api.addr_validate(guardian.address.as_str())?;
+    if guardian.expires == (Expiration::Never {}) {
+        return Err(ContractError::GuardianNeverExpires {});
+    }
+    Ok(())
+}
+
+/// enforces the anti-spam limits configured in the app settings
+fn check_proposer_limits(
+    deps: Deps<FuryQuery>,
+    env: &Env,
+    settings: &AppSettings,
+    app_id: u64,
+    proposer: &Addr,
+    balance: u128,
+) -> Result<(), ContractError> {
+    if let Some(min) = settings.min_propose_balance {
+        if balance < min {
+            return Err(ContractError::InsufficientProposerBalance { min });
+        }
+    }
+
+    if let Some(cooldown) = settings.slash_cooldown {
+        if let Some(slashed) = PROPOSERSLASHED.may_load(deps.storage, (app_id, proposer))? {
+            let until = slashed.plus_seconds(cooldown);
+            if env.block.time < until {
+                return Err(ContractError::ProposerCooldown { until });
+            }
+        }
+    }
+
+    if settings.max_active_proposals.is_none() && settings.max_proposals_per_proposer.is_none() {
+        return Ok(());
+    }
+    // proposals whose voting ended do not count, whether or not their status was stored yet
+    let mut app_active = 0;
+    let mut proposer_active = 0;
+    for id in ACTIVEPROPOSALS
+        .prefix(app_id)
+        .keys(deps.storage, None, None, Order::Ascending)
+    {
+        let prop = PROPOSALS.load(deps.storage, id?)?;
+        if prop.is_active_at(&env.block) {
+            app_active += 1;
+            if prop.proposer == *proposer {
+                proposer_active += 1;
+            }
+        }
+    }
+    if let Some(max) = settings.max_active_proposals {
+        if app_active >= max {
+            return Err(ContractError::TooManyActiveProposals { max });
+        }
+    }
+    if let Some(max) = settings.max_proposals_per_proposer {
+        if proposer_active >= max {
+            return Err(ContractError::TooManyProposerProposals { max });
+        }
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_vote(
     deps: DepsMut<FuryQuery>,
     env: Env,
     info: MessageInfo,
     proposal_id: u64,
+    vote: Vote,
+    conviction: Option<u8>,
+    option: Option<u32>,
+    memo: Option<String>,
 ) -> Result<Response<FuryMessages>, ContractError> {
     if !info.funds.is_empty() {
         return Err(ContractError::CustomError {
             val: "Funds deposit not allowed".to_string(),
         });
     }
-    // Get proposal status
-    let prop = PROPOSALS.load(deps.storage, proposal_id)?;
-    let status = prop.current_status(&env.block);
+    let status = cast_vote(
+        deps,
+        &env,
+        &info.sender,
+        proposal_id,
+        vote,
+        conviction,
+        option,
+        memo,
+    )?;
 
-    // Open and Pending proposal status are not eligible for refund
-    if status == Status::Pending {
-        return Err(ContractError::PendingProposal {});
+    Ok(Response::new()
+        .add_attribute("action", "vote")
+        .add_attribute("voter", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("status", format!("{:?}", status))
+        .add_attribute("vote", format!("{:?}", vote)))
+}
+
+pub fn execute_submit_signed_votes(
+    mut deps: DepsMut<FuryQuery>,
+    env: Env,
+    info: MessageInfo,
+    ballots: Vec<SignedBallot>,
+) -> Result<Response<FuryMessages>, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::CustomError {
+            val: "Funds deposit not allowed".to_string(),
+        });
+    }
+    if ballots.is_empty() {
+        return Err(ContractError::NoBallots {});
     }
 
-    if status == Status::Open {
-        return Err(ContractError::OpenProposal {});
+    let mut response = Response::new()
+        .add_attribute("action", "submit_signed_votes")
+        .add_attribute("relayer", info.sender);
+    for ballot in ballots {
+        let voter = verify_signed_ballot(deps.as_ref(), &env, &ballot)?;
+
+        // ballots are replay protected by a strictly increasing nonce per voter
+        let expected = VOTENONCES
+            .may_load(deps.storage, &voter)?
+            .unwrap_or_default();
+        if ballot.nonce != expected {
+            return Err(ContractError::InvalidNonce { expected });
+        }
+        VOTENONCES.save(deps.storage, &voter, &(expected + 1))?;
+
+        cast_vote(
+            deps.branch(),
+            &env,
+            &voter,
+            ballot.proposal_id,
+            ballot.vote,
+            ballot.conviction,
+            ballot.option,
+            None,
+        )?;
+        response = response.add_attribute(
+            "vote",
+            format!("{}:{}:{:?}", voter, ballot.proposal_id, ballot.vote),
+        );
     }
+    Ok(response)
+}
+
+/// records the ballot of `voter` and updates the proposal tally, returns the new proposal status
+#[allow(clippy::too_many_arguments)]
+fn cast_vote(
+    mut deps: DepsMut<FuryQuery>,
+    env: &Env,
+    voter: &Addr,
+    proposal_id: u64,
+    vote: Vote,
+    conviction: Option<u8>,
+    option: Option<u32>,
+    memo: Option<String>,
+) -> Result<Status, ContractError> {
+    // ensure proposal exists and can be voted on
+    let mut prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    let status = prop.current_status(&env.block);
 
-    //disallow slashed proposal
-    if status == Status::Rejected && prop.check_vetoed(&env.block) {
-        return Err(ContractError::SlashedProposal {});
+    // only Open voting status is eligible for voting
+    if status != Status::Open {
+        return Err(ContractError::NotOpen {});
     }
 
-    // get sender deposit info
-    let deposit_info = VOTERDEPOSIT.may_load(deps.storage, (proposal_id, &info.sender))?;
+    // votes on a commit-reveal proposal stay secret until the reveal phase
+    if prop.commit_reveal.is_some() {
+        return Err(ContractError::SecretBallot {});
+    }
+    // an expedited proposal still open after its short expiry is a regular proposal now
+    prop.end_expedited_phase(&env.block);
 
-    // If no reposit for the proposal
-    if deposit_info.is_none() {
-        return Err(ContractError::NoDeposit {});
+    record_ballot(
+        deps.branch(),
+        &env.block,
+        &mut prop,
+        proposal_id,
+        voter,
+        vote,
+        conviction,
+        option,
+        memo,
+    )?;
+
+    // objections turn an optimistic proposal into a normal vote with a fresh voting period
+    if prop.is_optimistic() && prop.objections_exceeded() {
+        prop.escalated = true;
+        prop.expires = prop.duration.after(&env.block);
     }
+    prop.update_status(&env.block);
+    save_proposal(deps.storage, proposal_id, &prop)?;
+    Ok(prop.status)
+}
 
-    //// need to update current_deposit////////
+/// saves the ballot of `voter` and adds it to the tally of `prop`, replacing a previous ballot
+#[allow(clippy::too_many_arguments)]
+fn record_ballot(
+    deps: DepsMut<FuryQuery>,
+    block: &BlockInfo,
+    prop: &mut Proposal,
+    proposal_id: u64,
+    voter: &Addr,
+    vote: Vote,
+    conviction: Option<u8>,
+    option: Option<u32>,
+    memo: Option<String>,
+) -> Result<(), ContractError> {
+    if memo
+        .as_ref()
+        .is_some_and(|memo| memo.chars().count() > MAX_MEMO_LENGTH)
+    {
+        return Err(ContractError::MemoTooLong {
+            max: MAX_MEMO_LENGTH,
+        });
+    }
+    // multiple choice ballots pick an option with a yes vote, "none of the above" is a no vote
+    let valid_option = match option {
+        Some(index) => vote == Vote::Yes && (index as usize) < prop.options.len(),
+        None => !(prop.is_multiple_choice() && vote == Vote::Yes),
+    };
+    if !valid_option {
+        return Err(ContractError::InvalidOption {});
+    }
 
-    VOTERDEPOSIT.remove(deps.storage, (proposal_id, &info.sender));
+    let raw_power = query_snapshot_power(deps.as_ref(), prop, voter)?;
+    let multiplier = conviction_multiplier(deps.as_ref(), prop, voter, conviction)?;
+    let effective_power = prop.tally_mode.effective_weight(raw_power) * multiplier as u128;
+    //check previous vote (if any) in order to remove the weights it was counted with
+    let previous_vote = BALLOTS.may_load(deps.storage, (proposal_id, voter))?;
 
-    PROPOSALS.save(deps.storage, proposal_id, &prop)?;
+    if let Some(prev_vote) = previous_vote {
+        for (tally, weight) in prop.participants.iter_mut().zip(&prev_vote.app_weights) {
+            tally.votes.subtract_vote(prev_vote.vote, *weight);
+        }
+        prop.votes.subtract_vote(prev_vote.vote, prev_vote.weight);
+        prop.raw_votes
+            .subtract_vote(prev_vote.vote, prev_vote.raw_weight);
+        if let Some(index) = prev_vote.option {
+            prop.option_votes[index as usize] -= prev_vote.weight;
+        }
+    }
 
-    Ok(Response::new()
-        .add_message(BankMsg::Send {
-            to_address: info.sender.to_string(),
-            amount: deposit_info.unwrap(),
+    // participating apps count the raw balances of their own gov token
+    let app_weights = prop
+        .participants
+        .iter()
+        .map(|tally| {
+            query_vtoken_balance(deps.as_ref(), &tally.token_denom, voter, prop.start_height)
         })
-        .add_attribute("action", "refund")
-        .add_attribute("sender", info.sender)
-        .add_attribute("proposal_id", proposal_id.to_string()))
+        .collect::<StdResult<Vec<_>>>()?;
+    for (tally, weight) in prop.participants.iter_mut().zip(&app_weights) {
+        tally.votes.add_vote(vote, *weight);
+    }
+
+    let ballot_new = Ballot {
+        weight: effective_power,
+        vote,
+        raw_weight: raw_power,
+        conviction,
+        option,
+        memo,
+        app_weights,
+    };
+    //update ballot vote
+    save_ballot(
+        deps.storage,
+        prop.app_mapping_id,
+        proposal_id,
+        voter,
+        &ballot_new,
+        block,
+    )?;
+
+    // update vote tally
+    prop.votes.add_vote(vote, effective_power);
+    prop.raw_votes.add_vote(vote, raw_power);
+    if let Some(index) = option {
+        prop.option_votes[index as usize] += effective_power;
+    }
+    Ok(())
 }
 
-pub fn execute_slash(
+pub fn execute_commit_vote(
     deps: DepsMut<FuryQuery>,
     env: Env,
     info: MessageInfo,
     proposal_id: u64,
+    commitment: String,
 ) -> Result<Response<FuryMessages>, ContractError> {
     if !info.funds.is_empty() {
         return Err(ContractError::CustomError {
@@ -659,1235 +1460,5993 @@ pub fn execute_slash(
         });
     }
     let mut prop = PROPOSALS.load(deps.storage, proposal_id)?;
-    let status = prop.current_status(&env.block);
-
-    //Check if proposal is rejected
-    if status != Status::Rejected {
-        return Err(ContractError::NotRejected {});
+    if prop.current_status(&env.block) != Status::Open {
+        return Err(ContractError::NotOpen {});
+    }
+    if prop.commit_reveal.is_none() {
+        return Err(ContractError::CommitRevealNotEnabled {});
     }
 
-    // check if proposal is vetoed
-    if !prop.check_vetoed(&env.block) {
-        return Err(ContractError::ProposalNotVetoed {});
+    let weight = query_snapshot_power(deps.as_ref(), &prop, &info.sender)?;
+
+    // a new commitment replaces the previous one of the voter
+    if let Some(previous) = COMMITMENTS.may_load(deps.storage, (proposal_id, &info.sender))? {
+        prop.unrevealed_weight = prop.unrevealed_weight.saturating_sub(previous.weight);
     }
+    COMMITMENTS.save(
+        deps.storage,
+        (proposal_id, &info.sender),
+        &Commitment { commitment, weight },
+    )?;
+    prop.unrevealed_weight += weight;
+    save_proposal(deps.storage, proposal_id, &prop)?;
 
-    //check if proposal already slashed
-    if prop.is_slashed {
-        return Err(ContractError::AlreadySlashed {});
+    Ok(Response::new()
+        .add_attribute("action", "commit_vote")
+        .add_attribute("voter", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string()))
+}
+
+pub fn execute_reveal_vote(
+    mut deps: DepsMut<FuryQuery>,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+    vote: Vote,
+    salt: String,
+) -> Result<Response<FuryMessages>, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::CustomError {
+            val: "Funds deposit not allowed".to_string(),
+        });
+    }
+    let mut prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    if prop.current_status(&env.block) != Status::Revealing {
+        return Err(ContractError::NotRevealing {});
     }
 
-    let deposit_amount = prop.current_deposit;
-    let deposit_denom = prop.token_denom.clone();
-    let slash_amount = Coin {
-        amount: Uint128::from(deposit_amount),
-        denom: deposit_denom,
-    };
-    prop.is_slashed = true;
+    let commitment = COMMITMENTS
+        .may_load(deps.storage, (proposal_id, &info.sender))?
+        .ok_or(ContractError::NoCommitment {})?;
+    if commitment.commitment != commitment_hash(vote, &salt, &info.sender) {
+        return Err(ContractError::InvalidReveal {});
+    }
+    COMMITMENTS.remove(deps.storage, (proposal_id, &info.sender));
+    prop.unrevealed_weight = prop.unrevealed_weight.saturating_sub(commitment.weight);
 
-    PROPOSALS.save(deps.storage, proposal_id, &prop)?;
+    record_ballot(
+        deps.branch(),
+        &env.block,
+        &mut prop,
+        proposal_id,
+        &info.sender,
+        vote,
+        None,
+        None,
+        None,
+    )?;
+    prop.update_status(&env.block);
+    save_proposal(deps.storage, proposal_id, &prop)?;
 
     Ok(Response::new()
-        .add_message(FuryMessages::MsgBurnGovTokensForApp {
-            app_id: prop.app_mapping_id,
-            amount: slash_amount,
-            from: env.contract.address.to_string(),
-        })
-        .add_attribute("action", "Slash")
-        .add_attribute("trigger_address", info.sender)
-        .add_attribute("proposal_id", proposal_id.to_string()))
+        .add_attribute("action", "reveal_vote")
+        .add_attribute("voter", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("vote", format!("{:?}", vote)))
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps<FuryQuery>, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::Threshold { proposal_id } => to_binary(&query_threshold(deps, proposal_id)?),
-        QueryMsg::Proposal { proposal_id } => {
-            to_binary(&query_proposal_detailed(deps, env, proposal_id)?)
-        }
-        QueryMsg::Vote { proposal_id, voter } => to_binary(&query_vote(deps, proposal_id, voter)?),
-        QueryMsg::ListProposals { start_after, limit } => {
-            to_binary(&list_proposals(deps, env, start_after, limit)?)
-        }
-        QueryMsg::ReverseProposals {
-            start_before,
-            limit,
-        } => to_binary(&reverse_proposals(deps, env, start_before, limit)?),
-        QueryMsg::ListVotes {
-            proposal_id,
-            start_after,
-            limit,
-        } => to_binary(&list_votes(deps, proposal_id, start_after, limit)?),
-        QueryMsg::ListAppProposal {
-            app_id,
-            start_after,
-            limit,
-            status,
-        } => to_binary(&get_proposals_by_app(
-            deps,
-            env,
-            app_id,
-            start_after,
-            limit,
-            status,
-        )?),
-        QueryMsg::AppAllUpData { app_id } => to_binary(&get_all_up_info_by_app(deps, env, app_id)?),
-
-        _ => panic!("Not implemented"),
-    }
+/// vtoken balance of `voter` at the proposal `start_height`
+fn query_snapshot_power(deps: Deps<FuryQuery>, prop: &Proposal, voter: &Addr) -> StdResult<u128> {
+    query_vtoken_balance(deps, &prop.token_denom, voter, prop.start_height)
 }
 
-fn query_threshold(deps: Deps<FuryQuery>, proposal_id: u64) -> StdResult<ThresholdResponse> {
+/// vtoken balance of `address` in `denom` at `height`
+fn query_vtoken_balance(
+    deps: Deps<FuryQuery>,
+    denom: &str,
+    address: &Addr,
+    height: u64,
+) -> StdResult<u128> {
     let cfg = CONFIG.load(deps.storage)?;
-    let prop = PROPOSALS.load(deps.storage, proposal_id)?;
-
-    Ok(cfg.threshold.to_response(prop.total_weight))
+    let query_msg = QueryMsg::TotalVTokens {
+        denom: denom.to_string(),
+        address: address.clone(),
+        height: Some(height),
+    };
+    let balance_response: Uint128 = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: cfg.locking_contract.to_string(),
+        msg: to_json_binary(&query_msg)?,
+    }))?;
+    Ok(balance_response.u128())
 }
 
-fn query_proposal_detailed(
+/// multiplier for a ballot committed with a conviction tier, verified against the voter's
+/// lock end in the locking contract at the proposal `start_height`
+fn conviction_multiplier(
     deps: Deps<FuryQuery>,
-    env: Env,
-    id: u64,
-) -> StdResult<ProposalResponseTotal> {
-    let prop = PROPOSALS.load(deps.storage, id)?;
-    let status = prop.current_status(&env.block);
-    Ok(ProposalResponseTotal {
-        id,
-        title: prop.title,
-        description: prop.description,
-        msgs: prop.msgs,
-        status,
-        start_time: prop.start_time,
-        expires: prop.expires,
-        votes: prop.votes,
-        duration: prop.duration,
-        start_height: prop.start_height,
-        threshold: prop.threshold,
-        proposer: prop.proposer,
-        token_denom: prop.token_denom,
-        total_weight: prop.total_weight,
-        current_deposit: prop.current_deposit,
-    })
-}
-
-// settings for pagination
-const MAX_LIMIT: u32 = 300;
-const DEFAULT_LIMIT: u32 = 100;
+    prop: &Proposal,
+    voter: &Addr,
+    conviction: Option<u8>,
+) -> Result<u64, ContractError> {
+    let tier = match conviction {
+        Some(tier) => tier,
+        None => return Ok(1),
+    };
+    let settings = prop
+        .conviction
+        .ok_or(ContractError::ConvictionNotEnabled {})?;
+    if tier == 0 || tier > settings.max_tier {
+        return Err(ContractError::InvalidConvictionTier {
+            max_tier: settings.max_tier,
+        });
+    }
+    if tier == 1 {
+        return Ok(1);
+    }
 
-fn list_proposals(
-    deps: Deps<FuryQuery>,
-    env: Env,
-    start_after: Option<u64>,
-    limit: Option<u32>,
-) -> StdResult<ProposalListResponse> {
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let start = start_after.map(Bound::exclusive);
-    let proposals = PROPOSALS
-        .range(deps.storage, start, None, Order::Ascending)
-        .take(limit)
-        .map(|p| map_proposal(&env.block, p))
-        .collect::<StdResult<_>>()?;
+    let cfg = CONFIG.load(deps.storage)?;
+    let query_msg = QueryMsg::LockEnd {
+        address: voter.clone(),
+        denom: prop.token_denom.clone(),
+        height: Some(prop.start_height),
+    };
+    let lock_response: LockEndResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: cfg.locking_contract.to_string(),
+            msg: to_json_binary(&query_msg)?,
+        }))?;
 
-    Ok(ProposalListResponse { proposals })
+    match lock_response.end_time {
+        Some(end_time) if end_time >= settings.required_lock_end(prop.start_time, tier) => {
+            Ok(tier as u64)
+        }
+        _ => Err(ContractError::InsufficientLockDuration {}),
+    }
 }
 
-fn get_proposals_by_app(
-    deps: Deps<FuryQuery>,
+pub fn execute_execute(
+    deps: DepsMut<FuryQuery>,
     env: Env,
-    app_id: u64,
-    start_after: u32,
-    limit: Option<u32>,
-    status: Option<Status>,
-) -> StdResult<AppProposalResponse> {
-    let mut info = match PROPOSALSBYAPP.may_load(deps.storage, app_id)? {
-        Some(record) => record,
-        None => vec![],
-    };
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response<FuryMessages>, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::CustomError {
+            val: "Funds deposit not allowed".to_string(),
+        });
+    }
+    //Anyone can trigger the execution if the proposal current status is Passed
+    execute_passed_proposal(deps, &env, info.sender.as_str(), proposal_id)
+}
 
-    let mut all_proposals = vec![];
-    info.reverse();
-    for i in info {
-        let proposal = query_proposal_detailed(deps, env.clone(), i)?;
-        match status {
-            Some(status) => {
-                if status == proposal.status {
-                    all_proposals.push(proposal.clone());
-                }
-            }
-            None => {
-                all_proposals.push(proposal);
-            }
+/// dispatches the messages of a passed proposal once its review window and timelock are over
+fn execute_passed_proposal(
+    deps: DepsMut<FuryQuery>,
+    env: &Env,
+    sender: &str,
+    proposal_id: u64,
+) -> Result<Response<FuryMessages>, ContractError> {
+    let mut prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    let status = prop.current_status(&env.block);
+
+    // a failed execution can be retried as long as the execution window is open
+    if status != Status::Passed && status != Status::ExecutionFailed {
+        return Err(ContractError::WrongExecuteStatus {});
+    }
+    if let Some(execution_ends) = prop.execution_ends()? {
+        if execution_ends.is_expired(&env.block) {
+            return Err(ContractError::ExecutionWindowClosed {});
         }
     }
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let start = start_after as usize;
-    let checkpoint = start + limit;
-    let mut proposal = vec![];
-    if !all_proposals.is_empty() {
-        // If the vec len is smaller than start_after, then empty vec
-        // If the checkpoint is >= length, then return all remaining elements
-        // else return the specific elements
-        let length = all_proposals.len();
-        if length <= start {
-            proposal = vec![];
-        } else if checkpoint >= length {
-            proposal = all_proposals[start..].to_vec();
-        } else {
-            proposal = all_proposals[start..checkpoint].to_vec();
+
+    // the guardian may still cancel the proposal during its review window
+    if let Some(review_ends) = prop.review_ends()? {
+        if !review_ends.is_expired(&env.block) {
+            return Err(ContractError::InReviewWindow {});
+        }
+    }
+    if let Some(timelock_ends) = prop.timelock_ends()? {
+        if !timelock_ends.is_expired(&env.block) {
+            return Err(ContractError::Timelocked {});
+        }
+    }
+    if let Some(execute_at) = &prop.execute_at {
+        if !execute_at.is_triggered(&env.block) {
+            return Err(ContractError::NotScheduledYet {});
+        }
+    }
+    for dependency in &prop.depends_on {
+        if PROPOSALS.load(deps.storage, *dependency)?.status != Status::Executed {
+            return Err(ContractError::DependencyNotExecuted {
+                proposal_id: *dependency,
+            });
         }
     }
-    let proposal_length = all_proposals.len();
 
-    Ok(AppProposalResponse {
-        proposals: proposal,
-        proposal_count: proposal_length as u64,
-    })
+    // the chain state may have changed since the proposal was created
+    let msgs = prop.passed_msgs();
+    if let Err(err) = msgs
+        .iter()
+        .try_for_each(|msg| validate_participating_msg(deps.as_ref(), msg, &prop.app_ids()))
+    {
+        prop.status = Status::ExecutionFailed;
+        prop.execution_error = Some(err.to_string());
+        record_outcome(
+            deps.storage,
+            &env.block,
+            proposal_id,
+            &mut prop,
+            ProposalOutcome::ExecutionFailed,
+        )?;
+        save_proposal(deps.storage, proposal_id, &prop)?;
+        return Ok(Response::new()
+            .add_attribute("action", "execute")
+            .add_attribute("sender", sender)
+            .add_attribute("proposal_id", proposal_id.to_string())
+            .add_attribute("status", format!("{:?}", prop.status))
+            .add_attribute("error", err.to_string()));
+    }
+
+    // the proposal is executed once the replies of all its messages succeeded
+    EXECUTIONRESULTS.save(deps.storage, proposal_id, &vec![])?;
+    if msgs.is_empty() {
+        record_executed(deps.storage, &env.block, proposal_id, &mut prop)?;
+    }
+
+    if let Some(guardian) = &prop.set_guardian {
+        let mut settings = APPSETTINGS
+            .may_load(deps.storage, prop.app_mapping_id)?
+            .unwrap_or_default();
+        settings.guardian = Some(guardian.clone());
+        APPSETTINGS.save(deps.storage, prop.app_mapping_id, &settings)?;
+    }
+
+    //Dispatch all proposed messages, the reply id is the proposal id
+    let msgs = msgs
+        .into_iter()
+        .map(|msg| SubMsg::reply_always(msg, proposal_id));
+    Ok(Response::new()
+        .add_submessages(msgs)
+        .add_attribute("action", "execute")
+        .add_attribute("sender", sender)
+        .add_attribute("proposal_id", proposal_id.to_string()))
 }
 
-fn get_all_up_info_by_app(
-    deps: Deps<FuryQuery>,
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(
+    deps: DepsMut<FuryQuery>,
     env: Env,
-    app_id: u64,
-) -> StdResult<AppGovConfig> {
-    let info = match PROPOSALSBYAPP.may_load(deps.storage, app_id)? {
-        Some(record) => record,
-        None => vec![],
+    msg: Reply,
+) -> Result<Response<FuryMessages>, ContractError> {
+    let proposal_id = msg.id;
+    let mut prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    let mut results = EXECUTIONRESULTS
+        .may_load(deps.storage, proposal_id)?
+        .unwrap_or_default();
+
+    // replies arrive in the order the messages were dispatched
+    let index = results.len() as u32;
+    let result = match msg.result.clone() {
+        SubMsgResult::Ok(_) => MessageResult {
+            index,
+            success: true,
+            error: None,
+            // only contract executions return MsgExecuteContractResponse data
+            data: parse_reply_execute_data(msg).ok().and_then(|res| res.data),
+        },
+        SubMsgResult::Err(err) => {
+            prop.status = Status::ExecutionFailed;
+            prop.execution_error = Some(err.clone());
+            record_outcome(
+                deps.storage,
+                &env.block,
+                proposal_id,
+                &mut prop,
+                ProposalOutcome::ExecutionFailed,
+            )?;
+            save_proposal(deps.storage, proposal_id, &prop)?;
+            MessageResult {
+                index,
+                success: false,
+                error: Some(err),
+                data: None,
+            }
+        }
     };
-
-    let app_response = query_app_exists(deps, app_id)?;
-    let gov_token_id = app_response.gov_token_id;
-    let total_weight = get_token_supply(deps, app_id, gov_token_id)?;
-
-    let mut participation_info = APPGOVCONFIG.may_load(deps.storage, app_id)?.unwrap();
-    let mut total_votes_weight: u128 = 0;
-    for i in info {
-        let proposal = query_proposal_detailed(deps, env.clone(), i)?;
-        total_votes_weight += proposal.votes.total();
+    let success = result.success;
+    results.push(result);
+    EXECUTIONRESULTS.save(deps.storage, proposal_id, &results)?;
+    if results.len() == prop.passed_msgs().len() && results.iter().all(|result| result.success) {
+        record_executed(deps.storage, &env.block, proposal_id, &mut prop)?;
     }
-    participation_info.current_supply = Uint128::from(total_weight).u128();
-    participation_info.active_participation_supply = total_votes_weight;
 
-    Ok(participation_info)
+    Ok(Response::new()
+        .add_attribute("action", "execute_reply")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("index", index.to_string())
+        .add_attribute("success", success.to_string()))
 }
 
-fn reverse_proposals(
-    deps: Deps<FuryQuery>,
-    env: Env,
-    start_before: Option<u64>,
-    limit: Option<u32>,
-) -> StdResult<ProposalListResponse> {
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let end = start_before.map(Bound::exclusive);
-    let props: StdResult<Vec<_>> = PROPOSALS
-        .range(deps.storage, None, end, Order::Descending)
-        .take(limit)
-        .map(|p| map_proposal(&env.block, p))
-        .collect();
+/// stores a proposal whose messages all succeeded as executed
+fn record_executed(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    proposal_id: u64,
+    prop: &mut Proposal,
+) -> StdResult<()> {
+    prop.status = Status::Executed;
+    prop.execution_error = None;
+    record_outcome(storage, block, proposal_id, prop, ProposalOutcome::Executed)?;
+    save_proposal(storage, proposal_id, prop)
+}
 
-    Ok(ProposalListResponse { proposals: props? })
+/// applies `update` to the governance stats of `app_id`
+fn update_app_stats(
+    storage: &mut dyn Storage,
+    app_id: u64,
+    update: impl FnOnce(&mut AppGovConfig),
+) -> StdResult<()> {
+    let mut stats = APPGOVCONFIG.may_load(storage, app_id)?.unwrap_or_default();
+    update(&mut stats);
+    APPGOVCONFIG.save(storage, app_id, &stats)
 }
 
-fn map_proposal(
+/// saves `prop`, keeping the active proposals of its app in line with its stored status
+fn save_proposal(storage: &mut dyn Storage, id: u64, prop: &Proposal) -> StdResult<()> {
+    PROPOSALS.save(storage, id, prop)?;
+    if prop.is_active() {
+        ACTIVEPROPOSALS.save(storage, (prop.app_mapping_id, id), &Empty {})
+    } else {
+        ACTIVEPROPOSALS.remove(storage, (prop.app_mapping_id, id));
+        Ok(())
+    }
+}
+
+/// stores the status of the app's proposals whose voting ended, so they leave the active proposals
+/// even if no one settled them yet
+fn settle_ended_proposals(
+    storage: &mut dyn Storage,
     block: &BlockInfo,
-    item: StdResult<(u64, Proposal)>,
-) -> StdResult<ProposalResponse> {
-    item.map(|(id, prop)| {
-        let status = prop.current_status(block);
-        let threshold = prop.threshold.to_response(prop.total_weight);
-        ProposalResponse {
-            id,
-            title: prop.title,
-            description: prop.description,
-            msgs: prop.msgs,
-            status,
-            expires: prop.expires,
-            threshold,
+    app_id: u64,
+) -> StdResult<()> {
+    let ids = ACTIVEPROPOSALS
+        .prefix(app_id)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for id in ids {
+        let mut prop = PROPOSALS.load(storage, id)?;
+        if !prop.is_active_at(block) {
+            prop.status = prop.current_status(block);
+            save_proposal(storage, id, &prop)?;
         }
-    })
+    }
+    Ok(())
 }
 
-fn query_vote(deps: Deps<FuryQuery>, proposal_id: u64, voter: String) -> StdResult<VoteResponse> {
-    let voter = deps.api.addr_validate(&voter)?;
-    let ballot = BALLOTS.may_load(deps.storage, (proposal_id, &voter))?;
-    let vote = ballot.map(|b| VoteInfo {
-        proposal_id,
-        voter: voter.into(),
-        vote: b.vote,
-        weight: b.weight,
-    });
-    Ok(VoteResponse { vote })
+/// applies `update` to the stats period of `app_id` containing `time`
+fn update_period_stats(
+    storage: &mut dyn Storage,
+    app_id: u64,
+    time: Timestamp,
+    update: impl FnOnce(&mut PeriodStats),
+) -> StdResult<()> {
+    let key = (app_id, stats_period(time));
+    let mut stats = PERIODSTATS.may_load(storage, key)?.unwrap_or_default();
+    update(&mut stats);
+    PERIODSTATS.save(storage, key, &stats)
 }
 
-fn list_votes(
-    deps: Deps<FuryQuery>,
+/// counts the outcome of `prop` in the stats of its app, replacing an earlier outcome
+fn record_outcome(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
     proposal_id: u64,
-    start_after: Option<String>,
-    limit: Option<u32>,
-) -> StdResult<VoteListResponse> {
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    prop: &mut Proposal,
+    outcome: ProposalOutcome,
+) -> StdResult<()> {
+    if prop.outcome == Some(outcome) {
+        return Ok(());
+    }
+    update_app_stats(storage, prop.app_mapping_id, |stats| {
+        stats.record_outcome(prop.outcome, outcome)
+    })?;
+    // the period stats only count the first decision, e.g. not a retried execution
+    if prop.outcome.is_none() {
+        let turnout = prop.turnout();
+        update_period_stats(storage, prop.app_mapping_id, block.time, |stats| {
+            stats.record_decision(outcome, turnout)
+        })?;
+    }
+    if outcome != ProposalOutcome::Cancelled {
+        assign_voter_rewards(storage, proposal_id, prop, outcome)?;
+    }
+    prop.outcome = Some(outcome);
+    Ok(())
+}
 
-    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+/// assigns a share of the app's voter rewards pool to the voters of a vetoed or high turnout
+/// proposal, at most once per proposal
+fn assign_voter_rewards(
+    storage: &mut dyn Storage,
+    proposal_id: u64,
+    prop: &Proposal,
+    outcome: ProposalOutcome,
+) -> StdResult<()> {
+    let settings = match APPSETTINGS
+        .may_load(storage, prop.app_mapping_id)?
+        .and_then(|settings| settings.voter_rewards)
+    {
+        Some(settings) => settings,
+        None => return Ok(()),
+    };
+    if PROPOSALREWARDS.has(storage, proposal_id)
+        || prop.votes.total() == 0
+        || (outcome != ProposalOutcome::Vetoed && prop.turnout() < settings.min_turnout)
+    {
+        return Ok(());
+    }
+    let pool = VOTERREWARDPOOL
+        .may_load(storage, prop.app_mapping_id)?
+        .unwrap_or_default();
+    let amount = (settings.payout_share * Uint128::from(pool)).u128();
+    if amount == 0 {
+        return Ok(());
+    }
+    VOTERREWARDPOOL.save(storage, prop.app_mapping_id, &(pool - amount))?;
+    PROPOSALREWARDS.save(
+        storage,
+        proposal_id,
+        &ProposalRewards {
+            amount,
+            denom: prop.token_denom.clone(),
+            total_weight: prop.votes.total(),
+        },
+    )
+}
 
-    let votes = BALLOTS
+/// returns all remaining deposits of a proposal to their depositors, along with the amount of
+/// the gov token returned
+fn refund_deposits(
+    storage: &mut dyn Storage,
+    proposal_id: u64,
+    prop: &Proposal,
+) -> StdResult<(Vec<BankMsg>, u128)> {
+    let deposits = VOTERDEPOSIT
         .prefix(proposal_id)
-        .range(deps.storage, start, None, Order::Ascending)
-        .take(limit)
-        .map(|item| {
-            item.map(|(addr, ballot)| VoteInfo {
-                proposal_id,
-                voter: addr.into(),
-                vote: ballot.vote,
-                weight: ballot.weight,
-            })
-        })
-        .collect::<StdResult<_>>()?;
-
-    Ok(VoteListResponse { votes })
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut refunds = vec![];
+    let mut refunded = 0;
+    for (depositor, amount) in deposits {
+        refunded += denom_amount(&amount, &prop.token_denom);
+        VOTERDEPOSIT.remove(storage, (proposal_id, &depositor));
+        refunds.push(BankMsg::Send {
+            to_address: depositor.to_string(),
+            amount,
+        });
+    }
+    update_app_stats(storage, prop.app_mapping_id, |stats| {
+        stats.refund_deposit(refunded)
+    })?;
+    Ok((refunds, refunded))
 }
 
-#[entry_point]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    let ver = cw2::get_contract_version(deps.storage)?;
-    // ensure we are migrating from an allowed contract
-    if ver.contract != CONTRACT_NAME {
-        return Err(StdError::generic_err("Can only upgrade from same type").into());
+pub fn execute_cancel_proposal(
+    deps: DepsMut<FuryQuery>,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+    reason: String,
+) -> Result<Response<FuryMessages>, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::CustomError {
+            val: "Funds deposit not allowed".to_string(),
+        });
     }
-    // note: better to do proper semver compare, but string compare *usually* works
-    if ver.version.as_str() > CONTRACT_VERSION {
-        return Err(StdError::generic_err("Cannot upgrade from a newer version").into());
+    let mut prop = PROPOSALS.load(deps.storage, proposal_id)?;
+
+    // only the current, unexpired guardian of the app can cancel
+    let settings = APPSETTINGS
+        .may_load(deps.storage, prop.app_mapping_id)?
+        .unwrap_or_default();
+    match settings.guardian {
+        Some(guardian)
+            if guardian.address == info.sender && !guardian.expires.is_expired(&env.block) => {}
+        _ => return Err(ContractError::Unauthorized {}),
+    }
+    if prop.set_guardian.is_some() {
+        return Err(ContractError::CannotCancelGuardianUpdate {});
+    }
+    if prop.current_status(&env.block) != Status::Passed {
+        return Err(ContractError::NotPassed {});
+    }
+    match prop.review_ends()? {
+        Some(review_ends) if !review_ends.is_expired(&env.block) => {}
+        _ => return Err(ContractError::ReviewWindowOver {}),
     }
 
-    // set the new version
-    cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    prop.status = Status::Cancelled;
+    record_outcome(
+        deps.storage,
+        &env.block,
+        proposal_id,
+        &mut prop,
+        ProposalOutcome::Cancelled,
+    )?;
+    save_proposal(deps.storage, proposal_id, &prop)?;
+    CANCELLATIONS.save(
+        deps.storage,
+        proposal_id,
+        &Cancellation {
+            guardian: info.sender.clone(),
+            reason: reason.clone(),
+            height: env.block.height,
+            time: env.block.time,
+        },
+    )?;
 
-    // do any desired state migrations...
+    // the deposits of a cancelled proposal are returned to the depositors
+    let (refunds, _) = refund_deposits(deps.storage, proposal_id, &prop)?;
 
-    Ok(Response::default())
+    Ok(Response::new()
+        .add_messages(refunds)
+        .add_attribute("action", "cancel_proposal")
+        .add_attribute("guardian", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("reason", reason))
 }
 
-#[cfg(test)]
-#[allow(unused_variables)]
-mod tests {
-    use crate::msg;
-    use cosmwasm_std::testing::{mock_env, mock_info};
-    use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage};
-    use cosmwasm_std::{coins, Addr, OwnedDeps};
-    use cosmwasm_std::{Decimal, Timestamp};
-    use cw_storage_plus::Map;
-    use cw_utils::Expiration;
-    use cw_utils::{Duration, Threshold};
-    use std::marker::PhantomData;
+pub fn execute_deposit(
+    deps: DepsMut<FuryQuery>,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response<FuryMessages>, ContractError> {
+    if info.funds.len() != 1 {
+        return Err(ContractError::CustomError {
+            val: "Only single denom deposit is allowed".to_string(),
+        });
+    }
+    // Get proposal latest status
+    let mut prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    let status = prop.current_status(&env.block);
 
-    use super::*;
+    // Validate sent tokens are the currect denom
+    if prop.token_denom != info.funds[0].denom {
+        return Err(ContractError::IncorrectDenomDeposit {});
+    };
 
-    const OWNER: &str = "admin0001";
+    // only Open or Pending Proposals are eligible for deposit
 
-    pub fn mock_dependencies1() -> OwnedDeps<MockStorage, MockApi, MockQuerier, FuryQuery> {
-        OwnedDeps {
-            storage: MockStorage::default(),
-            api: MockApi::default(),
-            querier: MockQuerier::default(),
-            custom_query_type: PhantomData,
-        }
+    if [
+        Status::Executed,
+        Status::Rejected,
+        Status::Passed,
+        Status::Revealing,
+        Status::Cancelled,
+        Status::ExecutionFailed,
+    ]
+    .iter()
+    .any(|x| *x == status)
+    {
+        return Err(ContractError::CannotDeposit {});
     }
 
-    #[test]
-    fn proper_initialization() {
-        let mut deps = mock_dependencies1();
+    // Get user deposit info for the proposal and update the deposit data
 
-        // Empty locking contract address should not be permitted
-        let msg = InstantiateMsg {
-            threshold: Threshold::ThresholdQuorum {
-                threshold: Decimal::one(),
-                quorum: Decimal::one(),
-            },
-            locking_contract: Addr::unchecked(""),
-        };
-        let result =
-            instantiate(deps.as_mut(), mock_env(), mock_info("sender", &[]), msg).unwrap_err();
-        match result {
-            ContractError::Std(StdError::GenericErr { .. }) => {}
-            e => panic!("{:?}", e),
-        };
+    let mut deposit_info = match VOTERDEPOSIT.may_load(deps.storage, (proposal_id, &info.sender))? {
+        Some(record) => record,
+        None => vec![],
+    };
+    let deposit_amount = info.funds[0].amount.u128();
+    match deposit_info
+        .iter_mut()
+        .find(|coin| coin.denom == info.funds[0].denom)
+    {
+        Some(current_deposit_coin) => current_deposit_coin.amount += info.funds[0].amount,
+        None => deposit_info.push(info.funds[0].clone()),
+    }
 
-        const OWNER: &str = "admin001";
-        let _addr1 = Addr::unchecked("addr1");
-        let _addr2 = Addr::unchecked("addr2");
-        let info = mock_info(OWNER, &[]);
+    prop.deposit.push(info.funds[0].clone());
+    prop.current_deposit += deposit_amount;
 
-        let not_acceptable_msg1 = InstantiateMsg {
-            threshold: Threshold::AbsoluteCount { weight: 10 },
-            locking_contract: Addr::unchecked("locking_contract"),
-        };
+    if prop.status == Status::Pending && prop.can_open() {
+        open_proposal(deps.storage, &env, &mut prop)?;
+    }
 
-        let not_acceptable_msg2 = InstantiateMsg {
-            threshold: Threshold::AbsolutePercentage {
-                percentage: Decimal::percent(50),
-            },
-            locking_contract: Addr::unchecked("locking_contract"),
-        };
+    VOTERDEPOSIT.save(deps.storage, (proposal_id, &info.sender), &deposit_info)?;
+    DEPOSITREVISION.save(deps.storage, (proposal_id, &info.sender), &prop.revision)?;
+    update_app_stats(deps.storage, prop.app_mapping_id, |stats| {
+        stats.lock_deposit(deposit_amount)
+    })?;
+    save_proposal(deps.storage, proposal_id, &prop)?;
 
-        let expected_msg = InstantiateMsg {
-            threshold: Threshold::ThresholdQuorum {
-                threshold: Decimal::percent(50),
-                quorum: Decimal::percent(33),
-            },
-            locking_contract: Addr::unchecked("locking_contract"),
-        };
+    Ok(Response::new()
+        .add_attribute("action", "deposit")
+        .add_attribute("depositor", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string()))
+}
 
-        let res1 = instantiate(deps.as_mut(), mock_env(), info.clone(), not_acceptable_msg1);
-        assert_eq!(res1, Err(ContractError::AbsoluteCountNotAccepted {}));
+/// moves a pending proposal to Open, recording how long it took to get there
+fn open_proposal(storage: &mut dyn Storage, env: &Env, prop: &mut Proposal) -> StdResult<()> {
+    let deposit_time = env.block.time.seconds() - prop.start_time.seconds();
+    update_period_stats(storage, prop.app_mapping_id, env.block.time, |stats| {
+        stats.record_deposit_time(deposit_time)
+    })?;
+    prop.status = Status::Open;
+    Ok(())
+}
 
-        let res2 = instantiate(deps.as_mut(), mock_env(), info.clone(), not_acceptable_msg2);
-        assert_eq!(res2, Err(ContractError::AbsolutePercentageNotAccepted {}));
+pub fn execute_sponsor(
+    deps: DepsMut<FuryQuery>,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response<FuryMessages>, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::CustomError {
+            val: "Funds deposit not allowed".to_string(),
+        });
+    }
+    let mut prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    if prop.current_status(&env.block) != Status::Pending {
+        return Err(ContractError::NotPending {});
+    }
+    let sponsorship = prop
+        .sponsorship
+        .ok_or(ContractError::SponsorshipNotRequired {})?;
+    if prop.proposer == info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if prop.sponsors.contains(&info.sender) {
+        return Err(ContractError::AlreadySponsored {});
+    }
+    let balance = query_vtoken_balance(
+        deps.as_ref(),
+        &prop.token_denom,
+        &info.sender,
+        env.block.height,
+    )?;
+    if balance < sponsorship.min_balance {
+        return Err(ContractError::InsufficientSponsorBalance {
+            min: sponsorship.min_balance,
+        });
+    }
+    prop.sponsors.push(info.sender.clone());
+    if prop.can_open() {
+        open_proposal(deps.storage, &env, &mut prop)?;
+    }
+    save_proposal(deps.storage, proposal_id, &prop)?;
 
-        let res3 = instantiate(deps.as_mut(), mock_env(), info.clone(), expected_msg);
-        assert_ne!(res3, Err(ContractError::AbsoluteCountNotAccepted {}));
-        assert_ne!(res3, Err(ContractError::AbsolutePercentageNotAccepted {}));
-        assert_ne!(res3, Err(ContractError::InvalidThreshold {}));
-        assert_ne!(res3, Err(ContractError::ZeroQuorumThreshold {}));
-        assert_ne!(res3, Err(ContractError::UnreachableQuorumThreshold {}));
+    Ok(Response::new()
+        .add_attribute("action", "sponsor")
+        .add_attribute("sponsor", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("status", format!("{:?}", prop.status)))
+}
+
+pub fn execute_unsponsor(
+    deps: DepsMut<FuryQuery>,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response<FuryMessages>, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::CustomError {
+            val: "Funds deposit not allowed".to_string(),
+        });
     }
-    // Propose Testcase
-    #[test]
-    fn test_propose() {
-        let mut deps = mock_dependencies1();
-        //let mut deps2=mock_dependencies1();
-        let info = mock_info(OWNER, &[]);
-        let msgs_com = vec![
-            FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 },
-            FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 34 },
-        ];
+    let mut prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    if prop.current_status(&env.block) != Status::Pending {
+        return Err(ContractError::NotPending {});
+    }
+    let index = prop
+        .sponsors
+        .iter()
+        .position(|sponsor| *sponsor == info.sender)
+        .ok_or(ContractError::NotSponsor {})?;
+    prop.sponsors.remove(index);
+    save_proposal(deps.storage, proposal_id, &prop)?;
 
-        let propose_1 = Propose {
-            title: "propose".to_string(),
-            description: "test_propose".to_string(),
-            msgs: msgs_com,
-            // note: we ignore API-spec'd earliest if passed, always opens immediately
-            latest: Some(Expiration::Never {}),
-            app_id_param: 33,
-        };
+    Ok(Response::new()
+        .add_attribute("action", "unsponsor")
+        .add_attribute("sponsor", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string()))
+}
 
-        //let msgs_length=msgs_com.len();
-        let k = execute_propose(deps.as_mut(), mock_env(), info.clone(), propose_1);
-        assert_eq!(k, Err(ContractError::ExtraMessages {}));
-        let msgs_2: Vec<FuryMessages> = vec![];
-        let propose_2 = Propose {
-            title: "propose".to_string(),
-            description: "test_propose".to_string(),
-            msgs: msgs_2,
-            // note: we ignore API-spec'd earliest if passed, always opens immediately
-            latest: Some(Expiration::Never {}),
-            app_id_param: 33,
-        };
-        let f = execute_propose(deps.as_mut(), mock_env(), info, propose_2);
-        assert_eq!(f, Err(ContractError::NoMessage {}));
+pub fn execute_amend_proposal(
+    deps: DepsMut<FuryQuery>,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+    title: Option<String>,
+    description: Option<String>,
+    msgs: Option<Vec<FuryMessages>>,
+) -> Result<Response<FuryMessages>, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::CustomError {
+            val: "Funds deposit not allowed".to_string(),
+        });
     }
+    let mut prop = PROPOSALS.load(deps.storage, proposal_id)?;
 
-    //Execute Testcase
-    #[test]
-    fn test_execute() {
-        let mut deps = mock_dependencies1();
-        let info = mock_info(OWNER, &[]);
-        let ts = cosmwasm_std::Timestamp::from_nanos(1_655_745_339);
-        let a = Uint128::from(123u128);
-        pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
-        let id = next_id(&mut deps.storage).unwrap();
-        let mut prop = Proposal {
-            title: "prop".to_string(),
-            start_time: ts,
-            description: "test prop".to_string(),
-            start_height: 43,
-            expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
-            msgs: vec![FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
-            status: Status::Passed,
-            duration: Duration::Time(40),
-            threshold: Threshold::ThresholdQuorum {
-                threshold: Decimal::percent(50),
-                quorum: Decimal::percent(33),
-            },
-            total_weight: 14,
-            votes: Votes {
-                yes: 32,
-                no: 24,
+    // only the proposer can amend, and only before voting starts
+    if prop.proposer != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if prop.current_status(&env.block) != Status::Pending {
+        return Err(ContractError::NotPending {});
+    }
+
+    if let Some(msgs) = msgs {
+        // options of a multiple choice proposal cannot be amended
+        if prop.is_multiple_choice() {
+            return Err(ContractError::ExtraMessages {});
+        }
+        if msgs.is_empty() {
+            return Err(ContractError::NoMessage {});
+        }
+        if msgs.len() > 1 {
+            return Err(ContractError::ExtraMessages {});
+        }
+        validate_participating_msg(deps.as_ref(), &msgs[0], &prop.app_ids())?;
+        let cfg = CONFIG.load(deps.storage)?;
+        let category = strictest_category(deps.as_ref(), &cfg, &[&msgs])?;
+        if category.map(|category| category.name) != prop.category {
+            return Err(ContractError::CategoryChanged {});
+        }
+        if prop.optimistic.is_some() {
+            let settings = APPSETTINGS
+                .may_load(deps.storage, prop.app_mapping_id)?
+                .unwrap_or_default();
+            let optimistic = settings
+                .optimistic
+                .ok_or(ContractError::OptimisticNotEnabled {})?;
+            validate_optimistic_msgs(&optimistic, &msgs)?;
+        }
+
+        // the stats of the creation period count the messages of the amended proposal
+        let old_kinds = prop
+            .msgs
+            .iter()
+            .map(msg_kind)
+            .collect::<StdResult<Vec<_>>>()?;
+        let new_kinds = msgs.iter().map(msg_kind).collect::<StdResult<Vec<_>>>()?;
+        update_period_stats(
+            deps.storage,
+            prop.app_mapping_id,
+            prop.start_time,
+            |stats| {
+                for kind in old_kinds {
+                    if let Some(count) = stats.msg_counts.get_mut(&kind) {
+                        *count -= 1;
+                        if *count == 0 {
+                            stats.msg_counts.remove(&kind);
+                        }
+                    }
+                }
+                for kind in new_kinds {
+                    *stats.msg_counts.entry(kind).or_default() += 1;
+                }
+            },
+        )?;
+        prop.msgs = msgs;
+    }
+    if let Some(title) = title {
+        prop.title = title;
+    }
+    if let Some(description) = description {
+        prop.description = description;
+    }
+
+    prop.revision += 1;
+    let content_hash = prop.content_hash()?;
+    let mut revisions = PROPOSALREVISIONS
+        .may_load(deps.storage, proposal_id)?
+        .unwrap_or_default();
+    revisions.push(ProposalRevision {
+        revision: prop.revision,
+        content_hash: content_hash.clone(),
+        height: env.block.height,
+        time: env.block.time,
+    });
+    PROPOSALREVISIONS.save(deps.storage, proposal_id, &revisions)?;
+    save_proposal(deps.storage, proposal_id, &prop)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "amend_proposal")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("revision", prop.revision.to_string())
+        .add_attribute("content_hash", content_hash))
+}
+
+pub fn execute_withdraw_deposit(
+    deps: DepsMut<FuryQuery>,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response<FuryMessages>, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::CustomError {
+            val: "Funds deposit not allowed".to_string(),
+        });
+    }
+    let mut prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    if prop.current_status(&env.block) != Status::Pending {
+        return Err(ContractError::NotPending {});
+    }
+
+    // depositors can only back out of an amendment made after their deposit, the proposer's
+    // deposit stays with the proposal they amended
+    if prop.proposer == info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    let deposit_revision = DEPOSITREVISION
+        .may_load(deps.storage, (proposal_id, &info.sender))?
+        .unwrap_or_default();
+    if deposit_revision >= prop.revision {
+        return Err(ContractError::NotAmended {});
+    }
+
+    let deposit_info = VOTERDEPOSIT
+        .may_load(deps.storage, (proposal_id, &info.sender))?
+        .ok_or(ContractError::NoDeposit {})?;
+    let withdrawn = denom_amount(&deposit_info, &prop.token_denom);
+    prop.current_deposit = prop.current_deposit.saturating_sub(withdrawn);
+    // the deposits of a proposal are not kept per depositor, the withdrawn amount comes off the
+    // latest ones
+    let denom = prop.token_denom.clone();
+    let mut remaining = Uint128::from(withdrawn);
+    for coin in prop.deposit.iter_mut().rev() {
+        if coin.denom == denom {
+            let taken = coin.amount.min(remaining);
+            coin.amount -= taken;
+            remaining -= taken;
+        }
+    }
+    prop.deposit.retain(|coin| !coin.amount.is_zero());
+    update_app_stats(deps.storage, prop.app_mapping_id, |stats| {
+        stats.refund_deposit(withdrawn)
+    })?;
+
+    VOTERDEPOSIT.remove(deps.storage, (proposal_id, &info.sender));
+    DEPOSITREVISION.remove(deps.storage, (proposal_id, &info.sender));
+    save_proposal(deps.storage, proposal_id, &prop)?;
+
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: deposit_info,
+        })
+        .add_attribute("action", "withdraw_deposit")
+        .add_attribute("depositor", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string()))
+}
+
+pub fn execute_refund(
+    mut deps: DepsMut<FuryQuery>,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response<FuryMessages>, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::CustomError {
+            val: "Funds deposit not allowed".to_string(),
+        });
+    }
+    // Get proposal status
+    let mut prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    let status = prop.current_status(&env.block);
+
+    // Open and Pending proposal status are not eligible for refund
+    if status == Status::Pending {
+        return Err(ContractError::PendingProposal {});
+    }
+
+    if status == Status::Open || status == Status::Revealing {
+        return Err(ContractError::OpenProposal {});
+    }
+
+    // If no reposit for the proposal, a slash that took the whole deposit removed it as well
+    if !VOTERDEPOSIT.has(deps.storage, (proposal_id, &info.sender)) {
+        if status == Status::Rejected && prop.is_slashed && prop.check_vetoed(&env.block) {
+            return Err(ContractError::SlashedProposal {});
+        }
+        return Err(ContractError::NoDeposit {});
+    }
+
+    // a rejected proposal loses a share of its deposits, the remainder stays refundable
+    let mut burned = 0;
+    if status == Status::Rejected {
+        if prop.check_vetoed(&env.block) {
+            if !prop.is_slashed {
+                burned = slash_proposal(deps.branch(), &env, proposal_id, &mut prop)?;
+            }
+        } else {
+            record_outcome(
+                deps.storage,
+                &env.block,
+                proposal_id,
+                &mut prop,
+                ProposalOutcome::Rejected,
+            )?;
+            burned = slash_rejected(deps.storage, &env.block, proposal_id, &mut prop)?;
+        }
+    }
+
+    // get sender deposit info, the slash may have consumed all of it
+    let deposit_info = VOTERDEPOSIT
+        .may_load(deps.storage, (proposal_id, &info.sender))?
+        .unwrap_or_default();
+
+    //// need to update current_deposit////////
+
+    VOTERDEPOSIT.remove(deps.storage, (proposal_id, &info.sender));
+
+    let refunded = denom_amount(&deposit_info, &prop.token_denom);
+    update_app_stats(deps.storage, prop.app_mapping_id, |stats| {
+        stats.refund_deposit(refunded)
+    })?;
+    save_proposal(deps.storage, proposal_id, &prop)?;
+
+    let refund = if deposit_info.is_empty() {
+        None
+    } else {
+        Some(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: deposit_info,
+        })
+    };
+    Ok(Response::new()
+        .add_messages(refund)
+        .add_messages(burn_msg(&env, &prop, burned))
+        .add_attribute("action", "refund")
+        .add_attribute("sender", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("burned", burned.to_string())
+        .add_attribute("returned", refunded.to_string()))
+}
+
+pub fn execute_slash(
+    deps: DepsMut<FuryQuery>,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response<FuryMessages>, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::CustomError {
+            val: "Funds deposit not allowed".to_string(),
+        });
+    }
+    let mut prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    let status = prop.current_status(&env.block);
+
+    //Check if proposal is rejected
+    if status != Status::Rejected {
+        return Err(ContractError::NotRejected {});
+    }
+
+    // check if proposal is vetoed
+    if !prop.check_vetoed(&env.block) {
+        return Err(ContractError::ProposalNotVetoed {});
+    }
+
+    //check if proposal already slashed
+    if prop.is_slashed {
+        return Err(ContractError::AlreadySlashed {});
+    }
+
+    let burned = slash_proposal(deps, &env, proposal_id, &mut prop)?;
+
+    Ok(Response::new()
+        .add_messages(burn_msg(&env, &prop, burned))
+        .add_attribute("action", "Slash")
+        .add_attribute("trigger_address", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("burned", burned.to_string())
+        .add_attribute("returned", "0"))
+}
+
+/// marks a vetoed proposal as slashed and returns the amount of its deposits to burn, the
+/// voter rewards pool keeps its share of the slashed deposits
+fn slash_proposal(
+    deps: DepsMut<FuryQuery>,
+    env: &Env,
+    proposal_id: u64,
+    prop: &mut Proposal,
+) -> StdResult<u128> {
+    let settings = APPSETTINGS
+        .may_load(deps.storage, prop.app_mapping_id)?
+        .unwrap_or_default();
+    let fraction = settings.slashing.unwrap_or_default().vetoed;
+    let slashed = slash_deposits(deps.storage, &env.block, proposal_id, prop, fraction)?;
+    let pooled = settings.voter_rewards.map_or(0, |rewards| {
+        (rewards.pool_share * Uint128::from(slashed)).u128()
+    });
+    if pooled > 0 {
+        let pool = VOTERREWARDPOOL
+            .may_load(deps.storage, prop.app_mapping_id)?
+            .unwrap_or_default();
+        VOTERREWARDPOOL.save(deps.storage, prop.app_mapping_id, &(pool + pooled))?;
+    }
+    record_outcome(
+        deps.storage,
+        &env.block,
+        proposal_id,
+        prop,
+        ProposalOutcome::Vetoed,
+    )?;
+
+    save_proposal(deps.storage, proposal_id, prop)?;
+    Ok(slashed - pooled)
+}
+
+/// slashes a rejected proposal that was not vetoed by the fraction configured for the reason it
+/// failed, returns the amount to burn
+fn slash_rejected(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    proposal_id: u64,
+    prop: &mut Proposal,
+) -> StdResult<u128> {
+    if prop.is_slashed {
+        return Ok(0);
+    }
+    let fraction = slash_fraction(storage, block, prop)?;
+    if fraction.is_zero() {
+        return Ok(0);
+    }
+    slash_deposits(storage, block, proposal_id, prop, fraction)
+}
+
+/// share of the deposits slashed for a rejected proposal
+fn slash_fraction(storage: &dyn Storage, block: &BlockInfo, prop: &Proposal) -> StdResult<Decimal> {
+    let slashing = APPSETTINGS
+        .may_load(storage, prop.app_mapping_id)?
+        .and_then(|settings| settings.slashing)
+        .unwrap_or_default();
+    let fraction = if prop.check_vetoed(block) {
+        slashing.vetoed
+    } else if !prop.min_deposit_reached() {
+        slashing.deposit_missed
+    } else if prop.quorum_missed() {
+        slashing.quorum_missed
+    } else {
+        Decimal::zero()
+    };
+    Ok(fraction)
+}
+
+/// slashes `fraction` of the deposits of a proposal and marks it as slashed, every depositor
+/// keeps the remainder of their deposit refundable and the proposer's slash cooldown starts.
+/// Returns the amount slashed.
+fn slash_deposits(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    proposal_id: u64,
+    prop: &mut Proposal,
+    fraction: Decimal,
+) -> StdResult<u128> {
+    let kept_share = Decimal::one() - fraction;
+    let mut kept_total = 0;
+    let deposits = VOTERDEPOSIT
+        .prefix(proposal_id)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (depositor, funds) in deposits {
+        let deposited = denom_amount(&funds, &prop.token_denom);
+        let kept = kept_share * Uint128::from(deposited);
+        let mut remainder: Vec<Coin> = funds
+            .into_iter()
+            .filter(|coin| coin.denom != prop.token_denom)
+            .collect();
+        if !kept.is_zero() {
+            remainder.push(Coin {
+                amount: kept,
+                denom: prop.token_denom.clone(),
+            });
+        }
+        if remainder.is_empty() {
+            VOTERDEPOSIT.remove(storage, (proposal_id, &depositor));
+        } else {
+            VOTERDEPOSIT.save(storage, (proposal_id, &depositor), &remainder)?;
+        }
+
+        kept_total += kept.u128();
+        let mut stats = VOTERSTATS
+            .may_load(storage, &depositor)?
+            .unwrap_or_default();
+        stats.deposits_slashed += deposited - kept.u128();
+        VOTERSTATS.save(storage, &depositor, &stats)?;
+    }
+    // every depositor keeps the rounded down share, the rounding remainders are slashed too
+    let slashed = prop.current_deposit.saturating_sub(kept_total);
+    update_app_stats(storage, prop.app_mapping_id, |stats| {
+        stats.slash_deposit(slashed)
+    })?;
+    let proposer = Addr::unchecked(&prop.proposer);
+    PROPOSERSLASHED.save(storage, (prop.app_mapping_id, &proposer), &block.time)?;
+    prop.is_slashed = true;
+    Ok(slashed)
+}
+
+/// message burning `amount` of the gov token held for a proposal, None if there is nothing to burn
+fn burn_msg(env: &Env, prop: &Proposal, amount: u128) -> Option<FuryMessages> {
+    if amount == 0 {
+        return None;
+    }
+    Some(FuryMessages::MsgBurnGovTokensForApp {
+        app_id: prop.app_mapping_id,
+        amount: Coin {
+            amount: Uint128::from(amount),
+            denom: prop.token_denom.clone(),
+        },
+        from: env.contract.address.to_string(),
+    })
+}
+
+pub fn execute_claim_voter_rewards(
+    deps: DepsMut<FuryQuery>,
+    info: MessageInfo,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<Response<FuryMessages>, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::CustomError {
+            val: "Funds deposit not allowed".to_string(),
+        });
+    }
+    let (rewarded, pending) =
+        pending_voter_rewards(deps.as_ref(), &info.sender, start_after, limit)?;
+    if rewarded.is_empty() {
+        return Err(ContractError::NoVoterRewards {});
+    }
+    for proposal_id in rewarded {
+        REWARDSCLAIMED.save(deps.storage, (proposal_id, &info.sender), &Empty {})?;
+    }
+
+    let mut response = Response::new()
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: pending.rewards,
+        })
+        .add_attribute("action", "claim_voter_rewards")
+        .add_attribute("voter", info.sender);
+    if let Some(last_proposal_id) = pending.last_proposal_id {
+        response = response.add_attribute("last_proposal_id", last_proposal_id.to_string());
+    }
+    Ok(response)
+}
+
+/// unclaimed rewards of `voter`, pro rata to the weight of the voter's ballot, from no more than
+/// `limit` ballots after `start_after`, along with the proposals they are paid for
+fn pending_voter_rewards(
+    deps: Deps<FuryQuery>,
+    voter: &Addr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<(Vec<u64>, VoterRewardsResponse)> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let ballots = VOTERBALLOTS
+        .prefix(voter)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    let last_proposal_id = match ballots.last() {
+        Some((proposal_id, _)) if ballots.len() == limit => Some(*proposal_id),
+        _ => None,
+    };
+
+    let mut rewarded = vec![];
+    let mut rewards = vec![];
+    for (proposal_id, ballot) in ballots {
+        let proposal_rewards = match PROPOSALREWARDS.may_load(deps.storage, proposal_id)? {
+            Some(proposal_rewards) => proposal_rewards,
+            None => continue,
+        };
+        if REWARDSCLAIMED.has(deps.storage, (proposal_id, voter)) {
+            continue;
+        }
+        let amount = Uint128::from(proposal_rewards.amount)
+            .multiply_ratio(ballot.weight, proposal_rewards.total_weight);
+        if !amount.is_zero() {
+            rewarded.push(proposal_id);
+            rewards.push(Coin {
+                amount,
+                denom: proposal_rewards.denom,
+            });
+        }
+    }
+    let pending = VoterRewardsResponse {
+        rewards: merge_coins(rewards),
+        last_proposal_id,
+    };
+    Ok((rewarded, pending))
+}
+
+/// sums coins of the same denom
+fn merge_coins(coins: impl IntoIterator<Item = Coin>) -> Vec<Coin> {
+    let mut totals = BTreeMap::<String, Uint128>::new();
+    for coin in coins {
+        *totals.entry(coin.denom).or_default() += coin.amount;
+    }
+    totals
+        .into_iter()
+        .map(|(denom, amount)| Coin { denom, amount })
+        .collect()
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps<FuryQuery>, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Threshold { proposal_id } => to_json_binary(&query_threshold(deps, proposal_id)?),
+        QueryMsg::Proposal { proposal_id } => {
+            to_json_binary(&query_proposal_detailed(deps, env, proposal_id)?)
+        }
+        QueryMsg::Vote { proposal_id, voter } => {
+            to_json_binary(&query_vote(deps, proposal_id, voter)?)
+        }
+        QueryMsg::ListProposals { start_after, limit } => {
+            to_json_binary(&list_proposals(deps, env, start_after, limit)?)
+        }
+        QueryMsg::ReverseProposals {
+            start_before,
+            limit,
+        } => to_json_binary(&reverse_proposals(deps, env, start_before, limit)?),
+        QueryMsg::ListVotes {
+            proposal_id,
+            start_after,
+            limit,
+        } => to_json_binary(&list_votes(deps, proposal_id, start_after, limit)?),
+        QueryMsg::ListAppProposal {
+            app_id,
+            start_after,
+            limit,
+            status,
+        } => to_json_binary(&get_proposals_by_app(
+            deps,
+            env,
+            app_id,
+            start_after,
+            limit,
+            status,
+        )?),
+        QueryMsg::AppAllUpData { app_id } => to_json_binary(&get_all_up_info_by_app(deps, app_id)?),
+        QueryMsg::Tally { proposal_id } => to_json_binary(&query_tally(deps, env, proposal_id)?),
+        QueryMsg::AppSettings { app_id } => to_json_binary(&query_app_settings(deps, app_id)?),
+        QueryMsg::ProposalRevisions { proposal_id } => {
+            to_json_binary(&query_proposal_revisions(deps, proposal_id)?)
+        }
+        QueryMsg::EffectiveWeight {
+            proposal_id,
+            voter,
+            conviction,
+        } => to_json_binary(&query_effective_weight(
+            deps,
+            proposal_id,
+            voter,
+            conviction,
+        )?),
+        QueryMsg::Categories {} => {
+            to_json_binary(&CATEGORIES.may_load(deps.storage)?.unwrap_or_default())
+        }
+        QueryMsg::Cancellation { proposal_id } => {
+            to_json_binary(&CANCELLATIONS.may_load(deps.storage, proposal_id)?)
+        }
+        QueryMsg::VoteNonce { voter } => to_json_binary(&query_vote_nonce(deps, voter)?),
+        QueryMsg::ListVotesByVoter {
+            voter,
+            app_id,
+            start_after,
+            limit,
+        } => to_json_binary(&list_votes_by_voter(
+            deps,
+            voter,
+            app_id,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::VoterStats { voter } => to_json_binary(&query_voter_stats(deps, voter)?),
+        QueryMsg::GovStats { app_id, from, to } => {
+            to_json_binary(&query_gov_stats(deps, app_id, from, to)?)
+        }
+        QueryMsg::VoteHistory { proposal_id, voter } => {
+            to_json_binary(&query_vote_history(deps, proposal_id, voter)?)
+        }
+        QueryMsg::ExecutionResults { proposal_id } => to_json_binary(
+            &EXECUTIONRESULTS
+                .may_load(deps.storage, proposal_id)?
+                .unwrap_or_default(),
+        ),
+        QueryMsg::ValidateProposal { propose, proposer } => {
+            to_json_binary(&query_validate_proposal(deps, env, *propose, proposer)?)
+        }
+        QueryMsg::PendingVoterRewards {
+            voter,
+            start_after,
+            limit,
+        } => {
+            let voter = deps.api.addr_validate(&voter)?;
+            let (_, pending) = pending_voter_rewards(deps, &voter, start_after, limit)?;
+            to_json_binary(&pending)
+        }
+
+        _ => panic!("Not implemented"),
+    }
+}
+
+/// dry run of `execute_propose` without funds, collects every problem instead of stopping at
+/// the first one and reports the deposit the proposal needs
+fn query_validate_proposal(
+    deps: Deps<FuryQuery>,
+    env: Env,
+    propose: Propose,
+    proposer: String,
+) -> StdResult<ValidateProposalResponse> {
+    let mut problems = vec![];
+    let proposer = match deps.api.addr_validate(&proposer) {
+        Ok(proposer) => Some(proposer),
+        Err(err) => {
+            problems.push(problem(err));
+            None
+        }
+    };
+
+    let (found, draft) = validate_propose(deps, &env, &propose, proposer.as_ref(), None)?;
+    problems.extend(found);
+    let mut min_deposit = None;
+    let mut submission_fees = vec![];
+    if let Some(draft) = draft {
+        if !draft.gov_token_denom.is_empty() {
+            min_deposit = Some(Coin {
+                amount: draft.min_deposit_amount,
+                denom: draft.gov_token_denom,
+            });
+        }
+        if let Some(fee) = draft.settings.submission_fee {
+            submission_fees = fee.amounts;
+        }
+    }
+
+    Ok(ValidateProposalResponse {
+        valid: problems.is_empty(),
+        problems: problems
+            .into_iter()
+            .map(|problem| ProposalProblem {
+                option: problem.option.map(|index| index as u32),
+                msg_index: problem.msg_index.map(|index| index as u32),
+                error: problem.err.to_string(),
+            })
+            .collect(),
+        min_deposit,
+        submission_fees,
+    })
+}
+
+/// threshold the proposal is tallied with, e.g. the one of its category
+fn query_threshold(deps: Deps<FuryQuery>, proposal_id: u64) -> StdResult<ThresholdResponse> {
+    let prop = PROPOSALS.load(deps.storage, proposal_id)?;
+
+    Ok(prop.threshold.to_response(prop.total_weight))
+}
+
+fn query_proposal_detailed(
+    deps: Deps<FuryQuery>,
+    env: Env,
+    id: u64,
+) -> StdResult<ProposalResponseTotal> {
+    let prop = PROPOSALS.load(deps.storage, id)?;
+    let status = prop.current_status(&env.block);
+    Ok(ProposalResponseTotal {
+        id,
+        title: prop.title,
+        description: prop.description,
+        msgs: prop.msgs,
+        status,
+        start_time: prop.start_time,
+        expires: prop.expires,
+        votes: prop.votes,
+        duration: prop.duration,
+        start_height: prop.start_height,
+        threshold: prop.threshold,
+        proposer: prop.proposer,
+        token_denom: prop.token_denom,
+        total_weight: prop.total_weight,
+        current_deposit: prop.current_deposit,
+        options: prop.options,
+        option_votes: prop.option_votes,
+        revision: prop.revision,
+        optimistic: prop.optimistic,
+        escalated: prop.escalated,
+        expedited: prop.expedited,
+        category: prop.category,
+        timelock: prop.timelock,
+        execution_error: prop.execution_error,
+        depends_on: prop.depends_on,
+        execute_at: prop.execute_at,
+        participants: prop.participants,
+        required_apps: prop.required_apps,
+        sponsors_required: prop
+            .sponsorship
+            .map_or(0, |sponsorship| sponsorship.sponsors_required),
+        sponsors: prop.sponsors,
+        submission_fee: prop.submission_fee,
+    })
+}
+
+fn query_tally(deps: Deps<FuryQuery>, env: Env, proposal_id: u64) -> StdResult<TallyResponse> {
+    let prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    let status = prop.current_status(&env.block);
+    let winning_option = prop.winning_option().map(|index| index as u32);
+    Ok(TallyResponse {
+        proposal_id,
+        status,
+        tally_mode: prop.tally_mode,
+        total_weight: prop.total_weight,
+        votes: prop.votes,
+        raw_votes: prop.raw_votes,
+        conviction: prop.conviction,
+        option_votes: prop.option_votes,
+        winning_option,
+    })
+}
+
+fn query_effective_weight(
+    deps: Deps<FuryQuery>,
+    proposal_id: u64,
+    voter: String,
+    conviction: Option<u8>,
+) -> StdResult<EffectiveWeightResponse> {
+    let voter = deps.api.addr_validate(&voter)?;
+    let prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    let raw_weight = query_snapshot_power(deps, &prop, &voter)?;
+    let multiplier = conviction_multiplier(deps, &prop, &voter, conviction)
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+    Ok(EffectiveWeightResponse {
+        raw_weight,
+        multiplier,
+        effective_weight: prop.tally_mode.effective_weight(raw_weight) * multiplier as u128,
+    })
+}
+
+fn query_proposal_revisions(
+    deps: Deps<FuryQuery>,
+    proposal_id: u64,
+) -> StdResult<Vec<ProposalRevision>> {
+    PROPOSALS.load(deps.storage, proposal_id)?;
+    Ok(PROPOSALREVISIONS
+        .may_load(deps.storage, proposal_id)?
+        .unwrap_or_default())
+}
+
+fn query_app_settings(deps: Deps<FuryQuery>, app_id: u64) -> StdResult<AppSettings> {
+    Ok(APPSETTINGS
+        .may_load(deps.storage, app_id)?
+        .unwrap_or_default())
+}
+
+fn query_vote_nonce(deps: Deps<FuryQuery>, voter: String) -> StdResult<u64> {
+    let voter = deps.api.addr_validate(&voter)?;
+    Ok(VOTENONCES
+        .may_load(deps.storage, &voter)?
+        .unwrap_or_default())
+}
+
+fn query_vote_history(
+    deps: Deps<FuryQuery>,
+    proposal_id: u64,
+    voter: String,
+) -> StdResult<Vec<VoteRecord>> {
+    let voter = deps.api.addr_validate(&voter)?;
+    Ok(VOTEHISTORY
+        .may_load(deps.storage, (proposal_id, &voter))?
+        .unwrap_or_default())
+}
+
+// settings for pagination
+const MAX_LIMIT: u32 = 300;
+const DEFAULT_LIMIT: u32 = 100;
+
+fn list_proposals(
+    deps: Deps<FuryQuery>,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ProposalListResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let proposals = PROPOSALS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|p| map_proposal(&env.block, p))
+        .collect::<StdResult<_>>()?;
+
+    Ok(ProposalListResponse { proposals })
+}
+
+fn get_proposals_by_app(
+    deps: Deps<FuryQuery>,
+    env: Env,
+    app_id: u64,
+    start_after: u32,
+    limit: Option<u32>,
+    status: Option<Status>,
+) -> StdResult<AppProposalResponse> {
+    let mut info = match PROPOSALSBYAPP.may_load(deps.storage, app_id)? {
+        Some(record) => record,
+        None => vec![],
+    };
+
+    let mut all_proposals = vec![];
+    info.reverse();
+    for i in info {
+        let proposal = query_proposal_detailed(deps, env.clone(), i)?;
+        match status {
+            Some(status) => {
+                if status == proposal.status {
+                    all_proposals.push(proposal.clone());
+                }
+            }
+            None => {
+                all_proposals.push(proposal);
+            }
+        }
+    }
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after as usize;
+    let checkpoint = start + limit;
+    let mut proposal = vec![];
+    if !all_proposals.is_empty() {
+        // If the vec len is smaller than start_after, then empty vec
+        // If the checkpoint is >= length, then return all remaining elements
+        // else return the specific elements
+        let length = all_proposals.len();
+        if length <= start {
+            proposal = vec![];
+        } else if checkpoint >= length {
+            proposal = all_proposals[start..].to_vec();
+        } else {
+            proposal = all_proposals[start..checkpoint].to_vec();
+        }
+    }
+    let proposal_length = all_proposals.len();
+
+    Ok(AppProposalResponse {
+        proposals: proposal,
+        proposal_count: proposal_length as u64,
+    })
+}
+
+fn get_all_up_info_by_app(deps: Deps<FuryQuery>, app_id: u64) -> StdResult<AppGovConfig> {
+    // the counters are kept up to date by the execute handlers
+    let mut participation_info = APPGOVCONFIG.load(deps.storage, app_id)?;
+
+    let app_response = query_app_exists(deps, app_id)?;
+    let gov_token_id = app_response.gov_token_id;
+    let total_weight = get_token_supply(deps, app_id, gov_token_id)?;
+    participation_info.current_supply = Uint128::from(total_weight).u128();
+
+    Ok(participation_info)
+}
+
+fn reverse_proposals(
+    deps: Deps<FuryQuery>,
+    env: Env,
+    start_before: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ProposalListResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let end = start_before.map(Bound::exclusive);
+    let props: StdResult<Vec<_>> = PROPOSALS
+        .range(deps.storage, None, end, Order::Descending)
+        .take(limit)
+        .map(|p| map_proposal(&env.block, p))
+        .collect();
+
+    Ok(ProposalListResponse { proposals: props? })
+}
+
+fn map_proposal(
+    block: &BlockInfo,
+    item: StdResult<(u64, Proposal)>,
+) -> StdResult<ProposalResponse> {
+    item.map(|(id, prop)| {
+        let status = prop.current_status(block);
+        let threshold = prop.threshold.to_response(prop.total_weight);
+        ProposalResponse {
+            id,
+            title: prop.title,
+            description: prop.description,
+            msgs: prop.msgs,
+            status,
+            expires: prop.expires,
+            threshold,
+        }
+    })
+}
+
+fn query_vote(deps: Deps<FuryQuery>, proposal_id: u64, voter: String) -> StdResult<VoteResponse> {
+    let voter = deps.api.addr_validate(&voter)?;
+    let ballot = BALLOTS.may_load(deps.storage, (proposal_id, &voter))?;
+    let vote = ballot.map(|b| VoteInfo {
+        proposal_id,
+        voter: voter.into(),
+        vote: b.vote,
+        weight: b.weight,
+        raw_weight: b.raw_weight,
+        option: b.option,
+        memo: b.memo,
+    });
+    Ok(VoteResponse { vote })
+}
+
+fn list_votes(
+    deps: Deps<FuryQuery>,
+    proposal_id: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<VoteListResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+    let votes = BALLOTS
+        .prefix(proposal_id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(addr, ballot)| VoteInfo {
+                proposal_id,
+                voter: addr.into(),
+                vote: ballot.vote,
+                weight: ballot.weight,
+                raw_weight: ballot.raw_weight,
+                option: ballot.option,
+                memo: ballot.memo,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(VoteListResponse { votes })
+}
+
+fn list_votes_by_voter(
+    deps: Deps<FuryQuery>,
+    voter: String,
+    app_id: Option<u64>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<VoteListResponse> {
+    let voter = deps.api.addr_validate(&voter)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let ballots = match app_id {
+        Some(app_id) => VOTERAPPBALLOTS
+            .prefix((&voter, app_id))
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|proposal_id| {
+                let proposal_id = proposal_id?;
+                Ok((
+                    proposal_id,
+                    VOTERBALLOTS.load(deps.storage, (&voter, proposal_id))?,
+                ))
+            })
+            .collect::<StdResult<Vec<_>>>()?,
+        None => VOTERBALLOTS
+            .prefix(&voter)
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?,
+    };
+
+    let mut votes = vec![];
+    for (proposal_id, ballot) in ballots {
+        votes.push(VoteInfo {
+            proposal_id,
+            voter: voter.to_string(),
+            vote: ballot.vote,
+            weight: ballot.weight,
+            raw_weight: ballot.raw_weight,
+            option: ballot.option,
+            memo: ballot.memo,
+        });
+    }
+
+    Ok(VoteListResponse { votes })
+}
+
+fn query_gov_stats(
+    deps: Deps<FuryQuery>,
+    app_id: u64,
+    from: Timestamp,
+    to: Timestamp,
+) -> StdResult<GovStatsResponse> {
+    let start = Bound::inclusive(stats_period(from));
+    let end = Bound::inclusive(stats_period(to));
+    let mut total = PeriodStats::default();
+    for item in
+        PERIODSTATS
+            .prefix(app_id)
+            .range(deps.storage, Some(start), Some(end), Order::Ascending)
+    {
+        let (_, stats) = item?;
+        total.proposals_created += stats.proposals_created;
+        total.proposals_passed += stats.proposals_passed;
+        total.proposals_rejected += stats.proposals_rejected;
+        total.proposals_vetoed += stats.proposals_vetoed;
+        total.turnout_sum += stats.turnout_sum;
+        total.merge_deposit_times(&stats);
+        for (kind, count) in stats.msg_counts {
+            *total.msg_counts.entry(kind).or_default() += count;
+        }
+    }
+
+    let decided = total.proposals_passed + total.proposals_rejected;
+    let rate = |count: u64| {
+        if decided == 0 {
+            Decimal::zero()
+        } else {
+            Decimal::from_ratio(count, decided)
+        }
+    };
+
+    Ok(GovStatsResponse {
+        app_id,
+        proposals_created: total.proposals_created,
+        proposals_decided: decided,
+        pass_rate: rate(total.proposals_passed),
+        reject_rate: rate(total.proposals_rejected),
+        veto_rate: rate(total.proposals_vetoed),
+        average_turnout: if decided == 0 {
+            Decimal::zero()
+        } else {
+            total.turnout_sum / Uint128::from(decided)
+        },
+        median_deposit_time: total.median_deposit_time(),
+        msg_counts: total.msg_counts,
+    })
+}
+
+fn query_voter_stats(deps: Deps<FuryQuery>, voter: String) -> StdResult<VoterStatsResponse> {
+    let voter = deps.api.addr_validate(&voter)?;
+    let stats = VOTERSTATS
+        .may_load(deps.storage, &voter)?
+        .unwrap_or_default();
+
+    // the voter is eligible for the proposals of the apps they voted or proposed in, the ballot
+    // cast for the proposer on creation is not a participation
+    let mut participated = 0;
+    let mut eligible = 0;
+    for item in VOTERAPPSTATS
+        .prefix(&voter)
+        .range(deps.storage, None, None, Order::Ascending)
+    {
+        let (app_id, app_stats) = item?;
+        let proposal_count = APPGOVCONFIG
+            .may_load(deps.storage, app_id)?
+            .unwrap_or_default()
+            .proposal_count;
+        participated += app_stats
+            .proposals_voted
+            .saturating_sub(app_stats.proposals_created);
+        eligible += proposal_count.saturating_sub(app_stats.proposals_created);
+    }
+    let participation_rate = if eligible == 0 {
+        Decimal::zero()
+    } else {
+        Decimal::from_ratio(participated, eligible)
+    };
+
+    Ok(VoterStatsResponse {
+        voter: voter.into(),
+        proposals_voted: stats.proposals_voted,
+        proposals_created: stats.proposals_created,
+        deposits_slashed: stats.deposits_slashed,
+        participation_rate,
+    })
+}
+
+#[entry_point]
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let ver = cw2::get_contract_version(deps.storage)?;
+    // ensure we are migrating from an allowed contract
+    if ver.contract != CONTRACT_NAME {
+        return Err(StdError::generic_err("Can only upgrade from same type").into());
+    }
+    // note: better to do proper semver compare, but string compare *usually* works
+    if ver.version.as_str() > CONTRACT_VERSION {
+        return Err(StdError::generic_err("Cannot upgrade from a newer version").into());
+    }
+
+    // set the new version
+    cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let mut proposals = PROPOSALS
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    backfill_gov_counters(deps.storage, &env.block, &mut proposals)?;
+
+    // active proposals, from the stored status of every proposal
+    for (id, prop) in proposals.iter().filter(|(_, prop)| prop.is_active()) {
+        ACTIVEPROPOSALS.save(deps.storage, (prop.app_mapping_id, *id), &Empty {})?;
+    }
+
+    // proposals created before the raw tally was kept counted raw vtoken balances in `votes`
+    for (id, prop) in proposals.iter_mut() {
+        if prop.raw_votes.total() > 0 || prop.votes.total() == 0 {
+            continue;
+        }
+        prop.raw_votes = prop.votes.clone();
+        PROPOSALS.save(deps.storage, *id, prop)?;
+        let ballots = BALLOTS
+            .prefix(*id)
+            .range(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (voter, mut ballot) in ballots {
+            ballot.raw_weight = ballot.weight;
+            BALLOTS.save(deps.storage, (*id, &voter), &ballot)?;
+        }
+    }
+    backfill_voter_index(deps.storage, &proposals)?;
+
+    Ok(Response::default())
+}
+
+/// rebuilds the ballots by voter and the voter stats from the stored ballots and proposals.
+/// Slashed deposits were not recorded per depositor before and keep their stored value.
+fn backfill_voter_index(storage: &mut dyn Storage, proposals: &[(u64, Proposal)]) -> StdResult<()> {
+    let mut voter_stats: BTreeMap<Addr, VoterStats> = BTreeMap::new();
+    let mut voter_app_stats: BTreeMap<(Addr, u64), VoterStats> = BTreeMap::new();
+    for (_, prop) in proposals {
+        let proposer = Addr::unchecked(&prop.proposer);
+        voter_stats
+            .entry(proposer.clone())
+            .or_default()
+            .proposals_created += 1;
+        voter_app_stats
+            .entry((proposer, prop.app_mapping_id))
+            .or_default()
+            .proposals_created += 1;
+    }
+    let ballots = BALLOTS
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let proposal_apps: BTreeMap<u64, u64> = proposals
+        .iter()
+        .map(|(id, prop)| (*id, prop.app_mapping_id))
+        .collect();
+    for ((proposal_id, voter), ballot) in ballots {
+        VOTERBALLOTS.save(storage, (&voter, proposal_id), &ballot)?;
+        if let Some(app_id) = proposal_apps.get(&proposal_id) {
+            VOTERAPPBALLOTS.save(storage, (&voter, *app_id, proposal_id), &Empty {})?;
+            voter_app_stats
+                .entry((voter.clone(), *app_id))
+                .or_default()
+                .proposals_voted += 1;
+        }
+        voter_stats.entry(voter).or_default().proposals_voted += 1;
+    }
+
+    for (voter, mut stats) in voter_stats {
+        stats.deposits_slashed = VOTERSTATS
+            .may_load(storage, &voter)?
+            .unwrap_or_default()
+            .deposits_slashed;
+        VOTERSTATS.save(storage, &voter, &stats)?;
+    }
+    for ((voter, app_id), stats) in voter_app_stats {
+        VOTERAPPSTATS.save(storage, (&voter, app_id), &stats)?;
+    }
+    Ok(())
+}
+
+/// recomputes the app governance counters that can be derived from the stored proposals, ballots
+/// and deposits. Refunds made before the counters were kept are not known.
+fn backfill_gov_counters(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    proposals: &mut [(u64, Proposal)],
+) -> StdResult<()> {
+    let mut counters: BTreeMap<u64, AppGovConfig> = BTreeMap::new();
+    for (id, prop) in proposals.iter_mut() {
+        let app = match counters.entry(prop.app_mapping_id) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let stored = APPGOVCONFIG
+                    .may_load(storage, prop.app_mapping_id)?
+                    .unwrap_or_default();
+                entry.insert(AppGovConfig {
+                    proposal_count: stored.proposal_count,
+                    current_supply: stored.current_supply,
+                    active_participation_supply: stored.active_participation_supply,
+                    deposits_refunded: stored.deposits_refunded,
+                    deposits_slashed: stored.deposits_slashed,
+                    ..AppGovConfig::default()
+                })
+            }
+        };
+
+        // proposals decided before their outcome was recorded
+        if prop.outcome.is_none() {
+            prop.outcome = match prop.current_status(block) {
+                Status::Executed => Some(ProposalOutcome::Executed),
+                _ if prop.is_slashed => Some(ProposalOutcome::Vetoed),
+                Status::Rejected if prop.check_vetoed(block) => Some(ProposalOutcome::Vetoed),
+                Status::Rejected => Some(ProposalOutcome::Rejected),
+                _ => None,
+            };
+            if prop.is_slashed {
+                // the deposits of a fully slashed proposal were burned but kept in storage
+                let depositors = VOTERDEPOSIT
+                    .prefix(*id)
+                    .keys(storage, None, None, Order::Ascending)
+                    .collect::<StdResult<Vec<_>>>()?;
+                for depositor in depositors {
+                    VOTERDEPOSIT.remove(storage, (*id, &depositor));
+                }
+                app.deposits_slashed += prop.current_deposit;
+            }
+            if prop.outcome.is_some() {
+                PROPOSALS.save(storage, *id, prop)?;
+            }
+        }
+        if let Some(outcome) = prop.outcome {
+            app.record_outcome(None, outcome);
+        }
+    }
+
+    let proposal_apps: BTreeMap<u64, (u64, String)> = proposals
+        .iter()
+        .map(|(id, prop)| (*id, (prop.app_mapping_id, prop.token_denom.clone())))
+        .collect();
+    let ballots = BALLOTS
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut voters = BTreeSet::new();
+    for (proposal_id, voter) in ballots {
+        if let Some((app_id, _)) = proposal_apps.get(&proposal_id) {
+            let app = counters.entry(*app_id).or_default();
+            app.votes_cast += 1;
+            if voters.insert((*app_id, voter.clone())) {
+                app.unique_voters += 1;
+                APPVOTERS.save(storage, (*app_id, &voter), &Empty {})?;
+            }
+        }
+    }
+    let deposits = VOTERDEPOSIT
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for ((proposal_id, _), amount) in deposits {
+        if let Some((app_id, denom)) = proposal_apps.get(&proposal_id) {
+            counters.entry(*app_id).or_default().deposits_locked += denom_amount(&amount, denom);
+        }
+    }
+
+    for (app_id, app) in counters {
+        APPGOVCONFIG.save(storage, app_id, &app)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(unused_variables)]
+mod tests {
+    use crate::msg;
+    use crate::msg::{SignedBallot, SignedVotePayload};
+    use crate::state::{
+        CommitRevealSettings, ConvictionSettings, ExpeditedSettings, OptimisticSettings,
+        ProposalOption, SlashSettings, SponsorSettings, SubmissionFee, VoterRewardSettings,
+        STATS_PERIOD,
+    };
+    use cosmwasm_std::testing::{mock_env, mock_info};
+    use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage};
+    use cosmwasm_std::{
+        coins, from_json, Addr, ContractResult, CosmosMsg, OwnedDeps, SystemResult,
+    };
+    use cosmwasm_std::{Attribute, Decimal, ReplyOn, SubMsgResponse, Timestamp};
+    use cw_storage_plus::Map;
+    use cw_utils::Expiration;
+    use cw_utils::{Duration, Threshold};
+    use fury_bindings::{
+        GetAppResponse, GetAssetDataResponse, MessageValidateResponse, TotalSupplyResponse,
+    };
+    use k256::ecdsa::SigningKey;
+    use std::collections::HashMap;
+    use std::marker::PhantomData;
+
+    use super::*;
+
+    const OWNER: &str = "admin0001";
+
+    pub fn mock_dependencies1() -> OwnedDeps<MockStorage, MockApi, MockQuerier, FuryQuery> {
+        OwnedDeps {
+            storage: MockStorage::default(),
+            api: MockApi::default(),
+            querier: MockQuerier::default(),
+            custom_query_type: PhantomData,
+        }
+    }
+
+    const GOV_DENOM: &str = "ugov";
+    // lock duration reported by the mocked locking contract for every address
+    const LOCKED_FOR: u64 = 3 * 86_400;
+
+    /// Instantiated deps whose querier answers the Fury module queries for any app and the
+    /// locking contract queries with `supply` vtokens and the given per address balances
+    pub fn mock_dependencies_with_locking(
+        supply: u128,
+        balances: &[(&str, u128)],
+    ) -> OwnedDeps<MockStorage, MockApi, MockQuerier<FuryQuery>, FuryQuery> {
+        let balances: HashMap<String, u128> = balances
+            .iter()
+            .map(|(addr, balance)| (addr.to_string(), *balance))
+            .collect();
+        let mut querier = MockQuerier::<FuryQuery>::new(&[]).with_custom_handler(move |query| {
+            let response = match query {
+                FuryQuery::GetApp { .. } => to_json_binary(&GetAppResponse {
+                    min_gov_deposit: "100".to_string(),
+                    gov_time_in_seconds: 1000,
+                    gov_token_id: 1,
+                }),
+                FuryQuery::GetAssetData { .. } => to_json_binary(&GetAssetDataResponse {
+                    denom: GOV_DENOM.to_string(),
+                }),
+                FuryQuery::TotalSupply { .. } => to_json_binary(&TotalSupplyResponse {
+                    current_supply: supply as u64,
+                }),
+                _ => to_json_binary(&MessageValidateResponse {
+                    found: true,
+                    err: String::new(),
+                }),
+            };
+            SystemResult::Ok(ContractResult::Ok(response.unwrap()))
+        });
+        querier.update_wasm(move |query| {
+            let response = match query {
+                WasmQuery::Smart { msg, .. } => match from_json::<QueryMsg>(msg).unwrap() {
+                    QueryMsg::Supply { .. } => to_json_binary(&TokenSupply {
+                        token: supply,
+                        vtoken: supply,
+                    }),
+                    QueryMsg::TotalVTokens { address, .. } => to_json_binary(&Uint128::from(
+                        balances.get(address.as_str()).copied().unwrap_or_default(),
+                    )),
+                    QueryMsg::LockEnd { .. } => to_json_binary(&LockEndResponse {
+                        end_time: Some(mock_env().block.time.plus_seconds(LOCKED_FOR)),
+                    }),
+                    q => panic!("unexpected locking contract query {:?}", q),
+                },
+                q => panic!("unexpected wasm query {:?}", q),
+            };
+            SystemResult::Ok(ContractResult::Ok(response.unwrap()))
+        });
+        let mut deps = OwnedDeps {
+            storage: MockStorage::default(),
+            api: MockApi::default(),
+            querier,
+            custom_query_type: PhantomData,
+        };
+        let msg = InstantiateMsg {
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+            },
+            locking_contract: Addr::unchecked("locking_contract"),
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+        deps
+    }
+
+    /// feeds a successful reply for every submessage of `res`
+    fn reply_succeeded(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier<FuryQuery>, FuryQuery>,
+        env: &Env,
+        res: &Response<FuryMessages>,
+    ) {
+        for msg in res
+            .messages
+            .iter()
+            .filter(|msg| msg.reply_on == ReplyOn::Always)
+        {
+            let succeeded = Reply {
+                id: msg.id,
+                result: SubMsgResult::Ok(SubMsgResponse {
+                    events: vec![],
+                    data: None,
+                }),
+            };
+            reply(deps.as_mut(), env.clone(), succeeded).unwrap();
+        }
+    }
+
+    fn mock_propose(app_id: u64) -> Propose {
+        Propose {
+            title: "title".to_string(),
+            description: "description".to_string(),
+            msgs: vec![FuryMessages::MsgWhitelistAppIdVaultInterest { app_id }],
+            latest: None,
+            app_id_param: app_id,
+            options: vec![],
+            optimistic: false,
+            set_guardian: None,
+            expedited: false,
+            depends_on: vec![],
+            execute_at: None,
+            participating_apps: vec![],
+        }
+    }
+
+    #[test]
+    fn proper_initialization() {
+        let mut deps = mock_dependencies1();
+
+        // Empty locking contract address should not be permitted
+        let msg = InstantiateMsg {
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::one(),
+                quorum: Decimal::one(),
+            },
+            locking_contract: Addr::unchecked(""),
+        };
+        let result =
+            instantiate(deps.as_mut(), mock_env(), mock_info("sender", &[]), msg).unwrap_err();
+        match result {
+            ContractError::Std(StdError::GenericErr { .. }) => {}
+            e => panic!("{:?}", e),
+        };
+
+        const OWNER: &str = "admin001";
+        let _addr1 = Addr::unchecked("addr1");
+        let _addr2 = Addr::unchecked("addr2");
+        let info = mock_info(OWNER, &[]);
+
+        let not_acceptable_msg1 = InstantiateMsg {
+            threshold: Threshold::AbsoluteCount { weight: 10 },
+            locking_contract: Addr::unchecked("locking_contract"),
+        };
+
+        let not_acceptable_msg2 = InstantiateMsg {
+            threshold: Threshold::AbsolutePercentage {
+                percentage: Decimal::percent(50),
+            },
+            locking_contract: Addr::unchecked("locking_contract"),
+        };
+
+        let expected_msg = InstantiateMsg {
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+            },
+            locking_contract: Addr::unchecked("locking_contract"),
+        };
+
+        let res1 = instantiate(deps.as_mut(), mock_env(), info.clone(), not_acceptable_msg1);
+        assert_eq!(res1, Err(ContractError::AbsoluteCountNotAccepted {}));
+
+        let res2 = instantiate(deps.as_mut(), mock_env(), info.clone(), not_acceptable_msg2);
+        assert_eq!(res2, Err(ContractError::AbsolutePercentageNotAccepted {}));
+
+        let res3 = instantiate(deps.as_mut(), mock_env(), info.clone(), expected_msg);
+        assert_ne!(res3, Err(ContractError::AbsoluteCountNotAccepted {}));
+        assert_ne!(res3, Err(ContractError::AbsolutePercentageNotAccepted {}));
+        assert_ne!(res3, Err(ContractError::InvalidThreshold {}));
+        assert_ne!(res3, Err(ContractError::ZeroQuorumThreshold {}));
+        assert_ne!(res3, Err(ContractError::UnreachableQuorumThreshold {}));
+    }
+    // Propose Testcase
+    #[test]
+    fn test_propose() {
+        let mut deps = mock_dependencies1();
+        //let mut deps2=mock_dependencies1();
+        let info = mock_info(OWNER, &[]);
+        let msgs_com = vec![
+            FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 },
+            FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 34 },
+        ];
+
+        let propose_1 = Propose {
+            title: "propose".to_string(),
+            description: "test_propose".to_string(),
+            msgs: msgs_com,
+            // note: we ignore API-spec'd earliest if passed, always opens immediately
+            latest: Some(Expiration::Never {}),
+            app_id_param: 33,
+            options: vec![],
+            optimistic: false,
+            set_guardian: None,
+            expedited: false,
+            depends_on: vec![],
+            execute_at: None,
+            participating_apps: vec![],
+        };
+
+        //let msgs_length=msgs_com.len();
+        let k = execute_propose(deps.as_mut(), mock_env(), info.clone(), propose_1);
+        assert_eq!(k, Err(ContractError::ExtraMessages {}));
+        let msgs_2: Vec<FuryMessages> = vec![];
+        let propose_2 = Propose {
+            title: "propose".to_string(),
+            description: "test_propose".to_string(),
+            msgs: msgs_2,
+            // note: we ignore API-spec'd earliest if passed, always opens immediately
+            latest: Some(Expiration::Never {}),
+            app_id_param: 33,
+            options: vec![],
+            optimistic: false,
+            set_guardian: None,
+            expedited: false,
+            depends_on: vec![],
+            execute_at: None,
+            participating_apps: vec![],
+        };
+        let f = execute_propose(deps.as_mut(), mock_env(), info, propose_2);
+        assert_eq!(f, Err(ContractError::NoMessage {}));
+    }
+
+    //Execute Testcase
+    #[test]
+    fn test_execute() {
+        let mut deps = mock_dependencies_with_locking(1_000, &[]);
+        let info = mock_info(OWNER, &[]);
+        let ts = cosmwasm_std::Timestamp::from_nanos(1_655_745_339);
+        let a = Uint128::from(123u128);
+        pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
+        let id = next_id(&mut deps.storage).unwrap();
+        let mut prop = Proposal {
+            title: "prop".to_string(),
+            start_time: ts,
+            description: "test prop".to_string(),
+            start_height: 43,
+            expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
+            msgs: vec![FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
+            status: Status::Passed,
+            duration: Duration::Time(40),
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+            },
+            total_weight: 14,
+            votes: Votes {
+                yes: 32,
+                no: 24,
+                abstain: 10,
+                veto: 3,
+            },
+            deposit: vec![Coin {
+                denom: "vote here".to_string(),
+                amount: a,
+            }],
+            proposer: "validator201".to_string(),
+            token_denom: "toVote".to_string(),
+            min_deposit: Uint128::from(45_u64),
+            current_deposit: 56,
+            app_mapping_id: 33,
+            is_slashed: true,
+            tally_mode: TallyMode::Linear,
+            raw_votes: Votes {
+                yes: 32,
+                no: 24,
+                abstain: 10,
+                veto: 3,
+            },
+            conviction: None,
+            options: vec![],
+            option_votes: vec![],
+            revision: 0,
+            commit_reveal: None,
+            reveal_expires: None,
+            unrevealed_weight: 0,
+            optimistic: None,
+            escalated: false,
+            review_period: None,
+            set_guardian: None,
+            expedited: None,
+            category: None,
+            timelock: 0,
+            execution_error: None,
+            execution_window: None,
+            outcome: None,
+            depends_on: vec![],
+            execute_at: None,
+            participants: vec![],
+            required_apps: None,
+            sponsorship: None,
+            sponsors: vec![],
+            submission_fee: None,
+            raw_total_weight: 0,
+        };
+
+        prop.update_status(&mock_env().block);
+
+        let _k = PROPOSALS.save(&mut deps.storage, id, &prop);
+
+        let err = execute_execute(deps.as_mut(), mock_env(), info, id);
+        assert_ne!(err, Err(ContractError::WrongExecuteStatus {}));
+        assert_ne!(err, Err(ContractError::NotExpiredYet {}));
+
+        assert_eq!(
+            err,
+            Ok(Response::new()
+                .add_submessages(
+                    prop.msgs
+                        .into_iter()
+                        .map(|msg| SubMsg::reply_always(msg, id))
+                )
+                .add_attribute("action", "execute")
+                .add_attribute("sender", OWNER)
+                .add_attribute("proposal_id", id.to_string()))
+        );
+    }
+
+    //    Refund Testcase
+    #[test]
+    fn test_refund_works() {
+        // let id = next_id(&mut deps.storage).unwrap();
+        let mut deps = mock_dependencies1();
+        let deposit_amount = Uint128::from(10u128);
+        let info = mock_info(
+            OWNER,
+            &[Coin {
+                denom: "coin".to_string(),
+                amount: deposit_amount,
+            }],
+        );
+        let _v1 = Vote::Yes;
+        let ts = cosmwasm_std::Timestamp::from_nanos(1_655_794_117);
+        let id = next_id(&mut deps.storage).unwrap();
+        pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
+        let prop = Proposal {
+            title: "prop".to_string(),
+            start_time: ts,
+            description: "test prop".to_string(),
+            start_height: 43,
+            expires: Expiration::Never {},
+            msgs: vec![FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
+            status: Status::Pending,
+            duration: Duration::Time(50000000),
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+            },
+            total_weight: 14,
+            votes: Votes {
+                yes: 10,
+                no: 5,
+                abstain: 10,
+                veto: 39,
+            },
+            deposit: vec![Coin {
+                denom: "vote here".to_string(),
+                amount: deposit_amount,
+            }],
+            proposer: "validator201".to_string(),
+            token_denom: "toVote".to_string(),
+            min_deposit: Uint128::from(33_u64),
+            current_deposit: 56,
+            app_mapping_id: id,
+            is_slashed: false,
+            tally_mode: TallyMode::Linear,
+            raw_votes: Votes {
+                yes: 10,
+                no: 5,
+                abstain: 10,
+                veto: 39,
+            },
+            conviction: None,
+            options: vec![],
+            option_votes: vec![],
+            revision: 0,
+            commit_reveal: None,
+            reveal_expires: None,
+            unrevealed_weight: 0,
+            optimistic: None,
+            escalated: false,
+            review_period: None,
+            set_guardian: None,
+            expedited: None,
+            category: None,
+            timelock: 0,
+            execution_error: None,
+            execution_window: None,
+            outcome: None,
+            depends_on: vec![],
+            execute_at: None,
+            participants: vec![],
+            required_apps: None,
+            sponsorship: None,
+            sponsors: vec![],
+            submission_fee: None,
+            raw_total_weight: 0,
+        };
+        // if status is pending should get non passedProposalRefund error
+        let mut _k = PROPOSALS.save(&mut deps.storage, id, &prop);
+        let mut prop = PROPOSALS.load(&deps.storage, id).unwrap();
+        assert_eq!(prop.status, Status::Pending);
+        let g = execute_refund(deps.as_mut(), mock_env(), info.clone(), id).unwrap_err();
+
+        // If status is Rejected Should get Slashedpropsal Error
+        prop.status = Status::Rejected;
+        _k = PROPOSALS.save(&mut deps.storage, id, &prop);
+        let z = execute_refund(deps.as_mut(), mock_env(), info.clone(), id);
+        match g {
+            ContractError::CustomError { .. } => {}
+            e => panic!("{:?}", e),
+        };
+
+        prop.status = Status::Passed;
+        _k = PROPOSALS.save(&mut deps.storage, id, &prop);
+        let mut prop = PROPOSALS.load(&deps.storage, id).unwrap();
+        assert_eq!(prop.status, Status::Passed);
+        let votes = prop.votes.clone();
+        assert_eq!(39, votes.veto);
+
+        prop.status = Status::Rejected;
+        prop.expires = Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_794_157));
+        let mut _prop = PROPOSALS.save(&mut deps.storage, id, &prop);
+        let mut prop = PROPOSALS.load(&deps.storage, id).unwrap();
+        assert_eq!(prop.status, Status::Rejected);
+        let i = execute_refund(deps.as_mut(), mock_env(), info.clone(), id);
+        match g {
+            ContractError::CustomError { .. } => {}
+            e => panic!("{:?}", e),
+        };
+        assert_eq!(
+            (Decimal::percent(33) * Uint128::from(votes.total())).u128(),
+            21
+        );
+        let _votes = prop.votes.clone();
+
+        prop.status = Status::Passed;
+        prop.expires = Expiration::Never {};
+        _prop = PROPOSALS.save(&mut deps.storage, id, &prop);
+        pub const VOTERDEPOSIT: Map<(u64, &Addr), Vec<Coin>> = Map::new("voter deposit");
+        let deposit_info = VOTERDEPOSIT
+            .may_load(&deps.storage, (id, &info.sender))
+            .unwrap();
+        assert_eq!(deposit_info, None);
+        let j = execute_refund(deps.as_mut(), mock_env(), info.clone(), id);
+
+        prop.status = Status::Open;
+        let a = Uint128::from(123u128);
+        let deposit_info1 = Some(vec![Coin {
+            denom: "coin".to_string(),
+            amount: a,
+        }])
+        .unwrap();
+        let mut _vot = VOTERDEPOSIT.save(&mut deps.storage, (id, &info.sender), &deposit_info1);
+        _vot = PROPOSALS.save(&mut deps.storage, id, &prop);
+        prop.status = Status::Passed;
+        _prop = PROPOSALS.save(&mut deps.storage, id, &prop);
+        let k = execute_refund(deps.as_mut(), mock_env(), info.clone(), id);
+        match g {
+            ContractError::CustomError { .. } => {}
+            e => panic!("{:?}", e),
+        };
+    }
+
+    //   Deposit Testcase
+    #[test]
+    #[ignore = "Invalid"]
+    fn test_deposit() {
+        let mut deps = mock_dependencies1();
+        let a = Uint128::from(123u128);
+        let info = mock_info(
+            OWNER,
+            &[Coin {
+                denom: "coin".to_string(),
+                amount: a,
+            }],
+        );
+        let ts = cosmwasm_std::Timestamp::from_nanos(1_655_745_339);
+        let a = Uint128::from(123u128);
+        pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
+        let id = next_id(&mut deps.storage).unwrap();
+
+        let mut prop = Proposal {
+            title: "prop".to_string(),
+            start_time: ts,
+            description: "test prop".to_string(),
+            start_height: 43,
+            expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
+            msgs: vec![FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
+            status: Status::Executed,
+            duration: Duration::Time(40),
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+            },
+            total_weight: 14,
+            votes: Votes {
+                yes: 10,
+                no: 5,
+                abstain: 10,
+                veto: 39,
+            },
+            deposit: vec![Coin {
+                denom: "vote here".to_string(),
+                amount: a,
+            }],
+            proposer: "validator201".to_string(),
+            token_denom: "toVote".to_string(),
+            min_deposit: Uint128::from(45_u64),
+            current_deposit: 56,
+            app_mapping_id: id,
+            is_slashed: true,
+            tally_mode: TallyMode::Linear,
+            raw_votes: Votes {
+                yes: 10,
+                no: 5,
+                abstain: 10,
+                veto: 39,
+            },
+            conviction: None,
+            options: vec![],
+            option_votes: vec![],
+            revision: 0,
+            commit_reveal: None,
+            reveal_expires: None,
+            unrevealed_weight: 0,
+            optimistic: None,
+            escalated: false,
+            review_period: None,
+            set_guardian: None,
+            expedited: None,
+            category: None,
+            timelock: 0,
+            execution_error: None,
+            execution_window: None,
+            outcome: None,
+            depends_on: vec![],
+            execute_at: None,
+            participants: vec![],
+            required_apps: None,
+            sponsorship: None,
+            sponsors: vec![],
+            submission_fee: None,
+            raw_total_weight: 0,
+        };
+
+        prop.update_status(&mock_env().block);
+        let mut _prop = PROPOSALS.save(&mut deps.storage, id, &prop);
+        let mut _vote = VOTERDEPOSIT.save(&mut deps.storage, (id, &info.sender), &info.funds);
+        let _deposit_info = VOTERDEPOSIT
+            .may_load(&deps.storage, (id, &info.sender))
+            .unwrap();
+        let err = execute_deposit(deps.as_mut(), mock_env(), info.clone(), id);
+        assert_eq!(err, Err(ContractError::CannotDeposit {}));
+        prop.status = Status::Open;
+        _prop = PROPOSALS.save(&mut deps.storage, id, &prop);
+        let a = Uint128::from(123u128);
+        pub const VOTERDEPOSIT: Map<(u64, &Addr), Vec<Coin>> = Map::new("voter deposit");
+        let deposit_info1 = Some(vec![Coin {
+            denom: "coin".to_string(),
+            amount: a,
+        }])
+        .unwrap();
+        let mut _deposit = VOTERDEPOSIT.save(&mut deps.storage, (id, &info.sender), &deposit_info1);
+
+        prop.status = Status::Open;
+        prop.expires = Expiration::Never {};
+        _prop = PROPOSALS.save(&mut deps.storage, id, &prop);
+        //  If the status is not equal to open or pending, the error "CannotDeposit" will appear.
+        let err = execute_deposit(deps.as_mut(), mock_env(), info.clone(), id);
+        assert_ne!(err, Err(ContractError::CannotDeposit {}));
+        assert_ne!(err, Err(ContractError::IncorrectDenomDeposit {}));
+
+        let z = execute_deposit(deps.as_mut(), mock_env(), info.clone(), id);
+        assert_eq!(
+            z,
+            Ok(Response::new()
+                .add_attribute("action", "deposit")
+                .add_attribute("depositor", info.sender)
+                .add_attribute("proposal_id", id.to_string()))
+        );
+    }
+
+    //Vote Testcase
+    #[test]
+    fn test_vote() {
+        let mut deps = mock_dependencies1();
+        let a = Uint128::from(123u128);
+        let info = mock_info(OWNER, &[]);
+        let ts = cosmwasm_std::Timestamp::from_nanos(1_655_827_190);
+        pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
+        let id = next_id(&mut deps.storage).unwrap();
+        let mut prop = Proposal {
+            title: "prop".to_string(),
+            start_time: ts,
+            description: "test prop".to_string(),
+            start_height: 43,
+            expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_897_190)),
+            msgs: vec![FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: id }],
+            status: Status::Passed,
+            duration: Duration::Time(400000000),
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+            },
+            total_weight: 40,
+            votes: Votes {
+                yes: 32,
+                no: 24,
+                abstain: 10,
+                veto: 3,
+            },
+            deposit: vec![Coin {
+                denom: "vote here".to_string(),
+                amount: a,
+            }],
+            proposer: "validator201".to_string(),
+            token_denom: "toVote".to_string(),
+            min_deposit: Uint128::from(45_u64),
+            current_deposit: 56,
+            app_mapping_id: id,
+            is_slashed: false,
+            tally_mode: TallyMode::Linear,
+            raw_votes: Votes {
+                yes: 32,
+                no: 24,
+                abstain: 10,
+                veto: 3,
+            },
+            conviction: None,
+            options: vec![],
+            option_votes: vec![],
+            revision: 0,
+            commit_reveal: None,
+            reveal_expires: None,
+            unrevealed_weight: 0,
+            optimistic: None,
+            escalated: false,
+            review_period: None,
+            set_guardian: None,
+            expedited: None,
+            category: None,
+            timelock: 0,
+            execution_error: None,
+            execution_window: None,
+            outcome: None,
+            depends_on: vec![],
+            execute_at: None,
+            participants: vec![],
+            required_apps: None,
+            sponsorship: None,
+            sponsors: vec![],
+            submission_fee: None,
+            raw_total_weight: 0,
+        };
+
+        let mut _prop = PROPOSALS.save(&mut deps.storage, id, &prop);
+
+        let prop1 = PROPOSALS.load(&deps.storage, id).unwrap();
+        assert_eq!(prop1.current_status(&mock_env().block), Status::Passed);
+
+        // If the status is not equal to "open," an error message will appear.
+        let k = execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            id,
+            Vote::Yes,
+            None,
+            None,
+            None,
+        );
+        assert_eq!(k, Err(ContractError::NotOpen {}));
+        prop.status = Status::Open;
+        prop.expires = Expiration::Never {};
+        _prop = PROPOSALS.save(&mut deps.storage, id, &prop);
+        let prop1 = PROPOSALS.load(&deps.storage, id).unwrap();
+        assert_eq!(prop1.expires, Expiration::Never {});
+        let _m = execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            id,
+            Vote::Yes,
+            None,
+            None,
+            None,
+        );
+        assert_eq!(prop1.status, Status::Open);
+        assert_eq!(prop1.current_status(&mock_env().block), Status::Open);
+        assert!(!prop.expires.is_expired(&mock_env().block));
+        assert_eq!(prop1.current_status(&mock_env().block), Status::Open);
+        assert_eq!(prop.current_status(&mock_env().block), Status::Open);
+        assert_eq!(prop.status, Status::Open);
+    }
+
+    #[test]
+    fn test_slash() {
+        let mut deps = mock_dependencies1();
+        let info = mock_info(OWNER, &[]);
+        let ts = cosmwasm_std::Timestamp::from_nanos(1_655_745_339);
+        let a = Uint128::from(123u128);
+        pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
+        let id = next_id(&mut deps.storage).unwrap();
+
+        let expected_msg = InstantiateMsg {
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+            },
+            locking_contract: Addr::unchecked("locking_contract"),
+        };
+        instantiate(deps.as_mut(), mock_env(), info.clone(), expected_msg).unwrap();
+        let mut prop = Proposal {
+            title: "prop".to_string(),
+            start_time: ts,
+            description: "test prop".to_string(),
+            start_height: 43,
+            expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
+            msgs: vec![FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
+            status: Status::Rejected,
+            duration: Duration::Time(40),
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+            },
+            total_weight: 40,
+            votes: Votes {
+                yes: 32,
+                no: 24,
+                abstain: 10,
+                veto: 40,
+            },
+            deposit: vec![Coin {
+                denom: "vote here".to_string(),
+                amount: a,
+            }],
+            proposer: "validator201".to_string(),
+            token_denom: "toVote".to_string(),
+            min_deposit: Uint128::from(45_u64),
+            current_deposit: 56,
+            app_mapping_id: 33,
+            is_slashed: false,
+            tally_mode: TallyMode::Linear,
+            raw_votes: Votes {
+                yes: 32,
+                no: 24,
+                abstain: 10,
+                veto: 40,
+            },
+            conviction: None,
+            options: vec![],
+            option_votes: vec![],
+            revision: 0,
+            commit_reveal: None,
+            reveal_expires: None,
+            unrevealed_weight: 0,
+            optimistic: None,
+            escalated: false,
+            review_period: None,
+            set_guardian: None,
+            expedited: None,
+            category: None,
+            timelock: 0,
+            execution_error: None,
+            execution_window: None,
+            outcome: None,
+            depends_on: vec![],
+            execute_at: None,
+            participants: vec![],
+            required_apps: None,
+            sponsorship: None,
+            sponsors: vec![],
+            submission_fee: None,
+            raw_total_weight: 0,
+        };
+        prop.expires = Expiration::Never {};
+        prop.update_status(&mock_env().block);
+
+        //If the majority of votes are vetoed, the Slash should be elected.
+        let _k = PROPOSALS.save(&mut deps.storage, id, &prop);
+        let res = execute_slash(deps.as_mut(), mock_env(), info.clone(), id);
+        assert_ne!(res, Err(ContractError::NotRejected {}));
+        assert_ne!(res, Err(ContractError::AlreadySlashed {}));
+        let prop1 = PROPOSALS.load(&deps.storage, id).unwrap();
+        // After running slash The is_slashed should be true.
+        assert_eq!(prop1.is_slashed, true);
+        assert_eq!(
+            res,
+            Ok(Response::new()
+                .add_message(FuryMessages::MsgBurnGovTokensForApp {
+                    app_id: prop.app_mapping_id,
+                    amount: Coin {
+                        denom: "toVote".to_string(),
+                        amount: Uint128::from(56u128)
+                    },
+                    from: "cosmos2contract".to_string(),
+                })
+                .add_attribute("action", "Slash")
+                .add_attribute("trigger_address", info.sender)
+                .add_attribute("proposal_id", id.to_string())
+                .add_attribute("burned", "56")
+                .add_attribute("returned", "0"))
+        );
+    }
+
+    #[test]
+    fn test_query() {
+        let mut deps = mock_dependencies1();
+        let info = mock_info(OWNER, &[]);
+        let ts = cosmwasm_std::Timestamp::from_nanos(1_655_745_339);
+        let a = Uint128::from(123u128);
+        pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
+        let id = next_id(&mut deps.storage).unwrap();
+
+        let expected_msg = InstantiateMsg {
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+            },
+            locking_contract: Addr::unchecked("locking_contract"),
+        };
+        instantiate(deps.as_mut(), mock_env(), info.clone(), expected_msg).unwrap();
+        let mut prop = Proposal {
+            title: "prop".to_string(),
+            start_time: ts,
+            description: "test prop".to_string(),
+            start_height: 43,
+            expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
+            msgs: vec![FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
+            status: Status::Passed,
+            duration: Duration::Time(40),
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+            },
+            total_weight: 14,
+            votes: Votes {
+                yes: 32,
+                no: 24,
+                abstain: 10,
+                veto: 3,
+            },
+            deposit: vec![Coin {
+                denom: "vote here".to_string(),
+                amount: a,
+            }],
+            proposer: "validator201".to_string(),
+            token_denom: "toVote".to_string(),
+            min_deposit: Uint128::from(45_u64),
+            current_deposit: 56,
+            app_mapping_id: 33,
+            is_slashed: true,
+            tally_mode: TallyMode::Linear,
+            raw_votes: Votes {
+                yes: 32,
+                no: 24,
                 abstain: 10,
                 veto: 3,
             },
-            deposit: vec![Coin {
-                denom: "vote here".to_string(),
-                amount: a,
-            }],
-            proposer: "validator201".to_string(),
-            token_denom: "toVote".to_string(),
-            min_deposit: Uint128::from(45_u64),
-            current_deposit: 56,
-            app_mapping_id: id,
-            is_slashed: true,
+            conviction: None,
+            options: vec![],
+            option_votes: vec![],
+            revision: 0,
+            commit_reveal: None,
+            reveal_expires: None,
+            unrevealed_weight: 0,
+            optimistic: None,
+            escalated: false,
+            review_period: None,
+            set_guardian: None,
+            expedited: None,
+            category: None,
+            timelock: 0,
+            execution_error: None,
+            execution_window: None,
+            outcome: None,
+            depends_on: vec![],
+            execute_at: None,
+            participants: vec![],
+            required_apps: None,
+            sponsorship: None,
+            sponsors: vec![],
+            submission_fee: None,
+            raw_total_weight: 0,
+        };
+
+        prop.update_status(&mock_env().block);
+        let _k = PROPOSALS.save(&mut deps.storage, id, &prop);
+
+        // Threshold should be from ThreshouldQuorm
+        let res = query_threshold(deps.as_ref(), id);
+        assert_eq!(
+            res,
+            Ok(ThresholdResponse::ThresholdQuorum {
+                threshold: (Decimal::percent(50)),
+                quorum: (Decimal::percent(33)),
+                total_weight: (14)
+            })
+        );
+
+        // Query_propsal_detailed should give deatails respected to propsal id
+        let res = query_proposal_detailed(deps.as_ref(), mock_env(), id);
+        let ts = cosmwasm_std::Timestamp::from_nanos(1_655_745_339);
+        assert_eq!(
+            res,
+            Ok(msg::ProposalResponseTotal {
+                id,
+                title: "prop".to_string(),
+                start_time: ts,
+                description: "test prop".to_string(),
+                start_height: 43,
+                expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
+                msgs: vec![FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
+                status: Status::Passed,
+                duration: Duration::Time(40),
+                threshold: Threshold::ThresholdQuorum {
+                    threshold: Decimal::percent(50),
+                    quorum: Decimal::percent(33),
+                },
+                total_weight: 14,
+                votes: Votes {
+                    yes: 32,
+                    no: 24,
+                    abstain: 10,
+                    veto: 3,
+                },
+                proposer: "validator201".to_string(),
+                token_denom: "toVote".to_string(),
+                current_deposit: 56,
+                options: vec![],
+                option_votes: vec![],
+                revision: 0,
+                optimistic: None,
+                escalated: false,
+                expedited: None,
+                category: None,
+                timelock: 0,
+                execution_error: None,
+                depends_on: vec![],
+                execute_at: None,
+                participants: vec![],
+                required_apps: None,
+                sponsors: vec![],
+                sponsors_required: 0,
+                submission_fee: None,
+            })
+        );
+
+        // List Proposal Should give list of propsals
+        let res = list_proposals(deps.as_ref(), mock_env(), Option::None, Option::None);
+        assert_eq!(
+            res,
+            Ok(ProposalListResponse {
+                proposals: vec![ProposalResponse {
+                    id: id,
+                    title: "prop".to_string(),
+                    description: "test prop".to_string(),
+                    msgs: vec![FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
+                    status: Status::Passed,
+                    expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
+                    threshold: ThresholdResponse::ThresholdQuorum {
+                        threshold: Decimal::percent(50),
+                        quorum: Decimal::percent(33),
+                        total_weight: 14,
+                    }
+                }]
+            })
+        );
+
+        let res = reverse_proposals(deps.as_ref(), mock_env(), Option::None, Option::None);
+        assert_eq!(
+            res,
+            Ok(ProposalListResponse {
+                proposals: vec![ProposalResponse {
+                    id: id,
+                    title: "prop".to_string(),
+                    description: "test prop".to_string(),
+                    msgs: vec![FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
+                    status: Status::Passed,
+                    expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
+                    threshold: ThresholdResponse::ThresholdQuorum {
+                        threshold: Decimal::percent(50),
+                        quorum: Decimal::percent(33),
+                        total_weight: 14,
+                    }
+                }]
+            })
+        );
+
+        let cfg = Config {
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+            },
+            locking_contract: Addr::unchecked("locking_contract"),
+        };
+        _ = CONFIG.save(&mut deps.storage, &cfg);
+
+        let ballot = Ballot {
+            weight: 10,
+            vote: Vote::Yes,
+            raw_weight: 10,
+            conviction: None,
+            option: None,
+            memo: None,
+            app_weights: vec![],
+        };
+        _ = BALLOTS.save(&mut deps.storage, (id, &info.sender), &ballot);
+
+        // Query Vote should show the Vote respected to voter and Propasal id.
+        let res = query_vote(deps.as_ref(), id, OWNER.to_string());
+        assert_eq!(
+            res,
+            Ok(VoteResponse {
+                vote: Some(VoteInfo {
+                    proposal_id: 1,
+                    voter: "admin0001".to_string(),
+                    vote: Vote::Yes,
+                    weight: 10,
+                    raw_weight: 10,
+                    option: None,
+                    memo: None,
+                })
+            })
+        );
+
+        // List Votes should show the VoteInfo respected to proposal id.
+        let res = list_votes(deps.as_ref(), id, Option::None, Option::None);
+        assert_eq!(
+            res,
+            Ok(VoteListResponse {
+                votes: vec![VoteInfo {
+                    proposal_id: 1,
+                    voter: "admin0001".to_string(),
+                    vote: Vote::Yes,
+                    weight: 10,
+                    raw_weight: 10,
+                    option: None,
+                    memo: None,
+                }]
+            })
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    fn test_deposit_non_gov_token() {
+        // Mock deps
+        let mut env = mock_env();
+        let mut deps = mock_dependencies1();
+
+        let DENOM: &str = "DNM";
+        env.block.time = Timestamp::from_seconds(1000);
+        env.block.height = 10;
+
+        // Instantiate
+        let imsg = InstantiateMsg {
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::one(),
+                quorum: Decimal::one(),
+            },
+            locking_contract: Addr::unchecked("locking-contract"),
+        };
+        let sender = Addr::unchecked("sender");
+        let info = mock_info(sender.as_str(), &[]);
+        instantiate(deps.as_mut(), env.clone(), info, imsg.clone()).unwrap();
+
+        // Save a proposal
+        let proposal = Proposal {
+            title: "title".to_string(),
+            start_time: Timestamp::from_seconds(1200),
+            description: "description".to_string(),
+            start_height: 10,
+            expires: Expiration::AtHeight(20),
+            msgs: vec![],
+            status: Status::Pending,
+            duration: Duration::Height(10),
+            threshold: imsg.threshold,
+            total_weight: 0,
+            votes: Votes {
+                yes: 0,
+                no: 0,
+                abstain: 0,
+                veto: 0,
+            },
+            deposit: vec![],
+            proposer: sender.to_string(),
+            token_denom: DENOM.to_string(),
+            min_deposit: Uint128::from(200_u64),
+            current_deposit: 0,
+            app_mapping_id: 1,
+            is_slashed: false,
+            tally_mode: TallyMode::Linear,
+            raw_votes: Votes {
+                yes: 0,
+                no: 0,
+                abstain: 0,
+                veto: 0,
+            },
+            conviction: None,
+            options: vec![],
+            option_votes: vec![],
+            revision: 0,
+            commit_reveal: None,
+            reveal_expires: None,
+            unrevealed_weight: 0,
+            optimistic: None,
+            escalated: false,
+            review_period: None,
+            set_guardian: None,
+            expedited: None,
+            category: None,
+            timelock: 0,
+            execution_error: None,
+            execution_window: None,
+            outcome: None,
+            depends_on: vec![],
+            execute_at: None,
+            participants: vec![],
+            required_apps: None,
+            sponsorship: None,
+            sponsors: vec![],
+            submission_fee: None,
+            raw_total_weight: 0,
+        };
+
+        PROPOSALS.save(deps.as_mut().storage, 1, &proposal).unwrap();
+
+        // Deposit gov token
+        let info = mock_info(sender.as_str(), &coins(100, DENOM));
+        let result = execute_deposit(deps.as_mut(), env.clone(), info.clone(), 1).unwrap();
+        assert_eq!(result.messages.len(), 0);
+
+        // Check correct update in VOTERDEPOSIT
+        let sender_deposits = VOTERDEPOSIT
+            .load(deps.as_ref().storage, (1, &sender))
+            .unwrap();
+        assert_eq!(sender_deposits.len(), 1);
+        assert_eq!(sender_deposits[0].amount.u128(), 100);
+        assert_eq!(sender_deposits[0].denom, DENOM.to_string());
+
+        // Check correct update in PROPOSALS
+        let proposal_data = PROPOSALS.load(deps.as_ref().storage, 1).unwrap();
+        match proposal_data.status {
+            Status::Pending => {}
+            e => panic!("{:?}", e),
+        };
+        assert_eq!(proposal_data.current_deposit, 100u128);
+        assert_eq!(proposal_data.deposit.len(), 1);
+        assert_eq!(proposal_data.deposit[0].amount.u128(), 100u128);
+        assert_eq!(proposal_data.deposit[0].denom, DENOM.to_string());
+
+        // Deposit non-gov token
+        let info = mock_info(sender.as_str(), &coins(100, "denom2"));
+        let result = execute_deposit(deps.as_mut(), env.clone(), info, 1).unwrap_err();
+
+        // Check no update in PROPOSALS
+        let proposal_data = PROPOSALS.load(deps.as_ref().storage, 1).unwrap();
+        assert_eq!(proposal_data.current_deposit, 100);
+        assert_eq!(proposal_data.deposit.len(), 1);
+
+        // the proposal opens once the deposits reach the minimum deposit
+        env.block.time = Timestamp::from_seconds(1300);
+        let info = mock_info(sender.as_str(), &coins(100, DENOM));
+        execute_deposit(deps.as_mut(), env, info, 1).unwrap();
+        let proposal_data = PROPOSALS.load(deps.as_ref().storage, 1).unwrap();
+        assert_eq!(proposal_data.current_deposit, 200);
+        assert_eq!(proposal_data.status, Status::Open);
+    }
+
+    #[test]
+    fn test_refund_invalid_requests() {
+        // Mock deps
+        let mut env = mock_env();
+        let mut deps = mock_dependencies1();
+        let sender = Addr::unchecked("sender");
+        let DENOM: &str = "denom1";
+
+        env.block.time = Timestamp::from_seconds(1000);
+        env.block.height = 10;
+
+        // Instantiate
+        let imsg = InstantiateMsg {
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::one(),
+                quorum: Decimal::one(),
+            },
+            locking_contract: Addr::unchecked("locking-contract"),
+        };
+        let sender = Addr::unchecked("sender");
+        let info = mock_info(sender.as_str(), &[]);
+        instantiate(deps.as_mut(), env.clone(), info, imsg.clone()).unwrap();
+
+        // Save a proposal
+        let mut proposal = Proposal {
+            title: "title".to_string(),
+            start_time: Timestamp::from_seconds(1200),
+            description: "description".to_string(),
+            start_height: 10,
+            expires: Expiration::AtHeight(20),
+            msgs: vec![],
+            status: Status::Pending,
+            duration: Duration::Height(10),
+            threshold: imsg.threshold,
+            total_weight: 0,
+            votes: Votes {
+                yes: 0,
+                no: 0,
+                abstain: 0,
+                veto: 0,
+            },
+            deposit: vec![],
+            proposer: sender.to_string(),
+            token_denom: DENOM.to_string(),
+            min_deposit: Uint128::from(100_u64),
+            current_deposit: 0,
+            app_mapping_id: 1,
+            is_slashed: false,
+            tally_mode: TallyMode::Linear,
+            raw_votes: Votes {
+                yes: 0,
+                no: 0,
+                abstain: 0,
+                veto: 0,
+            },
+            conviction: None,
+            options: vec![],
+            option_votes: vec![],
+            revision: 0,
+            commit_reveal: None,
+            reveal_expires: None,
+            unrevealed_weight: 0,
+            optimistic: None,
+            escalated: false,
+            review_period: None,
+            set_guardian: None,
+            expedited: None,
+            category: None,
+            timelock: 0,
+            execution_error: None,
+            execution_window: None,
+            outcome: None,
+            depends_on: vec![],
+            execute_at: None,
+            participants: vec![],
+            required_apps: None,
+            sponsorship: None,
+            sponsors: vec![],
+            submission_fee: None,
+            raw_total_weight: 0,
+        };
+
+        PROPOSALS.save(deps.as_mut().storage, 1, &proposal).unwrap();
+
+        // Execute refund on a pending proposal
+        let info = mock_info(sender.as_str(), &[]);
+
+        let result = execute_refund(deps.as_mut(), env.clone(), info.clone(), 1).unwrap_err();
+        match result {
+            ContractError::PendingProposal {} => {}
+            e => panic!("{:?}", e),
+        };
+
+        // Execute refund on an open proposal
+        proposal.status = Status::Open;
+        PROPOSALS.save(deps.as_mut().storage, 1, &proposal).unwrap();
+        let result = execute_refund(deps.as_mut(), env.clone(), info.clone(), 1).unwrap_err();
+        match result {
+            ContractError::OpenProposal {} => {}
+            e => panic!("{:?}", e),
+        };
+    }
+
+    #[test]
+    fn test_slash_invalid_requests() {
+        let mut env = mock_env();
+        let mut deps = mock_dependencies1();
+        env.block.time = Timestamp::from_seconds(100);
+        env.block.height = 10;
+        let DENOM: &str = "denom1";
+
+        // Instantiate
+        let imsg = InstantiateMsg {
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::one(),
+                quorum: Decimal::one(),
+            },
+            locking_contract: Addr::unchecked("locking-contract"),
+        };
+        let sender = Addr::unchecked("sender");
+        let info = mock_info(sender.as_str(), &[]);
+        instantiate(deps.as_mut(), env.clone(), info, imsg.clone()).unwrap();
+
+        // Save a proposal
+        let mut proposal = Proposal {
+            title: "title".to_string(),
+            start_time: Timestamp::from_seconds(1200),
+            description: "description".to_string(),
+            start_height: 10,
+            expires: Expiration::AtHeight(20),
+            msgs: vec![],
+            status: Status::Pending,
+            duration: Duration::Height(10),
+            threshold: imsg.threshold,
+            total_weight: 0,
+            votes: Votes {
+                yes: 0,
+                no: 0,
+                abstain: 0,
+                veto: 0,
+            },
+            deposit: vec![],
+            proposer: sender.to_string(),
+            token_denom: DENOM.to_string(),
+            min_deposit: Uint128::from(100_u64),
+            current_deposit: 0,
+            app_mapping_id: 1,
+            is_slashed: false,
+            tally_mode: TallyMode::Linear,
+            raw_votes: Votes {
+                yes: 0,
+                no: 0,
+                abstain: 0,
+                veto: 0,
+            },
+            conviction: None,
+            options: vec![],
+            option_votes: vec![],
+            revision: 0,
+            commit_reveal: None,
+            reveal_expires: None,
+            unrevealed_weight: 0,
+            optimistic: None,
+            escalated: false,
+            review_period: None,
+            set_guardian: None,
+            expedited: None,
+            category: None,
+            timelock: 0,
+            execution_error: None,
+            execution_window: None,
+            outcome: None,
+            depends_on: vec![],
+            execute_at: None,
+            participants: vec![],
+            required_apps: None,
+            sponsorship: None,
+            sponsors: vec![],
+            submission_fee: None,
+            raw_total_weight: 0,
+        };
+
+        PROPOSALS.save(deps.as_mut().storage, 1, &proposal).unwrap();
+
+        // Slash for pending proposal
+        let info = mock_info(sender.as_str(), &[]);
+
+        let result = execute_slash(deps.as_mut(), env.clone(), info.clone(), 1).unwrap_err();
+        match result {
+            ContractError::NotRejected {} => {}
+            e => panic!("{:?}", e),
+        };
+
+        // Slash for rejected proposal but not vetoed
+        env.block.time = Timestamp::from_seconds(5000);
+        env.block.height = 30;
+        let info = mock_info(sender.as_str(), &[]);
+
+        let result = execute_slash(deps.as_mut(), env.clone(), info.clone(), 1).unwrap_err();
+        match result {
+            ContractError::ProposalNotVetoed {} => {}
+            e => panic!("{:?}", e),
+        };
+    }
+
+    #[test]
+    fn test_tally_mode_effective_weight() {
+        assert_eq!(TallyMode::Linear.effective_weight(99), 99);
+        assert_eq!(TallyMode::Quadratic.effective_weight(100), 10);
+        assert_eq!(TallyMode::Quadratic.effective_weight(99), 9);
+        assert_eq!(TallyMode::Quadratic.effective_weight(0), 0);
+    }
+
+    #[test]
+    fn test_quadratic_voting() {
+        let mut deps = mock_dependencies_with_locking(10_000, &[(OWNER, 400), ("voter", 900)]);
+        let settings = AppSettings {
+            tally_mode: TallyMode::Quadratic,
+            quadratic_total_weight: None,
+            ..AppSettings::default()
+        };
+        APPSETTINGS
+            .save(deps.as_mut().storage, 1, &settings)
+            .unwrap();
+
+        // quadratic proposals need a configured snapshot supply
+        let info = mock_info(OWNER, &coins(100, GOV_DENOM));
+        let err = execute_propose(deps.as_mut(), mock_env(), info.clone(), mock_propose(1));
+        assert_eq!(err, Err(ContractError::NoQuadraticSupply {}));
+
+        let settings = AppSettings {
+            tally_mode: TallyMode::Quadratic,
+            quadratic_total_weight: Some(200),
+            ..AppSettings::default()
+        };
+        APPSETTINGS
+            .save(deps.as_mut().storage, 1, &settings)
+            .unwrap();
+        execute_propose(deps.as_mut(), mock_env(), info, mock_propose(1)).unwrap();
+
+        let info = mock_info("voter", &[]);
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            1,
+            Vote::No,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let tally = query_tally(deps.as_ref(), mock_env(), 1).unwrap();
+        assert_eq!(tally.tally_mode, TallyMode::Quadratic);
+        assert_eq!(tally.total_weight, 200);
+        assert_eq!((tally.votes.yes, tally.votes.no), (20, 30));
+        assert_eq!((tally.raw_votes.yes, tally.raw_votes.no), (400, 900));
+
+        let vote = query_vote(deps.as_ref(), 1, "voter".to_string())
+            .unwrap()
+            .vote
+            .unwrap();
+        assert_eq!((vote.weight, vote.raw_weight), (30, 900));
+    }
+    #[test]
+    fn test_conviction_voting() {
+        let mut deps = mock_dependencies_with_locking(10_000, &[(OWNER, 100), ("voter", 100)]);
+        let info = mock_info(OWNER, &coins(100, GOV_DENOM));
+        execute_propose(deps.as_mut(), mock_env(), info, mock_propose(1)).unwrap();
+
+        // conviction is only available when the app enabled it before the proposal started
+        let info = mock_info("voter", &[]);
+        let err = execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            1,
+            Vote::No,
+            Some(2),
+            None,
+            None,
+        );
+        assert_eq!(err, Err(ContractError::ConvictionNotEnabled {}));
+
+        let settings = AppSettings {
+            conviction: Some(ConvictionSettings {
+                lock_period_per_tier: 86_400,
+                max_tier: 6,
+            }),
+            ..AppSettings::default()
         };
+        APPSETTINGS
+            .save(deps.as_mut().storage, 1, &settings)
+            .unwrap();
+        let info = mock_info(OWNER, &coins(100, GOV_DENOM));
+        execute_propose(deps.as_mut(), mock_env(), info, mock_propose(1)).unwrap();
+
+        let info = mock_info("voter", &[]);
+        let err = execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            2,
+            Vote::No,
+            Some(7),
+            None,
+            None,
+        );
+        assert_eq!(
+            err,
+            Err(ContractError::InvalidConvictionTier { max_tier: 6 })
+        );
+
+        // tier 5 needs four days of remaining lock, the voter only has three
+        let err = execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            2,
+            Vote::No,
+            Some(5),
+            None,
+            None,
+        );
+        assert_eq!(err, Err(ContractError::InsufficientLockDuration {}));
 
-        prop.update_status(&mock_env().block);
+        let weight =
+            query_effective_weight(deps.as_ref(), 2, "voter".to_string(), Some(4)).unwrap();
+        assert_eq!(
+            weight,
+            EffectiveWeightResponse {
+                raw_weight: 100,
+                multiplier: 4,
+                effective_weight: 400,
+            }
+        );
 
-        let _k = PROPOSALS.save(&mut deps.storage, id, &prop);
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            2,
+            Vote::No,
+            Some(4),
+            None,
+            None,
+        )
+        .unwrap();
+        let tally = query_tally(deps.as_ref(), mock_env(), 2).unwrap();
+        assert_eq!((tally.votes.yes, tally.votes.no), (100, 400));
+        assert_eq!((tally.raw_votes.yes, tally.raw_votes.no), (100, 100));
+        // quorum keeps using the raw supply
+        assert_eq!(tally.total_weight, 10_000);
+        let prop = PROPOSALS.load(deps.as_ref().storage, 2).unwrap();
+        assert_eq!(prop.quorum_weight(), 200);
+        assert_eq!(prop.turnout(), Decimal::percent(2));
+    }
+    fn auction_params_msg(app_id: u64, step: u64) -> FuryMessages {
+        FuryMessages::MsgAddAuctionParams {
+            app_id,
+            auction_duration_seconds: 3600,
+            buffer: Decimal::percent(120),
+            cusp: Decimal::percent(40),
+            step,
+            price_function_type: 1,
+            surplus_id: 1,
+            debt_id: 2,
+            dutch_id: 3,
+            bid_duration_seconds: 600,
+        }
+    }
 
-        let err = execute_execute(deps.as_mut(), mock_env(), info, id);
-        assert_ne!(err, Err(ContractError::WrongExecuteStatus {}));
-        assert_ne!(err, Err(ContractError::NotExpiredYet {}));
+    #[test]
+    fn test_multiple_choice_proposal() {
+        let balances = [
+            (OWNER, 100),
+            ("voter1", 300),
+            ("voter2", 200),
+            ("voter3", 100),
+        ];
+        let mut deps = mock_dependencies_with_locking(1_000, &balances);
+        let options: Vec<ProposalOption> = (0..3)
+            .map(|step| ProposalOption {
+                title: format!("step {}", step),
+                msgs: vec![auction_params_msg(1, step)],
+            })
+            .collect();
+        let info = mock_info(OWNER, &coins(100, GOV_DENOM));
+
+        let mut propose = mock_propose(1);
+        propose.options = options[..1].to_vec();
+        propose.msgs = vec![];
+        let err = execute_propose(deps.as_mut(), mock_env(), info.clone(), propose.clone());
+        assert_eq!(err, Err(ContractError::NotEnoughOptions {}));
+
+        propose.options = options.clone();
+        execute_propose(deps.as_mut(), mock_env(), info, propose).unwrap();
+
+        // picking an option needs a yes vote on an existing option
+        let info = mock_info("voter1", &[]);
+        let err = execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            info.clone(),
+            1,
+            Vote::Yes,
+            None,
+            None,
+            None,
+        );
+        assert_eq!(err, Err(ContractError::InvalidOption {}));
+        let err = execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            1,
+            Vote::Yes,
+            None,
+            Some(3),
+            None,
+        );
+        assert_eq!(err, Err(ContractError::InvalidOption {}));
+
+        for (voter, vote, option) in [
+            ("voter1", Vote::Yes, Some(1)),
+            ("voter2", Vote::Yes, Some(0)),
+            ("voter3", Vote::No, None),
+        ] {
+            let info = mock_info(voter, &[]);
+            execute_vote(deps.as_mut(), mock_env(), info, 1, vote, None, option, None).unwrap();
+        }
+
+        let tally = query_tally(deps.as_ref(), mock_env(), 1).unwrap();
+        assert_eq!(tally.option_votes, vec![200, 300, 0]);
+        assert_eq!(tally.winning_option, Some(1));
+        assert_eq!((tally.votes.no, tally.votes.abstain), (100, 100));
 
+        let votes = list_votes(deps.as_ref(), 1, None, None).unwrap().votes;
+        let voter1 = votes.iter().find(|v| v.voter == "voter1").unwrap();
+        assert_eq!(voter1.option, Some(1));
+
+        // the leading option beats "none of the above" and its messages are executed
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(1_001);
+        let res = execute_execute(deps.as_mut(), env, mock_info(OWNER, &[]), 1).unwrap();
+        assert_eq!(res.messages.len(), 1);
         assert_eq!(
-            err,
-            Ok(Response::new()
-                .add_messages(prop.msgs)
-                .add_attribute("action", "execute")
-                .add_attribute("sender", OWNER)
-                .add_attribute("proposal_id", id.to_string()))
+            res.messages[0].msg,
+            CosmosMsg::Custom(auction_params_msg(1, 1))
         );
     }
-
-    //    Refund Testcase
     #[test]
-    fn test_refund_works() {
-        // let id = next_id(&mut deps.storage).unwrap();
-        let mut deps = mock_dependencies1();
-        let deposit_amount = Uint128::from(10u128);
-        let info = mock_info(
-            OWNER,
-            &[Coin {
-                denom: "coin".to_string(),
-                amount: deposit_amount,
-            }],
+    fn test_amend_pending_proposal() {
+        let mut deps = mock_dependencies_with_locking(1_000, &[(OWNER, 100)]);
+        let info = mock_info(OWNER, &coins(40, GOV_DENOM));
+        execute_propose(deps.as_mut(), mock_env(), info, mock_propose(1)).unwrap();
+        let info = mock_info("depositor", &coins(30, GOV_DENOM));
+        execute_deposit(deps.as_mut(), mock_env(), info, 1).unwrap();
+
+        // nothing to object to before an amendment
+        let info = mock_info("depositor", &[]);
+        let err = execute_withdraw_deposit(deps.as_mut(), mock_env(), info, 1);
+        assert_eq!(err, Err(ContractError::NotAmended {}));
+
+        let info = mock_info("depositor", &[]);
+        let description = Some("fixed typo".to_string());
+        let err = execute_amend_proposal(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            1,
+            None,
+            description.clone(),
+            None,
         );
-        let _v1 = Vote::Yes;
-        let ts = cosmwasm_std::Timestamp::from_nanos(1_655_794_117);
-        let id = next_id(&mut deps.storage).unwrap();
-        pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
-        let prop = Proposal {
-            title: "prop".to_string(),
-            start_time: ts,
-            description: "test prop".to_string(),
-            start_height: 43,
-            expires: Expiration::Never {},
-            msgs: vec![FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
-            status: Status::Pending,
-            duration: Duration::Time(50000000),
-            threshold: Threshold::ThresholdQuorum {
-                threshold: Decimal::percent(50),
-                quorum: Decimal::percent(33),
-            },
-            total_weight: 14,
-            votes: Votes {
-                yes: 10,
-                no: 5,
-                abstain: 10,
-                veto: 39,
-            },
-            deposit: vec![Coin {
-                denom: "vote here".to_string(),
-                amount: deposit_amount,
-            }],
-            proposer: "validator201".to_string(),
-            token_denom: "toVote".to_string(),
-            min_deposit: Uint128::from(33_u64),
-            current_deposit: 56,
-            app_mapping_id: id,
-            is_slashed: false,
-        };
-        // if status is pending should get non passedProposalRefund error
-        let mut _k = PROPOSALS.save(&mut deps.storage, id, &prop);
-        let mut prop = PROPOSALS.load(&deps.storage, id).unwrap();
-        assert_eq!(prop.status, Status::Pending);
-        let g = execute_refund(deps.as_mut(), mock_env(), info.clone(), id).unwrap_err();
+        assert_eq!(err, Err(ContractError::Unauthorized {}));
 
-        // If status is Rejected Should get Slashedpropsal Error
-        prop.status = Status::Rejected;
-        _k = PROPOSALS.save(&mut deps.storage, id, &prop);
-        let z = execute_refund(deps.as_mut(), mock_env(), info.clone(), id);
-        match g {
-            ContractError::CustomError { .. } => {}
-            e => panic!("{:?}", e),
-        };
+        // amended messages go through the same validation as a new proposal
+        let info = mock_info(OWNER, &[]);
+        let msgs = Some(vec![
+            FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 1 },
+            FuryMessages::MsgWhitelistAppIdLiquidation { app_id: 1 },
+        ]);
+        let err =
+            execute_amend_proposal(deps.as_mut(), mock_env(), info.clone(), 1, None, None, msgs);
+        assert_eq!(err, Err(ContractError::ExtraMessages {}));
+        let msgs = Some(vec![FuryMessages::MsgWhitelistAppIdVaultInterest {
+            app_id: 2,
+        }]);
+        let err =
+            execute_amend_proposal(deps.as_mut(), mock_env(), info.clone(), 1, None, None, msgs);
+        assert_eq!(err, Err(ContractError::DifferentAppID {}));
+        // auction params of any app stay allowed on single-app proposals
+        let msg = auction_params_msg(2, 1);
+        validate_participating_msg(deps.as_ref(), &msg, &[1]).unwrap();
+        let err = validate_participating_msg(deps.as_ref(), &msg, &[1, 3]);
+        assert_eq!(err, Err(ContractError::AppNotParticipating { app_id: 2 }));
+
+        execute_amend_proposal(deps.as_mut(), mock_env(), info, 1, None, description, None)
+            .unwrap();
+        let prop = query_proposal_detailed(deps.as_ref(), mock_env(), 1).unwrap();
+        assert_eq!(prop.description, "fixed typo");
+        assert_eq!(prop.revision, 1);
+        let revisions = query_proposal_revisions(deps.as_ref(), 1).unwrap();
+        assert_eq!(revisions.len(), 2);
+        assert_ne!(revisions[0].content_hash, revisions[1].content_hash);
+
+        // the period stats count the amended messages instead of the original ones
+        let info = mock_info(OWNER, &[]);
+        let msgs = Some(vec![FuryMessages::MsgWhitelistAppIdLiquidation {
+            app_id: 1,
+        }]);
+        execute_amend_proposal(deps.as_mut(), mock_env(), info, 1, None, None, msgs).unwrap();
+        let period = stats_period(mock_env().block.time);
+        let stats = PERIODSTATS
+            .load(deps.as_ref().storage, (1, period))
+            .unwrap();
+        assert_eq!(
+            stats.msg_counts,
+            BTreeMap::from([("msg_whitelist_app_id_liquidation".to_string(), 1)])
+        );
 
-        prop.status = Status::Passed;
-        _k = PROPOSALS.save(&mut deps.storage, id, &prop);
-        let mut prop = PROPOSALS.load(&deps.storage, id).unwrap();
-        assert_eq!(prop.status, Status::Passed);
-        let votes = prop.votes.clone();
-        assert_eq!(39, votes.veto);
+        // the proposer made the amendment, the third party depositor may back out
+        let info = mock_info(OWNER, &[]);
+        let err = execute_withdraw_deposit(deps.as_mut(), mock_env(), info, 1);
+        assert_eq!(err, Err(ContractError::Unauthorized {}));
+        let info = mock_info("depositor", &[]);
+        let res = execute_withdraw_deposit(deps.as_mut(), mock_env(), info, 1).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "depositor".to_string(),
+                amount: coins(30, GOV_DENOM),
+            })
+        );
+        let prop = PROPOSALS.load(deps.as_ref().storage, 1).unwrap();
+        assert_eq!(prop.current_deposit, 40);
+        assert_eq!(prop.deposit, coins(40, GOV_DENOM));
 
-        prop.status = Status::Rejected;
-        prop.expires = Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_794_157));
-        let mut _prop = PROPOSALS.save(&mut deps.storage, id, &prop);
-        let mut prop = PROPOSALS.load(&deps.storage, id).unwrap();
-        assert_eq!(prop.status, Status::Rejected);
-        let i = execute_refund(deps.as_mut(), mock_env(), info.clone(), id);
-        match g {
-            ContractError::CustomError { .. } => {}
-            e => panic!("{:?}", e),
+        // once voting started the proposal is final
+        let info = mock_info("depositor", &coins(100, GOV_DENOM));
+        execute_deposit(deps.as_mut(), mock_env(), info, 1).unwrap();
+        let info = mock_info(OWNER, &[]);
+        let title = Some("title".to_string());
+        let err = execute_amend_proposal(deps.as_mut(), mock_env(), info, 1, title, None, None);
+        assert_eq!(err, Err(ContractError::NotPending {}));
+    }
+
+    #[test]
+    fn test_proposal_spam_limits() {
+        let balances = [(OWNER, 100), ("voter1", 300), ("minnow", 10)];
+        let mut deps = mock_dependencies_with_locking(1_000, &balances);
+        let settings = AppSettings {
+            max_active_proposals: Some(2),
+            max_proposals_per_proposer: Some(1),
+            slash_cooldown: Some(500),
+            min_propose_balance: Some(50),
+            ..AppSettings::default()
         };
+        APPSETTINGS
+            .save(deps.as_mut().storage, 1, &settings)
+            .unwrap();
+
+        let info = mock_info("minnow", &coins(40, GOV_DENOM));
+        let err = execute_propose(deps.as_mut(), mock_env(), info, mock_propose(1));
         assert_eq!(
-            (Decimal::percent(33) * Uint128::from(votes.total())).u128(),
-            21
+            err,
+            Err(ContractError::InsufficientProposerBalance { min: 50 })
         );
-        let _votes = prop.votes.clone();
 
-        prop.status = Status::Passed;
-        prop.expires = Expiration::Never {};
-        _prop = PROPOSALS.save(&mut deps.storage, id, &prop);
-        pub const VOTERDEPOSIT: Map<(u64, &Addr), Vec<Coin>> = Map::new("voter deposit");
-        let deposit_info = VOTERDEPOSIT
-            .may_load(&deps.storage, (id, &info.sender))
+        let info = mock_info(OWNER, &coins(40, GOV_DENOM));
+        execute_propose(deps.as_mut(), mock_env(), info.clone(), mock_propose(1)).unwrap();
+        let err = execute_propose(deps.as_mut(), mock_env(), info, mock_propose(1));
+        assert_eq!(err, Err(ContractError::TooManyProposerProposals { max: 1 }));
+
+        let info = mock_info("voter1", &coins(40, GOV_DENOM));
+        execute_propose(deps.as_mut(), mock_env(), info, mock_propose(1)).unwrap();
+        let info = mock_info("voter1", &coins(40, GOV_DENOM));
+        let err = execute_propose(deps.as_mut(), mock_env(), info, mock_propose(1));
+        assert_eq!(err, Err(ContractError::TooManyActiveProposals { max: 2 }));
+
+        // other apps are not affected by the limits
+        let info = mock_info(OWNER, &coins(40, GOV_DENOM));
+        execute_propose(deps.as_mut(), mock_env(), info, mock_propose(2)).unwrap();
+
+        // expired proposals free up their slots even before they are settled, a recent slash
+        // still blocks the proposer
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(1001);
+        env.block.height += 200;
+        let slashed_at = env.block.time;
+        PROPOSERSLASHED
+            .save(
+                deps.as_mut().storage,
+                (1, &Addr::unchecked(OWNER)),
+                &slashed_at,
+            )
             .unwrap();
-        assert_eq!(deposit_info, None);
-        let j = execute_refund(deps.as_mut(), mock_env(), info.clone(), id);
+        let info = mock_info(OWNER, &coins(40, GOV_DENOM));
+        let err = execute_propose(deps.as_mut(), env.clone(), info.clone(), mock_propose(1));
+        assert_eq!(
+            err,
+            Err(ContractError::ProposerCooldown {
+                until: slashed_at.plus_seconds(500)
+            })
+        );
+        env.block.time = env.block.time.plus_seconds(500);
+        execute_propose(deps.as_mut(), env, info, mock_propose(1)).unwrap();
+        // creating the proposal stored the status of the expired ones
+        let active = ACTIVEPROPOSALS
+            .prefix(1)
+            .keys(deps.as_ref().storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(active, vec![4]);
+        assert_eq!(
+            PROPOSALS.load(deps.as_ref().storage, 1).unwrap().status,
+            Status::Rejected
+        );
+    }
 
-        prop.status = Status::Open;
-        let a = Uint128::from(123u128);
-        let deposit_info1 = Some(vec![Coin {
-            denom: "coin".to_string(),
-            amount: a,
-        }])
-        .unwrap();
-        let mut _vot = VOTERDEPOSIT.save(&mut deps.storage, (id, &info.sender), &deposit_info1);
-        _vot = PROPOSALS.save(&mut deps.storage, id, &prop);
-        prop.status = Status::Passed;
-        _prop = PROPOSALS.save(&mut deps.storage, id, &prop);
-        let k = execute_refund(deps.as_mut(), mock_env(), info.clone(), id);
-        match g {
-            ContractError::CustomError { .. } => {}
-            e => panic!("{:?}", e),
+    const CONTRACT_ADDR: &str = "fury14hj2tavq8fpesdwxxcu44rty3hh90vhujrvcmstl4zr3txmfvw9s4hmalr";
+
+    fn signed_env() -> Env {
+        let mut env = mock_env();
+        env.contract.address = Addr::unchecked(CONTRACT_ADDR);
+        env
+    }
+
+    // local secp256k1 key and the matching bech32 account address
+    fn signer(seed: u8) -> (SigningKey, String) {
+        use bech32::{ToBase32, Variant};
+        use ripemd::Ripemd160;
+        use sha2::{Digest, Sha256};
+
+        let key = SigningKey::from_bytes(&[seed; 32].into()).unwrap();
+        let pubkey = key.verifying_key().to_encoded_point(true);
+        let account = Ripemd160::digest(Sha256::digest(pubkey.as_bytes()));
+        let address = bech32::encode("fury", account.to_base32(), Variant::Bech32).unwrap();
+        (key, address)
+    }
+
+    fn sign_ballot(
+        key: &SigningKey,
+        env: &Env,
+        proposal_id: u64,
+        vote: Vote,
+        nonce: u64,
+    ) -> SignedBallot {
+        use k256::ecdsa::signature::hazmat::PrehashSigner;
+        use sha2::{Digest, Sha256};
+
+        let payload = SignedVotePayload {
+            chain_id: env.block.chain_id.clone(),
+            contract: env.contract.address.to_string(),
+            proposal_id,
+            vote,
+            conviction: None,
+            option: None,
+            nonce,
         };
+        let hash = Sha256::digest(to_json_binary(&payload).unwrap().as_slice());
+        let signature: k256::ecdsa::Signature = key.sign_prehash(&hash).unwrap();
+        let signature = signature.normalize_s().unwrap_or(signature);
+        SignedBallot {
+            proposal_id,
+            vote,
+            conviction: None,
+            option: None,
+            nonce,
+            pubkey: Binary::from(key.verifying_key().to_encoded_point(true).as_bytes()),
+            signature: Binary::from(signature.to_bytes().to_vec()),
+        }
     }
 
-    //   Deposit Testcase
     #[test]
-    #[ignore = "Invalid"]
-    fn test_deposit() {
-        let mut deps = mock_dependencies1();
-        let a = Uint128::from(123u128);
-        let info = mock_info(
-            OWNER,
-            &[Coin {
-                denom: "coin".to_string(),
-                amount: a,
-            }],
-        );
-        let ts = cosmwasm_std::Timestamp::from_nanos(1_655_745_339);
-        let a = Uint128::from(123u128);
-        pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
-        let id = next_id(&mut deps.storage).unwrap();
+    fn test_submit_signed_votes() {
+        let (key1, voter1) = signer(1);
+        let (key2, voter2) = signer(2);
+        let balances = [(OWNER, 100), (voter1.as_str(), 300), (voter2.as_str(), 200)];
+        let mut deps = mock_dependencies_with_locking(10_000, &balances);
+        let env = signed_env();
+        let info = mock_info(OWNER, &coins(100, GOV_DENOM));
+        execute_propose(deps.as_mut(), env.clone(), info, mock_propose(1)).unwrap();
+
+        let relayer = mock_info("relayer", &[]);
+        let err = execute_submit_signed_votes(deps.as_mut(), env.clone(), relayer.clone(), vec![]);
+        assert_eq!(err, Err(ContractError::NoBallots {}));
+
+        // a ballot signed for another chain does not verify
+        let mut other_chain = env.clone();
+        other_chain.block.chain_id = "other-chain".to_string();
+        let ballot = sign_ballot(&key1, &other_chain, 1, Vote::No, 0);
+        let err =
+            execute_submit_signed_votes(deps.as_mut(), env.clone(), relayer.clone(), vec![ballot]);
+        assert_eq!(err, Err(ContractError::InvalidSignature {}));
+
+        // a relayer cannot alter the signed vote
+        let mut ballot = sign_ballot(&key1, &env, 1, Vote::No, 0);
+        ballot.vote = Vote::Yes;
+        let err =
+            execute_submit_signed_votes(deps.as_mut(), env.clone(), relayer.clone(), vec![ballot]);
+        assert_eq!(err, Err(ContractError::InvalidSignature {}));
+
+        let ballots = vec![
+            sign_ballot(&key1, &env, 1, Vote::No, 0),
+            sign_ballot(&key2, &env, 1, Vote::Yes, 0),
+        ];
+        execute_submit_signed_votes(deps.as_mut(), env.clone(), relayer.clone(), ballots.clone())
+            .unwrap();
+        let vote = query_vote(deps.as_ref(), 1, voter1.clone()).unwrap();
+        assert_eq!(vote.vote.unwrap().vote, Vote::No);
+        let prop = PROPOSALS.load(deps.as_ref().storage, 1).unwrap();
+        assert_eq!(prop.votes.yes, 300);
+        assert_eq!(prop.votes.no, 300);
+        assert_eq!(query_vote_nonce(deps.as_ref(), voter1).unwrap(), 1);
+
+        // replaying a relayed ballot is rejected
+        let err = execute_submit_signed_votes(deps.as_mut(), env.clone(), relayer, ballots);
+        assert_eq!(err, Err(ContractError::InvalidNonce { expected: 1 }));
+    }
 
-        let mut prop = Proposal {
-            title: "prop".to_string(),
-            start_time: ts,
-            description: "test prop".to_string(),
-            start_height: 43,
-            expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
-            msgs: vec![FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
-            status: Status::Executed,
-            duration: Duration::Time(40),
-            threshold: Threshold::ThresholdQuorum {
-                threshold: Decimal::percent(50),
-                quorum: Decimal::percent(33),
-            },
-            total_weight: 14,
-            votes: Votes {
-                yes: 10,
-                no: 5,
-                abstain: 10,
-                veto: 39,
-            },
-            deposit: vec![Coin {
-                denom: "vote here".to_string(),
-                amount: a,
-            }],
-            proposer: "validator201".to_string(),
-            token_denom: "toVote".to_string(),
-            min_deposit: Uint128::from(45_u64),
-            current_deposit: 56,
-            app_mapping_id: id,
-            is_slashed: true,
+    #[test]
+    fn test_commit_reveal_voting() {
+        let balances = [(OWNER, 100), ("voter1", 300), ("voter2", 200)];
+        let mut deps = mock_dependencies_with_locking(1_000, &balances);
+        let mut settings = AppSettings {
+            commit_reveal: Some(CommitRevealSettings {
+                reveal_period: Duration::Time(500),
+                count_unrevealed_in_quorum: false,
+            }),
+            ..AppSettings::default()
         };
+        APPSETTINGS
+            .save(deps.as_mut().storage, 1, &settings)
+            .unwrap();
+        let info = mock_info(OWNER, &coins(100, GOV_DENOM));
+        execute_propose(deps.as_mut(), mock_env(), info, mock_propose(1)).unwrap();
+
+        let voter1 = mock_info("voter1", &[]);
+        let voter2 = mock_info("voter2", &[]);
+        let err = execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            voter1.clone(),
+            1,
+            Vote::No,
+            None,
+            None,
+            None,
+        );
+        assert_eq!(err, Err(ContractError::SecretBallot {}));
+
+        let commitment = commitment_hash(Vote::No, "salt1", &Addr::unchecked("voter1"));
+        execute_commit_vote(deps.as_mut(), mock_env(), voter1.clone(), 1, commitment).unwrap();
+        let commitment = commitment_hash(Vote::Yes, "salt2", &Addr::unchecked("voter2"));
+        execute_commit_vote(deps.as_mut(), mock_env(), voter2, 1, commitment).unwrap();
+        let prop = PROPOSALS.load(deps.as_ref().storage, 1).unwrap();
+        assert_eq!(prop.votes.no, 0);
+        assert_eq!(prop.unrevealed_weight, 500);
+
+        let err = execute_reveal_vote(
+            deps.as_mut(),
+            mock_env(),
+            voter1.clone(),
+            1,
+            Vote::No,
+            "salt1".to_string(),
+        );
+        assert_eq!(err, Err(ContractError::NotRevealing {}));
 
-        prop.update_status(&mock_env().block);
-        let mut _prop = PROPOSALS.save(&mut deps.storage, id, &prop);
-        let mut _vote = VOTERDEPOSIT.save(&mut deps.storage, (id, &info.sender), &info.funds);
-        let _deposit_info = VOTERDEPOSIT
-            .may_load(&deps.storage, (id, &info.sender))
+        // the reveal phase follows the voting period
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(1000);
+        let prop = query_proposal_detailed(deps.as_ref(), env.clone(), 1).unwrap();
+        assert_eq!(prop.status, Status::Revealing);
+        let commitment = commitment_hash(Vote::Yes, "late", &Addr::unchecked("voter1"));
+        let err = execute_commit_vote(deps.as_mut(), env.clone(), voter1.clone(), 1, commitment);
+        assert_eq!(err, Err(ContractError::NotOpen {}));
+
+        let err = execute_reveal_vote(
+            deps.as_mut(),
+            env.clone(),
+            voter1.clone(),
+            1,
+            Vote::Yes,
+            "salt1".to_string(),
+        );
+        assert_eq!(err, Err(ContractError::InvalidReveal {}));
+        execute_reveal_vote(
+            deps.as_mut(),
+            env.clone(),
+            voter1.clone(),
+            1,
+            Vote::No,
+            "salt1".to_string(),
+        )
+        .unwrap();
+        let prop = PROPOSALS.load(deps.as_ref().storage, 1).unwrap();
+        assert_eq!(prop.votes.no, 300);
+        assert_eq!(prop.unrevealed_weight, 200);
+
+        // voter2 never reveals
+        env.block.time = env.block.time.plus_seconds(500);
+        let prop = query_proposal_detailed(deps.as_ref(), env.clone(), 1).unwrap();
+        assert_eq!(prop.status, Status::Rejected);
+
+        // unrevealed commitments only reach the quorum when the app counts them
+        settings.commit_reveal = Some(CommitRevealSettings {
+            reveal_period: Duration::Time(500),
+            count_unrevealed_in_quorum: true,
+        });
+        APPSETTINGS
+            .save(deps.as_mut().storage, 1, &settings)
             .unwrap();
-        let err = execute_deposit(deps.as_mut(), mock_env(), info.clone(), id);
-        assert_eq!(err, Err(ContractError::CannotDeposit {}));
-        prop.status = Status::Open;
-        _prop = PROPOSALS.save(&mut deps.storage, id, &prop);
-        let a = Uint128::from(123u128);
-        pub const VOTERDEPOSIT: Map<(u64, &Addr), Vec<Coin>> = Map::new("voter deposit");
-        let deposit_info1 = Some(vec![Coin {
-            denom: "coin".to_string(),
-            amount: a,
-        }])
+        let info = mock_info(OWNER, &coins(100, GOV_DENOM));
+        execute_propose(deps.as_mut(), mock_env(), info, mock_propose(1)).unwrap();
+        let commitment = commitment_hash(Vote::No, "salt", &Addr::unchecked("voter1"));
+        execute_commit_vote(deps.as_mut(), mock_env(), voter1, 2, commitment).unwrap();
+        let prop = query_proposal_detailed(deps.as_ref(), env, 2).unwrap();
+        assert_eq!(prop.status, Status::Passed);
+    }
+
+    #[test]
+    fn test_optimistic_proposal() {
+        let balances = [(OWNER, 100), ("voter1", 300), ("voter2", 50)];
+        let mut deps = mock_dependencies_with_locking(1_000, &balances);
+        let mut propose = mock_propose(1);
+        propose.optimistic = true;
+        let info = mock_info(OWNER, &coins(100, GOV_DENOM));
+        let err = execute_propose(deps.as_mut(), mock_env(), info.clone(), propose.clone());
+        assert_eq!(err, Err(ContractError::OptimisticNotEnabled {}));
+
+        let settings = AppSettings {
+            optimistic: Some(OptimisticSettings {
+                allowed_msgs: vec!["msg_whitelist_app_id_vault_interest".to_string()],
+                objection_threshold: Decimal::percent(10),
+            }),
+            ..AppSettings::default()
+        };
+        APPSETTINGS
+            .save(deps.as_mut().storage, 1, &settings)
+            .unwrap();
+        let mut not_allowed = propose.clone();
+        not_allowed.msgs = vec![auction_params_msg(1, 1)];
+        let err = execute_propose(deps.as_mut(), mock_env(), info.clone(), not_allowed);
+        assert_eq!(
+            err,
+            Err(ContractError::MsgNotOptimistic {
+                kind: "msg_add_auction_params".to_string()
+            })
+        );
+
+        execute_propose(deps.as_mut(), mock_env(), info.clone(), propose.clone()).unwrap();
+        execute_propose(deps.as_mut(), mock_env(), info, propose).unwrap();
+
+        // objections below the threshold do not stop the proposal, quorum is not required
+        let voter2 = mock_info("voter2", &[]);
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            voter2,
+            1,
+            Vote::No,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(1000);
+        let prop = query_proposal_detailed(deps.as_ref(), env.clone(), 1).unwrap();
+        assert_eq!(prop.status, Status::Passed);
+        assert!(!prop.escalated);
+
+        // crossing the threshold escalates into a normal vote with a new voting period
+        let mut vote_env = mock_env();
+        vote_env.block.time = vote_env.block.time.plus_seconds(500);
+        let voter1 = mock_info("voter1", &[]);
+        execute_vote(
+            deps.as_mut(),
+            vote_env.clone(),
+            voter1,
+            2,
+            Vote::Veto,
+            None,
+            None,
+            None,
+        )
         .unwrap();
-        let mut _deposit = VOTERDEPOSIT.save(&mut deps.storage, (id, &info.sender), &deposit_info1);
+        let prop = query_proposal_detailed(deps.as_ref(), env.clone(), 2).unwrap();
+        assert!(prop.escalated);
+        assert_eq!(prop.status, Status::Open);
+        assert_eq!(prop.expires, Duration::Time(1000).after(&vote_env.block));
+        env.block.time = env.block.time.plus_seconds(500);
+        let prop = query_proposal_detailed(deps.as_ref(), env, 2).unwrap();
+        assert_eq!(prop.status, Status::Rejected);
+    }
 
-        prop.status = Status::Open;
-        prop.expires = Expiration::Never {};
-        _prop = PROPOSALS.save(&mut deps.storage, id, &prop);
-        //  If the status is not equal to open or pending, the error "CannotDeposit" will appear.
-        let err = execute_deposit(deps.as_mut(), mock_env(), info.clone(), id);
-        assert_ne!(err, Err(ContractError::CannotDeposit {}));
-        assert_ne!(err, Err(ContractError::IncorrectDenomDeposit {}));
+    #[test]
+    fn test_guardian_cancel() {
+        // the guardian role cannot be installed without an expiration
+        let mut sudo_deps = mock_dependencies_with_locking(1_000, &[]);
+        let guardian = Guardian {
+            address: Addr::unchecked("guardian"),
+            expires: Expiration::Never {},
+            review_period: Duration::Time(300),
+        };
+        let err = sudo(
+            sudo_deps.as_mut(),
+            mock_env(),
+            SudoMsg::UpdateGuardian {
+                app_id: 1,
+                guardian: Some(guardian),
+            },
+        );
+        assert_eq!(err, Err(ContractError::GuardianNeverExpires {}));
+
+        let balances = [(OWNER, 100), ("voter1", 300)];
+        let mut deps = mock_dependencies_with_locking(1_000, &balances);
+        let guardian = Guardian {
+            address: Addr::unchecked("guardian"),
+            expires: Expiration::AtTime(mock_env().block.time.plus_seconds(10_000)),
+            review_period: Duration::Time(300),
+        };
+        let settings = AppSettings {
+            guardian: Some(guardian.clone()),
+            ..AppSettings::default()
+        };
+        APPSETTINGS
+            .save(deps.as_mut().storage, 1, &settings)
+            .unwrap();
 
-        let z = execute_deposit(deps.as_mut(), mock_env(), info.clone(), id);
+        let mut rotate = mock_propose(1);
+        rotate.msgs = vec![];
+        rotate.set_guardian = Some(Guardian {
+            address: Addr::unchecked("council"),
+            ..guardian
+        });
+        for propose in [mock_propose(1), mock_propose(1), rotate] {
+            let info = mock_info(OWNER, &coins(100, GOV_DENOM));
+            let res = execute_propose(deps.as_mut(), mock_env(), info, propose).unwrap();
+            let id: u64 = res.attributes[2].value.parse().unwrap();
+            let voter1 = mock_info("voter1", &[]);
+            execute_vote(
+                deps.as_mut(),
+                mock_env(),
+                voter1,
+                id,
+                Vote::Yes,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        }
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(1000);
+        let anyone = mock_info("anyone", &[]);
+        let err = execute_execute(deps.as_mut(), env.clone(), anyone.clone(), 1);
+        assert_eq!(err, Err(ContractError::InReviewWindow {}));
+        let err = execute_cancel_proposal(
+            deps.as_mut(),
+            env.clone(),
+            anyone.clone(),
+            1,
+            "malicious".to_string(),
+        );
+        assert_eq!(err, Err(ContractError::Unauthorized {}));
+
+        let guardian_info = mock_info("guardian", &[]);
+        let res = execute_cancel_proposal(
+            deps.as_mut(),
+            env.clone(),
+            guardian_info.clone(),
+            1,
+            "malicious".to_string(),
+        )
+        .unwrap();
         assert_eq!(
-            z,
-            Ok(Response::new()
-                .add_attribute("action", "deposit")
-                .add_attribute("depositor", info.sender)
-                .add_attribute("proposal_id", id.to_string()))
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: OWNER.to_string(),
+                amount: coins(100, GOV_DENOM),
+            })
+        );
+        let cancellation: Option<Cancellation> = from_json(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::Cancellation { proposal_id: 1 },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(cancellation.unwrap().reason, "malicious");
+        let prop = query_proposal_detailed(deps.as_ref(), env.clone(), 1).unwrap();
+        assert_eq!(prop.status, Status::Cancelled);
+        let err = execute_execute(deps.as_mut(), env.clone(), anyone.clone(), 1);
+        assert_eq!(err, Err(ContractError::WrongExecuteStatus {}));
+
+        // the guardian cannot block its own rotation
+        let err = execute_cancel_proposal(
+            deps.as_mut(),
+            env.clone(),
+            guardian_info.clone(),
+            3,
+            "no".to_string(),
         );
+        assert_eq!(err, Err(ContractError::CannotCancelGuardianUpdate {}));
+        execute_execute(deps.as_mut(), env.clone(), anyone.clone(), 3).unwrap();
+        let settings = APPSETTINGS.load(deps.as_ref().storage, 1).unwrap();
+        assert_eq!(settings.guardian.unwrap().address, "council");
+
+        // after the review window the proposal is executable and no longer cancellable
+        env.block.time = env.block.time.plus_seconds(300);
+        let guardian_info = mock_info("council", &[]);
+        let err =
+            execute_cancel_proposal(deps.as_mut(), env.clone(), guardian_info, 2, String::new());
+        assert_eq!(err, Err(ContractError::ReviewWindowOver {}));
+        execute_execute(deps.as_mut(), env, anyone, 2).unwrap();
     }
 
-    //Vote Testcase
     #[test]
-    fn test_vote() {
-        let mut deps = mock_dependencies1();
-        let a = Uint128::from(123u128);
-        let info = mock_info(OWNER, &[]);
-        let ts = cosmwasm_std::Timestamp::from_nanos(1_655_827_190);
-        pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
-        let id = next_id(&mut deps.storage).unwrap();
-        let mut prop = Proposal {
-            title: "prop".to_string(),
-            start_time: ts,
-            description: "test prop".to_string(),
-            start_height: 43,
-            expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_897_190)),
-            msgs: vec![FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: id }],
-            status: Status::Passed,
-            duration: Duration::Time(400000000),
-            threshold: Threshold::ThresholdQuorum {
-                threshold: Decimal::percent(50),
-                quorum: Decimal::percent(33),
-            },
-            total_weight: 40,
-            votes: Votes {
-                yes: 32,
-                no: 24,
-                abstain: 10,
-                veto: 3,
-            },
-            deposit: vec![Coin {
-                denom: "vote here".to_string(),
-                amount: a,
-            }],
-            proposer: "validator201".to_string(),
-            token_denom: "toVote".to_string(),
-            min_deposit: Uint128::from(45_u64),
-            current_deposit: 56,
-            app_mapping_id: id,
-            is_slashed: false,
+    fn test_expedited_proposal() {
+        let balances = [(OWNER, 100), ("voter1", 300), ("voter2", 300)];
+        let mut deps = mock_dependencies_with_locking(1_000, &balances);
+        let mut propose = mock_propose(1);
+        propose.expedited = true;
+        let info = mock_info(OWNER, &coins(200, GOV_DENOM));
+        let err = execute_propose(deps.as_mut(), mock_env(), info.clone(), propose.clone());
+        assert_eq!(err, Err(ContractError::ExpeditedNotEnabled {}));
+
+        let settings = AppSettings {
+            expedited: Some(ExpeditedSettings {
+                voting_period: 200,
+                threshold: Decimal::percent(67),
+                deposit_multiplier: 2,
+            }),
+            ..AppSettings::default()
         };
+        APPSETTINGS
+            .save(deps.as_mut().storage, 1, &settings)
+            .unwrap();
 
-        let mut _prop = PROPOSALS.save(&mut deps.storage, id, &prop);
+        // the regular minimum deposit is not enough to open an expedited proposal
+        let low_deposit = mock_info(OWNER, &coins(150, GOV_DENOM));
+        execute_propose(deps.as_mut(), mock_env(), low_deposit, propose.clone()).unwrap();
+        let prop = query_proposal_detailed(deps.as_ref(), mock_env(), 1).unwrap();
+        assert_eq!(prop.status, Status::Pending);
 
-        let prop1 = PROPOSALS.load(&deps.storage, id).unwrap();
-        assert_eq!(prop1.current_status(&mock_env().block), Status::Passed);
+        execute_propose(deps.as_mut(), mock_env(), info.clone(), propose.clone()).unwrap();
+        execute_propose(deps.as_mut(), mock_env(), info, propose).unwrap();
+        let voter1 = mock_info("voter1", &[]);
+        let voter2 = mock_info("voter2", &[]);
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            voter1.clone(),
+            2,
+            Vote::Yes,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            voter1,
+            3,
+            Vote::Yes,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            voter2.clone(),
+            3,
+            Vote::No,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
 
-        // If the status is not equal to "open," an error message will appear.
-        let k = execute_vote(deps.as_mut(), mock_env(), info.clone(), id, Vote::Yes);
-        assert_eq!(k, Err(ContractError::NotOpen {}));
-        prop.status = Status::Open;
-        prop.expires = Expiration::Never {};
-        _prop = PROPOSALS.save(&mut deps.storage, id, &prop);
-        let prop1 = PROPOSALS.load(&deps.storage, id).unwrap();
-        assert_eq!(prop1.expires, Expiration::Never {});
-        let _m = execute_vote(deps.as_mut(), mock_env(), info, id, Vote::Yes);
-        assert_eq!(prop1.status, Status::Open);
-        assert_eq!(prop1.current_status(&mock_env().block), Status::Open);
-        assert!(!prop.expires.is_expired(&mock_env().block));
-        assert_eq!(prop1.current_status(&mock_env().block), Status::Open);
-        assert_eq!(prop.current_status(&mock_env().block), Status::Open);
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(100);
+        let prop = query_proposal_detailed(deps.as_ref(), env.clone(), 2).unwrap();
         assert_eq!(prop.status, Status::Open);
+
+        // the stricter threshold is met at the short expiry
+        env.block.time = env.block.time.plus_seconds(100);
+        let prop = query_proposal_detailed(deps.as_ref(), env.clone(), 2).unwrap();
+        assert_eq!(prop.status, Status::Passed);
+
+        // 4/7 support falls back to a regular proposal with the normal duration
+        let prop = query_proposal_detailed(deps.as_ref(), env.clone(), 3).unwrap();
+        assert_eq!(prop.status, Status::Open);
+        execute_vote(
+            deps.as_mut(),
+            env.clone(),
+            voter2,
+            3,
+            Vote::No,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let prop = query_proposal_detailed(deps.as_ref(), env.clone(), 3).unwrap();
+        assert_eq!(prop.expedited, None);
+        env.block.time = env.block.time.plus_seconds(800);
+        let prop = query_proposal_detailed(deps.as_ref(), env, 3).unwrap();
+        assert_eq!(prop.status, Status::Passed);
     }
 
     #[test]
-    fn test_slash() {
-        let mut deps = mock_dependencies1();
-        let info = mock_info(OWNER, &[]);
-        let ts = cosmwasm_std::Timestamp::from_nanos(1_655_745_339);
-        let a = Uint128::from(123u128);
-        pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
-        let id = next_id(&mut deps.storage).unwrap();
-
-        let expected_msg = InstantiateMsg {
-            threshold: Threshold::ThresholdQuorum {
-                threshold: Decimal::percent(50),
-                quorum: Decimal::percent(33),
-            },
-            locking_contract: Addr::unchecked("locking_contract"),
+    fn test_message_categories() {
+        let whitelist = Category {
+            name: "whitelist".to_string(),
+            msgs: vec!["msg_whitelist_app_id_vault_interest".to_string()],
+            threshold: Decimal::percent(50),
+            quorum: Decimal::percent(10),
+            deposit_multiplier: 1,
+            timelock: 0,
         };
-        instantiate(deps.as_mut(), mock_env(), info.clone(), expected_msg).unwrap();
-        let mut prop = Proposal {
-            title: "prop".to_string(),
-            start_time: ts,
-            description: "test prop".to_string(),
-            start_height: 43,
-            expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
-            msgs: vec![FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
-            status: Status::Rejected,
-            duration: Duration::Time(40),
-            threshold: Threshold::ThresholdQuorum {
-                threshold: Decimal::percent(50),
-                quorum: Decimal::percent(33),
-            },
-            total_weight: 40,
-            votes: Votes {
-                yes: 32,
-                no: 24,
-                abstain: 10,
-                veto: 40,
-            },
-            deposit: vec![Coin {
-                denom: "vote here".to_string(),
-                amount: a,
-            }],
-            proposer: "validator201".to_string(),
-            token_denom: "toVote".to_string(),
-            min_deposit: Uint128::from(45_u64),
-            current_deposit: 56,
-            app_mapping_id: 33,
-            is_slashed: false,
+        let emergency = Category {
+            name: "emergency".to_string(),
+            msgs: vec!["msg_add_auction_params".to_string()],
+            threshold: Decimal::percent(75),
+            quorum: Decimal::percent(40),
+            deposit_multiplier: 3,
+            timelock: 100,
         };
-        prop.expires = Expiration::Never {};
-        prop.update_status(&mock_env().block);
 
-        //If the majority of votes are vetoed, the Slash should be elected.
-        let _k = PROPOSALS.save(&mut deps.storage, id, &prop);
-        let res = execute_slash(deps.as_mut(), mock_env(), info.clone(), id);
-        assert_ne!(res, Err(ContractError::NotRejected {}));
-        assert_ne!(res, Err(ContractError::AlreadySlashed {}));
-        let prop1 = PROPOSALS.load(&deps.storage, id).unwrap();
-        // After running slash The is_slashed should be true.
-        assert_eq!(prop1.is_slashed, true);
+        let mut sudo_deps = mock_dependencies_with_locking(1_000, &[]);
+        let mut duplicate = emergency.clone();
+        duplicate.msgs = whitelist.msgs.clone();
+        let err = sudo(
+            sudo_deps.as_mut(),
+            mock_env(),
+            SudoMsg::UpdateCategories {
+                categories: vec![whitelist.clone(), duplicate],
+            },
+        );
         assert_eq!(
-            res,
-            Ok(Response::new()
-                .add_message(FuryMessages::MsgBurnGovTokensForApp {
-                    app_id: prop.app_mapping_id,
-                    amount: Coin {
-                        denom: "toVote".to_string(),
-                        amount: Uint128::from(56u128)
-                    },
-                    from: "cosmos2contract".to_string(),
-                })
-                .add_attribute("action", "Slash")
-                .add_attribute("trigger_address", info.sender)
-                .add_attribute("proposal_id", id.to_string()))
+            err,
+            Err(ContractError::DuplicateCategoryMsg {
+                kind: "msg_whitelist_app_id_vault_interest".to_string()
+            })
         );
-    }
 
-    #[test]
-    fn test_query() {
-        let mut deps = mock_dependencies1();
-        let info = mock_info(OWNER, &[]);
-        let ts = cosmwasm_std::Timestamp::from_nanos(1_655_745_339);
-        let a = Uint128::from(123u128);
-        pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
-        let id = next_id(&mut deps.storage).unwrap();
+        let balances = [(OWNER, 100), ("voter1", 300), ("voter2", 200)];
+        let mut deps = mock_dependencies_with_locking(1_000, &balances);
+        let categories = vec![whitelist, emergency];
+        CATEGORIES.save(deps.as_mut().storage, &categories).unwrap();
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Categories {}).unwrap();
+        assert_eq!(from_json::<Vec<Category>>(&res).unwrap(), categories);
 
-        let expected_msg = InstantiateMsg {
-            threshold: Threshold::ThresholdQuorum {
-                threshold: Decimal::percent(50),
-                quorum: Decimal::percent(33),
+        // a whitelist change passes with the lower quorum of its category
+        let info = mock_info(OWNER, &coins(100, GOV_DENOM));
+        execute_propose(deps.as_mut(), mock_env(), info.clone(), mock_propose(1)).unwrap();
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(1000);
+        let prop = query_proposal_detailed(deps.as_ref(), env.clone(), 1).unwrap();
+        assert_eq!(prop.category, Some("whitelist".to_string()));
+        assert_eq!(prop.status, Status::Passed);
+
+        // an emergency change needs three times the deposit and a higher threshold
+        let mut propose = mock_propose(1);
+        propose.msgs = vec![auction_params_msg(1, 1)];
+        execute_propose(deps.as_mut(), mock_env(), info, propose.clone()).unwrap();
+        let prop = query_proposal_detailed(deps.as_ref(), mock_env(), 2).unwrap();
+        assert_eq!(prop.status, Status::Pending);
+        let info = mock_info(OWNER, &coins(300, GOV_DENOM));
+        execute_propose(deps.as_mut(), mock_env(), info.clone(), propose.clone()).unwrap();
+        execute_propose(deps.as_mut(), mock_env(), info.clone(), propose).unwrap();
+        let voter1 = mock_info("voter1", &[]);
+        let voter2 = mock_info("voter2", &[]);
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            voter1.clone(),
+            3,
+            Vote::Yes,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            voter2,
+            3,
+            Vote::No,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            voter1,
+            4,
+            Vote::Yes,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let prop = query_proposal_detailed(deps.as_ref(), env.clone(), 3).unwrap();
+        assert_eq!(prop.status, Status::Rejected);
+
+        // and waits for its timelock before execution
+        let anyone = mock_info("anyone", &[]);
+        let err = execute_execute(deps.as_mut(), env.clone(), anyone.clone(), 4);
+        assert_eq!(err, Err(ContractError::Timelocked {}));
+        env.block.time = env.block.time.plus_seconds(100);
+        execute_execute(deps.as_mut(), env, anyone, 4).unwrap();
+
+        // the strictest category among the options applies
+        let options = vec![
+            ProposalOption {
+                title: "whitelist".to_string(),
+                msgs: vec![FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 1 }],
             },
-            locking_contract: Addr::unchecked("locking_contract"),
-        };
-        instantiate(deps.as_mut(), mock_env(), info.clone(), expected_msg).unwrap();
-        let mut prop = Proposal {
-            title: "prop".to_string(),
-            start_time: ts,
-            description: "test prop".to_string(),
-            start_height: 43,
-            expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
-            msgs: vec![FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
-            status: Status::Passed,
-            duration: Duration::Time(40),
-            threshold: Threshold::ThresholdQuorum {
+            ProposalOption {
+                title: "auction".to_string(),
+                msgs: vec![auction_params_msg(1, 2)],
+            },
+        ];
+        let mut propose = mock_propose(1);
+        propose.msgs = vec![];
+        propose.options = options.clone();
+        execute_propose(deps.as_mut(), mock_env(), info.clone(), propose).unwrap();
+        let prop = query_proposal_detailed(deps.as_ref(), mock_env(), 5).unwrap();
+        assert_eq!(prop.category, Some("emergency".to_string()));
+        assert_eq!(prop.timelock, 100);
+
+        // every requirement is the strictest one among the categories and the default
+        let mut whitelist = categories[0].clone();
+        whitelist.timelock = 200;
+        let categories = vec![whitelist, categories[1].clone()];
+        CATEGORIES.save(deps.as_mut().storage, &categories).unwrap();
+        let mut propose = mock_propose(1);
+        propose.msgs = vec![];
+        propose.options = options;
+        execute_propose(deps.as_mut(), mock_env(), info.clone(), propose.clone()).unwrap();
+        let prop = query_proposal_detailed(deps.as_ref(), mock_env(), 6).unwrap();
+        assert_eq!(prop.category, Some("emergency".to_string()));
+        assert_eq!(prop.timelock, 200);
+        assert_eq!(
+            query_threshold(deps.as_ref(), 6),
+            Ok(ThresholdResponse::ThresholdQuorum {
+                threshold: Decimal::percent(75),
+                quorum: Decimal::percent(40),
+                total_weight: 1000,
+            })
+        );
+        assert_eq!(
+            prop.threshold,
+            Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(75),
+                quorum: Decimal::percent(40),
+            }
+        );
+        propose.options[1].msgs = vec![FuryMessages::MsgWhitelistAppIdLiquidation { app_id: 1 }];
+        execute_propose(deps.as_mut(), mock_env(), info, propose).unwrap();
+        let prop = query_proposal_detailed(deps.as_ref(), mock_env(), 7).unwrap();
+        assert_eq!(prop.category, Some("whitelist".to_string()));
+        assert_eq!(prop.timelock, 200);
+        assert_eq!(
+            prop.threshold,
+            Threshold::ThresholdQuorum {
                 threshold: Decimal::percent(50),
                 quorum: Decimal::percent(33),
-            },
-            total_weight: 14,
-            votes: Votes {
-                yes: 32,
-                no: 24,
-                abstain: 10,
-                veto: 3,
-            },
-            deposit: vec![Coin {
-                denom: "vote here".to_string(),
-                amount: a,
-            }],
-            proposer: "validator201".to_string(),
-            token_denom: "toVote".to_string(),
-            min_deposit: Uint128::from(45_u64),
-            current_deposit: 56,
-            app_mapping_id: 33,
-            is_slashed: true,
-        };
+            }
+        );
+    }
 
-        prop.update_status(&mock_env().block);
-        let _k = PROPOSALS.save(&mut deps.storage, id, &prop);
+    #[test]
+    fn test_execute_revalidates_msgs() {
+        let balances = [(OWNER, 100), ("voter1", 300)];
+        let mut deps = mock_dependencies_with_locking(1_000, &balances);
+        let info = mock_info(OWNER, &coins(100, GOV_DENOM));
+        execute_propose(deps.as_mut(), mock_env(), info, mock_propose(1)).unwrap();
+        let voter1 = mock_info("voter1", &[]);
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            voter1,
+            1,
+            Vote::Yes,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
 
-        // Threshold should be from ThreshouldQuorm
-        let res = query_threshold(deps.as_ref(), id);
+        // the app got whitelisted by someone else in the meantime
+        let querier = std::mem::replace(&mut deps.querier, MockQuerier::new(&[]));
+        deps.querier = querier.with_custom_handler(|_| {
+            SystemResult::Ok(ContractResult::Ok(
+                to_json_binary(&MessageValidateResponse {
+                    found: false,
+                    err: "app already whitelisted".to_string(),
+                })
+                .unwrap(),
+            ))
+        });
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(1000);
+        let anyone = mock_info("anyone", &[]);
+        let res = execute_execute(deps.as_mut(), env.clone(), anyone.clone(), 1).unwrap();
+        assert!(res.messages.is_empty());
+        let prop = query_proposal_detailed(deps.as_ref(), env.clone(), 1).unwrap();
+        assert_eq!(prop.status, Status::ExecutionFailed);
+        assert_eq!(
+            prop.execution_error,
+            Some(
+                ContractError::ProposalError {
+                    err: "app already whitelisted".to_string()
+                }
+                .to_string()
+            )
+        );
+        // a retry validates the messages again
+        let res = execute_execute(deps.as_mut(), env.clone(), anyone, 1).unwrap();
+        assert!(res.messages.is_empty());
+
+        // deposits of a failed proposal can be refunded
+        let info = mock_info(OWNER, &[]);
+        let res = execute_refund(deps.as_mut(), env, info, 1).unwrap();
         assert_eq!(
-            res,
-            Ok(ThresholdResponse::ThresholdQuorum {
-                threshold: (Decimal::percent(50)),
-                quorum: (Decimal::percent(33)),
-                total_weight: (14)
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: OWNER.to_string(),
+                amount: coins(100, GOV_DENOM),
             })
         );
+    }
 
-        // Query_propsal_detailed should give deatails respected to propsal id
-        let res = query_proposal_detailed(deps.as_ref(), mock_env(), id);
-        let ts = cosmwasm_std::Timestamp::from_nanos(1_655_745_339);
+    #[test]
+    fn test_execute_reply_and_retry() {
+        let balances = [(OWNER, 100), ("voter1", 300)];
+        let mut deps = mock_dependencies_with_locking(1_000, &balances);
+        let settings = AppSettings {
+            execution_window: Some(500),
+            ..AppSettings::default()
+        };
+        APPSETTINGS
+            .save(deps.as_mut().storage, 1, &settings)
+            .unwrap();
+        let info = mock_info(OWNER, &coins(100, GOV_DENOM));
+        execute_propose(deps.as_mut(), mock_env(), info, mock_propose(1)).unwrap();
+        let voter1 = mock_info("voter1", &[]);
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            voter1,
+            1,
+            Vote::Yes,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(1000);
+        let anyone = mock_info("anyone", &[]);
+        let res = execute_execute(deps.as_mut(), env.clone(), anyone.clone(), 1).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(res.messages[0].id, 1);
+        assert_eq!(res.messages[0].reply_on, ReplyOn::Always);
+        // the outcome is only recorded once the replies arrived
+        let prop = PROPOSALS.load(deps.as_ref().storage, 1).unwrap();
+        assert_eq!(prop.outcome, None);
+        assert_ne!(prop.status, Status::Executed);
+
+        // the chain module rejects the message
+        let failed = Reply {
+            id: 1,
+            result: SubMsgResult::Err("pair does not exist".to_string()),
+        };
+        reply(deps.as_mut(), env.clone(), failed).unwrap();
+        let prop = query_proposal_detailed(deps.as_ref(), env.clone(), 1).unwrap();
+        assert_eq!(prop.status, Status::ExecutionFailed);
         assert_eq!(
-            res,
-            Ok(msg::ProposalResponseTotal {
-                id,
-                title: "prop".to_string(),
-                start_time: ts,
-                description: "test prop".to_string(),
-                start_height: 43,
-                expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
-                msgs: vec![FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
-                status: Status::Passed,
-                duration: Duration::Time(40),
-                threshold: Threshold::ThresholdQuorum {
-                    threshold: Decimal::percent(50),
-                    quorum: Decimal::percent(33),
+            prop.execution_error,
+            Some("pair does not exist".to_string())
+        );
+        let results: Vec<MessageResult> = from_json(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::ExecutionResults { proposal_id: 1 },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            results,
+            vec![MessageResult {
+                index: 0,
+                success: false,
+                error: Some("pair does not exist".to_string()),
+                data: None,
+            }]
+        );
+
+        // the retry succeeds and replaces the results of the failed attempt
+        env.block.time = env.block.time.plus_seconds(100);
+        let res = execute_execute(deps.as_mut(), env.clone(), anyone.clone(), 1).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        let succeeded = Reply {
+            id: 1,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        };
+        reply(deps.as_mut(), env.clone(), succeeded).unwrap();
+        let prop = query_proposal_detailed(deps.as_ref(), env.clone(), 1).unwrap();
+        assert_eq!(prop.status, Status::Executed);
+        assert_eq!(prop.execution_error, None);
+        let stats = APPGOVCONFIG.load(deps.as_ref().storage, 1).unwrap();
+        assert_eq!((stats.proposals_executed, stats.proposals_failed), (1, 0));
+        let results = EXECUTIONRESULTS.load(deps.as_ref().storage, 1).unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].success);
+
+        // no retries once the execution window is closed
+        let mut prop = PROPOSALS.load(deps.as_ref().storage, 1).unwrap();
+        prop.status = Status::ExecutionFailed;
+        PROPOSALS.save(deps.as_mut().storage, 1, &prop).unwrap();
+        env.block.time = env.block.time.plus_seconds(400);
+        let err = execute_execute(deps.as_mut(), env, anyone, 1);
+        assert_eq!(err, Err(ContractError::ExecutionWindowClosed {}));
+    }
+
+    #[test]
+    fn test_vote_memo_and_history() {
+        let balances = [(OWNER, 100), ("voter1", 300)];
+        let mut deps = mock_dependencies_with_locking(1_000, &balances);
+        let info = mock_info(OWNER, &coins(100, GOV_DENOM));
+        execute_propose(deps.as_mut(), mock_env(), info, mock_propose(1)).unwrap();
+
+        let voter1 = mock_info("voter1", &[]);
+        let err = execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            voter1.clone(),
+            1,
+            Vote::Yes,
+            None,
+            None,
+            Some("x".repeat(257)),
+        );
+        assert_eq!(err, Err(ContractError::MemoTooLong { max: 256 }));
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            voter1.clone(),
+            1,
+            Vote::Yes,
+            None,
+            None,
+            Some("looks good".to_string()),
+        )
+        .unwrap();
+
+        // a vote change removes the weight the previous ballot was counted with
+        deps.querier = mock_dependencies_with_locking(1_000, &[("voter1", 500)]).querier;
+        let mut env = mock_env();
+        env.block.height += 1;
+        execute_vote(
+            deps.as_mut(),
+            env.clone(),
+            voter1,
+            1,
+            Vote::No,
+            None,
+            None,
+            Some("changed my mind".to_string()),
+        )
+        .unwrap();
+        let prop = query_proposal_detailed(deps.as_ref(), env.clone(), 1).unwrap();
+        assert_eq!(prop.votes.yes, 100);
+        assert_eq!(prop.votes.no, 500);
+
+        let vote = query_vote(deps.as_ref(), 1, "voter1".to_string()).unwrap();
+        assert_eq!(vote.vote.unwrap().memo, Some("changed my mind".to_string()));
+        let history: Vec<VoteRecord> = from_json(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::VoteHistory {
+                    proposal_id: 1,
+                    voter: "voter1".to_string(),
                 },
-                total_weight: 14,
-                votes: Votes {
-                    yes: 32,
-                    no: 24,
-                    abstain: 10,
-                    veto: 3,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].ballot.vote, Vote::Yes);
+        assert_eq!(history[0].ballot.weight, 300);
+        assert_eq!(history[0].ballot.memo, Some("looks good".to_string()));
+        assert_eq!(history[1].ballot.vote, Vote::No);
+        assert_eq!(history[1].height, env.block.height);
+    }
+
+    #[test]
+    fn test_votes_by_voter() {
+        let balances = [(OWNER, 100), ("voter1", 300), ("voter2", 600)];
+        let mut deps = mock_dependencies_with_locking(1_000, &balances);
+        let info = mock_info(OWNER, &coins(100, GOV_DENOM));
+        execute_propose(deps.as_mut(), mock_env(), info.clone(), mock_propose(1)).unwrap();
+        execute_propose(deps.as_mut(), mock_env(), info.clone(), mock_propose(2)).unwrap();
+        execute_propose(deps.as_mut(), mock_env(), info, mock_propose(1)).unwrap();
+
+        let voter1 = mock_info("voter1", &[]);
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            voter1.clone(),
+            1,
+            Vote::Yes,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            voter1.clone(),
+            2,
+            Vote::No,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        // changing a vote does not count as another participation
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            voter1,
+            2,
+            Vote::Abstain,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let voter2 = mock_info("voter2", &[]);
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            voter2,
+            1,
+            Vote::Veto,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let votes = list_votes_by_voter(deps.as_ref(), "voter1".to_string(), None, None, None)
+            .unwrap()
+            .votes;
+        assert_eq!(
+            votes
+                .iter()
+                .map(|vote| (vote.proposal_id, vote.vote))
+                .collect::<Vec<_>>(),
+            vec![(1, Vote::Yes), (2, Vote::Abstain)]
+        );
+        let votes = list_votes_by_voter(deps.as_ref(), "voter1".to_string(), Some(2), None, None)
+            .unwrap()
+            .votes;
+        assert_eq!(votes.len(), 1);
+        assert_eq!(votes[0].proposal_id, 2);
+        let votes = list_votes_by_voter(deps.as_ref(), "voter1".to_string(), None, Some(1), None)
+            .unwrap()
+            .votes;
+        assert_eq!(votes[0].proposal_id, 2);
+
+        // voter1 left out one of the proposals of the apps they vote in
+        let stats = query_voter_stats(deps.as_ref(), "voter1".to_string()).unwrap();
+        assert_eq!(stats.proposals_voted, 2);
+        assert_eq!(stats.participation_rate, Decimal::from_ratio(2u128, 3u128));
+        let stats = query_voter_stats(deps.as_ref(), "voter2".to_string()).unwrap();
+        assert_eq!(stats.participation_rate, Decimal::from_ratio(1u128, 2u128));
+
+        // the vetoed proposal is slashed
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(1000);
+        execute_slash(deps.as_mut(), env, mock_info("anyone", &[]), 1).unwrap();
+        let stats: VoterStatsResponse = from_json(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::VoterStats {
+                    voter: OWNER.to_string(),
                 },
-                proposer: "validator201".to_string(),
-                token_denom: "toVote".to_string(),
-                current_deposit: 56
-            })
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            stats,
+            VoterStatsResponse {
+                voter: OWNER.to_string(),
+                proposals_voted: 3,
+                proposals_created: 3,
+                deposits_slashed: 100,
+                participation_rate: Decimal::zero(),
+            }
         );
+    }
 
-        // List Proposal Should give list of propsals
-        let res = list_proposals(deps.as_ref(), mock_env(), Option::None, Option::None);
+    #[test]
+    fn test_app_gov_stats() {
+        let balances = [(OWNER, 100), ("voter1", 300), ("voter2", 600)];
+        let mut deps = mock_dependencies_with_locking(1_000, &balances);
+        // apps without proposals have no stats yet
+        let err = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::AppAllUpData { app_id: 1 },
+        );
+        assert!(err.is_err());
+
+        let info = mock_info(OWNER, &coins(100, GOV_DENOM));
+        execute_propose(deps.as_mut(), mock_env(), info.clone(), mock_propose(1)).unwrap();
+        execute_propose(deps.as_mut(), mock_env(), info, mock_propose(1)).unwrap();
+        let voter1 = mock_info("voter1", &[]);
+        let voter2 = mock_info("voter2", &[]);
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            voter1.clone(),
+            1,
+            Vote::Yes,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            voter1.clone(),
+            1,
+            Vote::Abstain,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            voter1,
+            2,
+            Vote::No,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            voter2,
+            2,
+            Vote::Veto,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(1000);
+        let anyone = mock_info("anyone", &[]);
+        execute_slash(deps.as_mut(), env.clone(), anyone.clone(), 2).unwrap();
+        execute_refund(deps.as_mut(), env.clone(), mock_info(OWNER, &[]), 1).unwrap();
+        let res = execute_execute(deps.as_mut(), env.clone(), anyone, 1).unwrap();
+        reply_succeeded(&mut deps, &env, &res);
+
+        let stats: AppGovConfig =
+            from_json(&query(deps.as_ref(), env, QueryMsg::AppAllUpData { app_id: 1 }).unwrap())
+                .unwrap();
         assert_eq!(
-            res,
-            Ok(ProposalListResponse {
-                proposals: vec![ProposalResponse {
-                    id: id,
-                    title: "prop".to_string(),
-                    description: "test prop".to_string(),
-                    msgs: vec![FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
-                    status: Status::Passed,
-                    expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
-                    threshold: ThresholdResponse::ThresholdQuorum {
-                        threshold: Decimal::percent(50),
-                        quorum: Decimal::percent(33),
-                        total_weight: 14,
-                    }
-                }]
-            })
+            stats,
+            AppGovConfig {
+                proposal_count: 2,
+                current_supply: 1_000,
+                active_participation_supply: 1_400,
+                proposals_executed: 1,
+                proposals_failed: 0,
+                proposals_rejected: 0,
+                proposals_vetoed: 1,
+                proposals_cancelled: 0,
+                votes_cast: 5,
+                unique_voters: 3,
+                deposits_locked: 0,
+                deposits_refunded: 100,
+                deposits_slashed: 100,
+            }
         );
+    }
 
-        let res = reverse_proposals(deps.as_ref(), mock_env(), Option::None, Option::None);
+    #[test]
+    fn test_gov_stats() {
+        let balances = [(OWNER, 100), ("voter1", 300), ("voter2", 600)];
+        let mut deps = mock_dependencies_with_locking(1_000, &balances);
+        let info = mock_info(OWNER, &coins(100, GOV_DENOM));
+        execute_propose(deps.as_mut(), mock_env(), info, mock_propose(1)).unwrap();
+        let info = mock_info(OWNER, &coins(50, GOV_DENOM));
+        execute_propose(deps.as_mut(), mock_env(), info, mock_propose(1)).unwrap();
+
+        // the second proposal reaches its minimum deposit after 500 seconds
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(500);
+        let depositor = mock_info("voter1", &coins(60, GOV_DENOM));
+        execute_deposit(deps.as_mut(), env.clone(), depositor, 2).unwrap();
+        let voter1 = mock_info("voter1", &[]);
+        execute_vote(
+            deps.as_mut(),
+            env.clone(),
+            voter1,
+            1,
+            Vote::Yes,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let voter2 = mock_info("voter2", &[]);
+        execute_vote(
+            deps.as_mut(),
+            env.clone(),
+            voter2,
+            2,
+            Vote::Veto,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        env.block.time = env.block.time.plus_seconds(500);
+        let anyone = mock_info("anyone", &[]);
+        let res = execute_execute(deps.as_mut(), env.clone(), anyone.clone(), 1).unwrap();
+        reply_succeeded(&mut deps, &env, &res);
+        execute_slash(deps.as_mut(), env.clone(), anyone, 2).unwrap();
+
+        let stats: GovStatsResponse = from_json(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::GovStats {
+                    app_id: 1,
+                    from: mock_env().block.time,
+                    to: env.block.time,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
         assert_eq!(
-            res,
-            Ok(ProposalListResponse {
-                proposals: vec![ProposalResponse {
-                    id: id,
-                    title: "prop".to_string(),
-                    description: "test prop".to_string(),
-                    msgs: vec![FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
-                    status: Status::Passed,
-                    expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
-                    threshold: ThresholdResponse::ThresholdQuorum {
-                        threshold: Decimal::percent(50),
-                        quorum: Decimal::percent(33),
-                        total_weight: 14,
-                    }
-                }]
-            })
+            stats,
+            GovStatsResponse {
+                app_id: 1,
+                proposals_created: 2,
+                proposals_decided: 2,
+                pass_rate: Decimal::percent(50),
+                reject_rate: Decimal::percent(50),
+                veto_rate: Decimal::percent(50),
+                average_turnout: Decimal::percent(55),
+                median_deposit_time: Some(0),
+                msg_counts: vec![("msg_whitelist_app_id_vault_interest".to_string(), 2)]
+                    .into_iter()
+                    .collect(),
+            }
         );
 
-        let cfg = Config {
-            threshold: Threshold::ThresholdQuorum {
-                threshold: Decimal::percent(50),
-                quorum: Decimal::percent(33),
-            },
-            locking_contract: Addr::unchecked("locking_contract"),
-        };
-        _ = CONFIG.save(&mut deps.storage, &cfg);
+        // deposit times are kept in fixed buckets, the median is estimated within its bucket
+        let mut period = PeriodStats::default();
+        for seconds in [30, 400, 500, 90_000] {
+            period.record_deposit_time(seconds);
+        }
+        assert_eq!(period.deposit_time_buckets.len(), 13);
+        assert_eq!(period.median_deposit_time(), Some(330));
+
+        // nothing happened in earlier periods
+        let from = mock_env().block.time.minus_seconds(2 * STATS_PERIOD);
+        let stats = query_gov_stats(deps.as_ref(), 1, from, from).unwrap();
+        assert_eq!(stats.proposals_created, 0);
+        assert_eq!(stats.median_deposit_time, None);
+    }
 
-        let ballot = Ballot {
-            weight: 10,
-            vote: Vote::Yes,
-        };
-        _ = BALLOTS.save(&mut deps.storage, (id, &info.sender), &ballot);
+    #[test]
+    fn test_process_expired() {
+        let balances = [(OWNER, 100), ("voter1", 300), ("voter2", 600)];
+        let mut deps = mock_dependencies_with_locking(1_000, &balances);
+        let info = mock_info(OWNER, &coins(100, GOV_DENOM));
+        for _ in 0..3 {
+            execute_propose(deps.as_mut(), mock_env(), info.clone(), mock_propose(1)).unwrap();
+        }
+        let voter1 = mock_info("voter1", &[]);
+        let voter2 = mock_info("voter2", &[]);
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            voter1.clone(),
+            1,
+            Vote::Yes,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            voter2,
+            2,
+            Vote::Veto,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            voter1,
+            3,
+            Vote::No,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
 
-        // Query Vote should show the Vote respected to voter and Propasal id.
-        let res = query_vote(deps.as_ref(), id, OWNER.to_string());
+        // nothing is due before the proposals expire
+        let res = sudo(
+            deps.as_mut(),
+            mock_env(),
+            SudoMsg::ProcessExpired { limit: 10 },
+        )
+        .unwrap();
+        assert!(res.messages.is_empty());
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(1000);
+        let res = sudo(
+            deps.as_mut(),
+            env.clone(),
+            SudoMsg::ProcessExpired { limit: 2 },
+        )
+        .unwrap();
+        // the passed proposal is executed and its deposit returned
         assert_eq!(
-            res,
-            Ok(VoteResponse {
-                vote: Some(VoteInfo {
-                    proposal_id: 1,
-                    voter: "admin0001".to_string(),
-                    vote: Vote::Yes,
-                    weight: 10
-                })
+            res.messages[0],
+            SubMsg::reply_always(
+                FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 1 },
+                1
+            )
+        );
+        assert_eq!(
+            res.messages[1].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: OWNER.to_string(),
+                amount: coins(100, GOV_DENOM),
             })
         );
-
-        // List Votes should show the VoteInfo respected to proposal id.
-        let res = list_votes(deps.as_ref(), id, Option::None, Option::None);
+        // the vetoed proposal is slashed
         assert_eq!(
-            res,
-            Ok(VoteListResponse {
-                votes: vec![VoteInfo {
-                    proposal_id: 1,
-                    voter: "admin0001".to_string(),
-                    vote: Vote::Yes,
-                    weight: 10
-                }]
+            res.messages[2].msg,
+            CosmosMsg::Custom(FuryMessages::MsgBurnGovTokensForApp {
+                app_id: 1,
+                amount: Coin {
+                    denom: GOV_DENOM.to_string(),
+                    amount: Uint128::from(100u128),
+                },
+                from: env.contract.address.to_string(),
             })
         );
+        assert_eq!(res.messages.len(), 3);
+        let settled = |res: &Response<FuryMessages>| {
+            res.attributes
+                .iter()
+                .filter(|attr| attr.key == "burned" || attr.key == "returned")
+                .map(|attr| attr.value.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(settled(&res), vec!["1: 100", "2: 100", "2: 0"]);
+        reply_succeeded(&mut deps, &env, &res);
+        assert_eq!(
+            PROPOSALS.load(deps.as_ref().storage, 1).unwrap().status,
+            Status::Executed
+        );
+        assert!(PROPOSALS.load(deps.as_ref().storage, 2).unwrap().is_slashed);
+
+        // the rejected proposal is settled in the next run
+        let res = sudo(
+            deps.as_mut(),
+            env.clone(),
+            SudoMsg::ProcessExpired { limit: 2 },
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(settled(&res), vec!["3: 0", "3: 100"]);
+        let prop = PROPOSALS.load(deps.as_ref().storage, 3).unwrap();
+        assert_eq!(prop.status, Status::Rejected);
+        assert!(EXPIRYQUEUE
+            .keys(deps.as_ref().storage, None, None, Order::Ascending)
+            .next()
+            .is_none());
     }
 
-    #[allow(non_snake_case)]
     #[test]
-    fn test_deposit_non_gov_token() {
-        // Mock deps
+    fn test_proposal_dependencies_and_schedule() {
+        let balances = [(OWNER, 100), ("voter1", 300)];
+        let mut deps = mock_dependencies_with_locking(1_000, &balances);
+        let info = mock_info(OWNER, &coins(100, GOV_DENOM));
+        let mut dependent = mock_propose(1);
+        dependent.depends_on = vec![9];
+        let err = execute_propose(deps.as_mut(), mock_env(), info.clone(), dependent.clone());
+        assert_eq!(
+            err,
+            Err(ContractError::UnknownDependency { proposal_id: 9 })
+        );
+
+        execute_propose(deps.as_mut(), mock_env(), info.clone(), mock_propose(1)).unwrap();
+        let execute_at = Scheduled::AtTime(mock_env().block.time.plus_seconds(1500));
+        dependent.depends_on = vec![1];
+        dependent.execute_at = Some(execute_at);
+        execute_propose(deps.as_mut(), mock_env(), info, dependent).unwrap();
+        let voter1 = mock_info("voter1", &[]);
+        for id in 1..=2 {
+            execute_vote(
+                deps.as_mut(),
+                mock_env(),
+                voter1.clone(),
+                id,
+                Vote::Yes,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        }
+
         let mut env = mock_env();
-        let mut deps = mock_dependencies1();
+        env.block.time = env.block.time.plus_seconds(1000);
+        let prop = query_proposal_detailed(deps.as_ref(), env.clone(), 2).unwrap();
+        assert_eq!(prop.depends_on, vec![1]);
+        assert_eq!(prop.execute_at, Some(execute_at));
+        let anyone = mock_info("anyone", &[]);
+        let err = execute_execute(deps.as_mut(), env.clone(), anyone.clone(), 2);
+        assert_eq!(err, Err(ContractError::NotScheduledYet {}));
+
+        env.block.time = env.block.time.plus_seconds(500);
+        let err = execute_execute(deps.as_mut(), env.clone(), anyone.clone(), 2);
+        assert_eq!(
+            err,
+            Err(ContractError::DependencyNotExecuted { proposal_id: 1 })
+        );
+        let res = execute_execute(deps.as_mut(), env.clone(), anyone.clone(), 1).unwrap();
+        reply_succeeded(&mut deps, &env, &res);
+        let res = execute_execute(deps.as_mut(), env, anyone, 2).unwrap();
+        assert_eq!(res.messages.len(), 1);
+    }
 
-        let DENOM: &str = "DNM";
-        env.block.time = Timestamp::from_seconds(1000);
-        env.block.height = 10;
+    #[test]
+    fn test_cross_app_proposal() {
+        let balances = [(OWNER, 100), ("voter1", 300)];
+        let mut deps = mock_dependencies_with_locking(1_000, &balances);
+        // app 2 only passes with almost its whole supply
+        let settings = AppSettings {
+            threshold: Some(Threshold::AbsoluteCount { weight: 900 }),
+            ..AppSettings::default()
+        };
+        APPSETTINGS
+            .save(deps.as_mut().storage, 2, &settings)
+            .unwrap();
 
-        // Instantiate
-        let imsg = InstantiateMsg {
-            threshold: Threshold::ThresholdQuorum {
-                threshold: Decimal::one(),
-                quorum: Decimal::one(),
+        let info = mock_info(OWNER, &coins(100, GOV_DENOM));
+        let mut propose = mock_propose(1);
+        propose.participating_apps = vec![2, 2];
+        let err = execute_propose(deps.as_mut(), mock_env(), info.clone(), propose.clone());
+        assert_eq!(err, Err(ContractError::DuplicateApp { app_id: 2 }));
+        propose.participating_apps = vec![2];
+        propose.msgs = vec![FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 3 }];
+        let err = execute_propose(deps.as_mut(), mock_env(), info.clone(), propose.clone());
+        assert_eq!(err, Err(ContractError::AppNotParticipating { app_id: 3 }));
+        let err = sudo(
+            deps.as_mut(),
+            mock_env(),
+            SudoMsg::UpdateAppSettings {
+                app_id: 1,
+                settings: Box::new(AppSettings {
+                    required_apps: Some(0),
+                    ..AppSettings::default()
+                }),
             },
-            locking_contract: Addr::unchecked("locking-contract"),
+        );
+        assert_eq!(err, Err(ContractError::InvalidRequiredApps {}));
+
+        // proposal 1 needs both apps, proposals 2 and 3 only one of them, but the message of
+        // proposal 3 changes app 2
+        propose.msgs = vec![FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 1 }];
+        execute_propose(deps.as_mut(), mock_env(), info.clone(), propose.clone()).unwrap();
+        let settings = AppSettings {
+            required_apps: Some(1),
+            ..AppSettings::default()
         };
-        let sender = Addr::unchecked("sender");
-        let info = mock_info(sender.as_str(), &[]);
-        instantiate(deps.as_mut(), env.clone(), info, imsg.clone()).unwrap();
+        APPSETTINGS
+            .save(deps.as_mut().storage, 1, &settings)
+            .unwrap();
+        execute_propose(deps.as_mut(), mock_env(), info.clone(), propose.clone()).unwrap();
+        propose.msgs = vec![FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 2 }];
+        execute_propose(deps.as_mut(), mock_env(), info, propose).unwrap();
+        let voter1 = mock_info("voter1", &[]);
+        for vote in &[Vote::No, Vote::Yes] {
+            for id in 1..=3 {
+                execute_vote(
+                    deps.as_mut(),
+                    mock_env(),
+                    voter1.clone(),
+                    id,
+                    *vote,
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+            }
+        }
 
-        // Save a proposal
-        let proposal = Proposal {
-            title: "title".to_string(),
-            start_time: Timestamp::from_seconds(1200),
-            description: "description".to_string(),
-            start_height: 10,
-            expires: Expiration::AtHeight(20),
-            msgs: vec![],
-            status: Status::Pending,
-            duration: Duration::Height(10),
-            threshold: imsg.threshold,
-            total_weight: 0,
-            votes: Votes {
-                yes: 0,
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(1000);
+        let prop = query_proposal_detailed(deps.as_ref(), env.clone(), 1).unwrap();
+        assert_eq!(prop.participants.len(), 1);
+        assert_eq!(prop.participants[0].app_id, 2);
+        assert_eq!(prop.participants[0].total_weight, 1_000);
+        assert_eq!(
+            prop.participants[0].votes,
+            Votes {
+                yes: 400,
                 no: 0,
                 abstain: 0,
-                veto: 0,
-            },
-            deposit: vec![],
-            proposer: sender.to_string(),
-            token_denom: DENOM.to_string(),
-            min_deposit: Uint128::from(100_u64),
-            current_deposit: 0,
-            app_mapping_id: 1,
-            is_slashed: false,
+                veto: 0
+            }
+        );
+        assert_eq!(prop.status, Status::Rejected);
+        let prop = query_proposal_detailed(deps.as_ref(), env.clone(), 2).unwrap();
+        assert_eq!(prop.status, Status::Passed);
+        let prop = query_proposal_detailed(deps.as_ref(), env.clone(), 3).unwrap();
+        assert_eq!(prop.required_apps, Some(1));
+        assert_eq!(prop.status, Status::Rejected);
+
+        let listed = get_proposals_by_app(deps.as_ref(), env, 2, 0, None, None).unwrap();
+        assert_eq!(listed.proposal_count, 3);
+    }
+
+    #[test]
+    fn test_sponsorship() {
+        let balances = [
+            (OWNER, 100),
+            ("sponsor1", 60),
+            ("sponsor2", 80),
+            ("minnow", 10),
+        ];
+        let mut deps = mock_dependencies_with_locking(1_000, &balances);
+        let settings = AppSettings {
+            sponsorship: Some(SponsorSettings {
+                sponsors_required: 2,
+                min_balance: 50,
+            }),
+            ..AppSettings::default()
+        };
+        APPSETTINGS
+            .save(deps.as_mut().storage, 1, &settings)
+            .unwrap();
+
+        // the full deposit alone does not open the proposal
+        let info = mock_info(OWNER, &coins(100, GOV_DENOM));
+        execute_propose(deps.as_mut(), mock_env(), info.clone(), mock_propose(1)).unwrap();
+        let prop = query_proposal_detailed(deps.as_ref(), mock_env(), 1).unwrap();
+        assert_eq!(prop.status, Status::Pending);
+        assert_eq!(prop.sponsors_required, 2);
+
+        let err = execute_sponsor(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), 1);
+        assert_eq!(err, Err(ContractError::Unauthorized {}));
+        let err = execute_sponsor(deps.as_mut(), mock_env(), mock_info("minnow", &[]), 1);
+        assert_eq!(
+            err,
+            Err(ContractError::InsufficientSponsorBalance { min: 50 })
+        );
+        let sponsor1 = mock_info("sponsor1", &[]);
+        execute_sponsor(deps.as_mut(), mock_env(), sponsor1.clone(), 1).unwrap();
+        let err = execute_sponsor(deps.as_mut(), mock_env(), sponsor1.clone(), 1);
+        assert_eq!(err, Err(ContractError::AlreadySponsored {}));
+        execute_unsponsor(deps.as_mut(), mock_env(), sponsor1.clone(), 1).unwrap();
+        let err = execute_unsponsor(deps.as_mut(), mock_env(), sponsor1.clone(), 1);
+        assert_eq!(err, Err(ContractError::NotSponsor {}));
+
+        execute_sponsor(deps.as_mut(), mock_env(), sponsor1, 1).unwrap();
+        let prop = query_proposal_detailed(deps.as_ref(), mock_env(), 1).unwrap();
+        assert_eq!(prop.status, Status::Pending);
+        execute_sponsor(deps.as_mut(), mock_env(), mock_info("sponsor2", &[]), 1).unwrap();
+        let prop = query_proposal_detailed(deps.as_ref(), mock_env(), 1).unwrap();
+        assert_eq!(prop.status, Status::Open);
+        assert_eq!(
+            prop.sponsors,
+            vec![Addr::unchecked("sponsor1"), Addr::unchecked("sponsor2")]
+        );
+        let err = execute_unsponsor(deps.as_mut(), mock_env(), mock_info("sponsor2", &[]), 1);
+        assert_eq!(err, Err(ContractError::NotPending {}));
+    }
+
+    #[test]
+    fn test_voter_rewards() {
+        let balances = [(OWNER, 100), ("voter1", 300), ("voter2", 100)];
+        let mut deps = mock_dependencies_with_locking(1_000, &balances);
+        let settings = AppSettings {
+            voter_rewards: Some(VoterRewardSettings {
+                pool_share: Decimal::percent(40),
+                min_turnout: Decimal::one(),
+                payout_share: Decimal::percent(50),
+            }),
+            ..AppSettings::default()
         };
+        APPSETTINGS
+            .save(deps.as_mut().storage, 1, &settings)
+            .unwrap();
 
-        PROPOSALS.save(deps.as_mut().storage, 1, &proposal).unwrap();
+        let info = mock_info(OWNER, &coins(100, GOV_DENOM));
+        execute_propose(deps.as_mut(), mock_env(), info, mock_propose(1)).unwrap();
+        for voter in &["voter1", "voter2"] {
+            execute_vote(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(voter, &[]),
+                1,
+                Vote::Veto,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        }
 
-        // Deposit gov token
-        let info = mock_info(sender.as_str(), &coins(100, DENOM));
-        let result = execute_deposit(deps.as_mut(), env.clone(), info.clone(), 1).unwrap();
-        assert_eq!(result.messages.len(), 0);
+        // 40 of the 100 slashed go to the pool, half of the pool to the voters of the proposal
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(1000);
+        let res = execute_slash(deps.as_mut(), env, mock_info("anyone", &[]), 1).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(FuryMessages::MsgBurnGovTokensForApp {
+                app_id: 1,
+                amount: Coin::new(60, GOV_DENOM),
+                from: mock_env().contract.address.to_string(),
+            })]
+        );
+        assert_eq!(VOTERREWARDPOOL.load(deps.as_ref().storage, 1).unwrap(), 20);
+
+        let pending: VoterRewardsResponse = from_json(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::PendingVoterRewards {
+                    voter: "voter2".to_string(),
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            pending,
+            VoterRewardsResponse {
+                rewards: vec![Coin::new(4, GOV_DENOM)],
+                last_proposal_id: None,
+            }
+        );
+        // a page that ends at the limit points to the next one
+        let pending: VoterRewardsResponse = from_json(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::PendingVoterRewards {
+                    voter: "voter2".to_string(),
+                    start_after: None,
+                    limit: Some(1),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(pending.last_proposal_id, Some(1));
 
-        // Check correct update in VOTERDEPOSIT
-        let sender_deposits = VOTERDEPOSIT
-            .load(deps.as_ref().storage, (1, &sender))
-            .unwrap();
-        assert_eq!(sender_deposits.len(), 1);
-        assert_eq!(sender_deposits[0].amount.u128(), 100);
-        assert_eq!(sender_deposits[0].denom, DENOM.to_string());
+        let voter1 = mock_info("voter1", &[]);
+        let err = execute_claim_voter_rewards(deps.as_mut(), voter1.clone(), Some(1), None);
+        assert_eq!(err, Err(ContractError::NoVoterRewards {}));
+        let res = execute_claim_voter_rewards(deps.as_mut(), voter1.clone(), None, None).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "voter1".to_string(),
+                amount: vec![Coin::new(12, GOV_DENOM)],
+            })]
+        );
+        let err = execute_claim_voter_rewards(deps.as_mut(), voter1, None, None);
+        assert_eq!(err, Err(ContractError::NoVoterRewards {}));
+    }
 
-        // Check correct update in PROPOSALS
-        let proposal_data = PROPOSALS.load(deps.as_ref().storage, 1).unwrap();
-        match proposal_data.status {
-            Status::Pending => {}
-            e => panic!("{:?}", e),
+    #[test]
+    fn test_partial_slashing() {
+        let balances = [(OWNER, 100), ("voter1", 300), ("voter2", 100)];
+        let mut deps = mock_dependencies_with_locking(1_000, &balances);
+        let settings = AppSettings {
+            slashing: Some(SlashSettings {
+                vetoed: Decimal::percent(50),
+                quorum_missed: Decimal::percent(20),
+                deposit_missed: Decimal::percent(10),
+            }),
+            ..AppSettings::default()
         };
-        assert_eq!(proposal_data.current_deposit, 100u128);
-        assert_eq!(proposal_data.deposit.len(), 1);
-        assert_eq!(proposal_data.deposit[0].amount.u128(), 100u128);
-        assert_eq!(proposal_data.deposit[0].denom, DENOM.to_string());
+        APPSETTINGS
+            .save(deps.as_mut().storage, 1, &settings)
+            .unwrap();
 
-        // Deposit non-gov token
-        let info = mock_info(sender.as_str(), &coins(100, "denom2"));
-        let result = execute_deposit(deps.as_mut(), env.clone(), info, 1).unwrap_err();
+        // 1 gets vetoed, 2 misses the quorum, 3 never reaches the minimum deposit
+        let info = mock_info(OWNER, &coins(100, GOV_DENOM));
+        execute_propose(deps.as_mut(), mock_env(), info.clone(), mock_propose(1)).unwrap();
+        execute_propose(deps.as_mut(), mock_env(), info, mock_propose(1)).unwrap();
+        let info = mock_info(OWNER, &coins(50, GOV_DENOM));
+        execute_propose(deps.as_mut(), mock_env(), info, mock_propose(1)).unwrap();
+        let depositor = mock_info("depositor", &coins(50, GOV_DENOM));
+        execute_deposit(deps.as_mut(), mock_env(), depositor, 1).unwrap();
+        for voter in &["voter1", "voter2"] {
+            execute_vote(
+                deps.as_mut(),
+                mock_env(),
+                mock_info(voter, &[]),
+                1,
+                Vote::Veto,
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        }
 
-        // Check no update in PROPOSALS
-        let proposal_data = PROPOSALS.load(deps.as_ref().storage, 1).unwrap();
-        assert_eq!(proposal_data.current_deposit, 100);
-        assert_eq!(proposal_data.deposit.len(), 1);
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(1000);
+        let burn = |amount: u128| {
+            SubMsg::new(FuryMessages::MsgBurnGovTokensForApp {
+                app_id: 1,
+                amount: Coin::new(amount, GOV_DENOM),
+                from: mock_env().contract.address.to_string(),
+            })
+        };
+        let refund = |to: &str, amount: u128| {
+            SubMsg::new(BankMsg::Send {
+                to_address: to.to_string(),
+                amount: coins(amount, GOV_DENOM),
+            })
+        };
+        let settled = |res: &Response<FuryMessages>| {
+            res.attributes
+                .iter()
+                .filter(|attr| attr.key == "burned" || attr.key == "returned")
+                .map(|attr| attr.value.clone())
+                .collect::<Vec<_>>()
+        };
+
+        // the first refund slashes half of every deposit of the vetoed proposal
+        let depositor = mock_info("depositor", &[]);
+        let res = execute_refund(deps.as_mut(), env.clone(), depositor, 1).unwrap();
+        assert_eq!(res.messages, vec![refund("depositor", 25), burn(75)]);
+        assert_eq!(settled(&res), vec!["75", "25"]);
+        let owner = mock_info(OWNER, &[]);
+        let res = execute_refund(deps.as_mut(), env.clone(), owner.clone(), 1).unwrap();
+        assert_eq!(res.messages, vec![refund(OWNER, 50)]);
+        assert_eq!(settled(&res), vec!["0", "50"]);
+
+        // every slash starts the proposer's cooldown, not only a veto
+        let proposer = Addr::unchecked(OWNER);
+        PROPOSERSLASHED.remove(deps.as_mut().storage, (1, &proposer));
+        let res = execute_refund(deps.as_mut(), env.clone(), owner.clone(), 2).unwrap();
+        assert_eq!(res.messages, vec![refund(OWNER, 80), burn(20)]);
+        assert_eq!(
+            PROPOSERSLASHED
+                .load(deps.as_ref().storage, (1, &proposer))
+                .unwrap(),
+            env.block.time
+        );
+        let res = execute_refund(deps.as_mut(), env, owner, 3).unwrap();
+        assert_eq!(res.messages, vec![refund(OWNER, 45), burn(5)]);
+        assert_eq!(settled(&res), vec!["5", "45"]);
     }
 
     #[test]
-    fn test_refund_invalid_requests() {
-        // Mock deps
-        let mut env = mock_env();
-        let mut deps = mock_dependencies1();
-        let sender = Addr::unchecked("sender");
-        let DENOM: &str = "denom1";
+    fn test_submission_fee() {
+        let mut deps = mock_dependencies_with_locking(1_000, &[(OWNER, 100)]);
+        let settings = AppSettings {
+            submission_fee: Some(SubmissionFee {
+                amounts: vec![Coin::new(10, GOV_DENOM), Coin::new(5, "uusd")],
+                collector: Some(Addr::unchecked("collector")),
+            }),
+            ..AppSettings::default()
+        };
+        APPSETTINGS
+            .save(deps.as_mut().storage, 1, &settings)
+            .unwrap();
 
-        env.block.time = Timestamp::from_seconds(1000);
-        env.block.height = 10;
+        // without another accepted denom the fee comes out of the gov tokens
+        let info = mock_info(OWNER, &coins(110, GOV_DENOM));
+        let res = execute_propose(deps.as_mut(), mock_env(), info, mock_propose(1)).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "collector".to_string(),
+                amount: coins(10, GOV_DENOM),
+            })]
+        );
+        let prop = query_proposal_detailed(deps.as_ref(), mock_env(), 1).unwrap();
+        assert_eq!(prop.submission_fee, Some(Coin::new(10, GOV_DENOM)));
+        assert_eq!(prop.current_deposit, 100);
+        assert_eq!(prop.status, Status::Open);
 
-        // Instantiate
-        let imsg = InstantiateMsg {
-            threshold: Threshold::ThresholdQuorum {
-                threshold: Decimal::one(),
-                quorum: Decimal::one(),
-            },
-            locking_contract: Addr::unchecked("locking-contract"),
-        };
-        let sender = Addr::unchecked("sender");
-        let info = mock_info(sender.as_str(), &[]);
-        instantiate(deps.as_mut(), env.clone(), info, imsg.clone()).unwrap();
+        let funds = [Coin::new(100, GOV_DENOM), Coin::new(5, "uusd")];
+        execute_propose(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &funds),
+            mock_propose(1),
+        )
+        .unwrap();
+        let prop = query_proposal_detailed(deps.as_ref(), mock_env(), 2).unwrap();
+        assert_eq!(prop.submission_fee, Some(Coin::new(5, "uusd")));
+        assert_eq!(prop.current_deposit, 100);
+
+        let funds = [Coin::new(100, GOV_DENOM), Coin::new(3, "uusd")];
+        let err = execute_propose(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &funds),
+            mock_propose(1),
+        );
+        assert_eq!(err, Err(ContractError::InsufficientSubmissionFee {}));
+        let funds = [
+            Coin::new(100, GOV_DENOM),
+            Coin::new(5, "uusd"),
+            Coin::new(1, "other"),
+        ];
+        let err = execute_propose(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &funds),
+            mock_propose(1),
+        );
+        assert_eq!(err, Err(ContractError::AdditionalDenomDeposit {}));
+    }
 
-        // Save a proposal
-        let mut proposal = Proposal {
-            title: "title".to_string(),
-            start_time: Timestamp::from_seconds(1200),
-            description: "description".to_string(),
-            start_height: 10,
-            expires: Expiration::AtHeight(20),
-            msgs: vec![],
-            status: Status::Pending,
-            duration: Duration::Height(10),
-            threshold: imsg.threshold,
-            total_weight: 0,
-            votes: Votes {
-                yes: 0,
-                no: 0,
-                abstain: 0,
-                veto: 0,
-            },
-            deposit: vec![],
-            proposer: sender.to_string(),
-            token_denom: DENOM.to_string(),
-            min_deposit: Uint128::from(100_u64),
-            current_deposit: 0,
-            app_mapping_id: 1,
-            is_slashed: false,
+    #[test]
+    fn test_validate_proposal() {
+        let mut deps = mock_dependencies_with_locking(1_000, &[(OWNER, 100)]);
+        let category = Category {
+            name: "whitelist".to_string(),
+            msgs: vec!["msg_whitelist_app_id_vault_interest".to_string()],
+            threshold: Decimal::percent(50),
+            quorum: Decimal::percent(10),
+            deposit_multiplier: 3,
+            timelock: 0,
+        };
+        CATEGORIES
+            .save(deps.as_mut().storage, &vec![category])
+            .unwrap();
+        let settings = AppSettings {
+            expedited: Some(ExpeditedSettings {
+                voting_period: 200,
+                threshold: Decimal::percent(67),
+                deposit_multiplier: 2,
+            }),
+            ..AppSettings::default()
+        };
+        APPSETTINGS
+            .save(deps.as_mut().storage, 1, &settings)
+            .unwrap();
+        let validate = |propose: Propose, proposer: &str| -> ValidateProposalResponse {
+            let msg = QueryMsg::ValidateProposal {
+                propose: Box::new(propose),
+                proposer: proposer.to_string(),
+            };
+            from_json(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
         };
 
-        PROPOSALS.save(deps.as_mut().storage, 1, &proposal).unwrap();
+        let res = validate(mock_propose(1), OWNER);
+        assert_eq!(
+            res,
+            ValidateProposalResponse {
+                valid: true,
+                problems: vec![],
+                min_deposit: Some(Coin::new(300, GOV_DENOM)),
+                submission_fees: vec![],
+            }
+        );
 
-        // Execute refund on a pending proposal
-        let info = mock_info(sender.as_str(), &[]);
+        // the deposit includes both the category and the expedited multiplier
+        let mut propose = mock_propose(1);
+        propose.expedited = true;
+        let res = validate(propose, OWNER);
+        assert!(res.valid);
+        assert_eq!(res.min_deposit, Some(Coin::new(600, GOV_DENOM)));
+
+        // every problem is reported, not only the first one
+        let mut propose = mock_propose(1);
+        propose
+            .msgs
+            .push(FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 1 });
+        propose.latest = Some(Expiration::AtHeight(100));
+        propose.depends_on = vec![7];
+        let res = validate(propose, OWNER);
+        assert!(!res.valid);
+        assert_eq!(
+            res.problems,
+            vec![
+                ProposalProblem {
+                    option: None,
+                    msg_index: Some(1),
+                    error: ContractError::ExtraMessages {}.to_string(),
+                },
+                ProposalProblem {
+                    option: None,
+                    msg_index: None,
+                    error: ContractError::UnknownDependency { proposal_id: 7 }.to_string(),
+                },
+                ProposalProblem {
+                    option: None,
+                    msg_index: None,
+                    error: ContractError::WrongExpiration {}.to_string(),
+                },
+            ]
+        );
 
-        let result = execute_refund(deps.as_mut(), env.clone(), info.clone(), 1).unwrap_err();
-        match result {
-            ContractError::PendingProposal {} => {}
-            e => panic!("{:?}", e),
+        // the options of a multiple choice proposal are checked one by one
+        let mut propose = mock_propose(1);
+        propose.msgs = vec![];
+        propose.options = vec![
+            ProposalOption {
+                title: "a".to_string(),
+                msgs: vec![FuryMessages::MsgWhitelistAppIdVaultInterest { app_id: 1 }],
+            },
+            ProposalOption {
+                title: "b".to_string(),
+                msgs: vec![],
+            },
+        ];
+        let res = validate(propose, OWNER);
+        assert_eq!(
+            res.problems,
+            vec![ProposalProblem {
+                option: Some(1),
+                msg_index: None,
+                error: ContractError::NoMessage {}.to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_migrate_backfills_counters() {
+        let mut deps = mock_dependencies_with_locking(1_000, &[(OWNER, 100), ("voter", 100)]);
+        let info = mock_info(OWNER, &coins(100, GOV_DENOM));
+        execute_propose(deps.as_mut(), mock_env(), info, mock_propose(1)).unwrap();
+        let info = mock_info("voter", &[]);
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            1,
+            Vote::No,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        // an executed proposal of a contract that did not keep the counters yet
+        let stored = APPGOVCONFIG.load(deps.as_ref().storage, 1).unwrap();
+        let legacy = AppGovConfig {
+            proposal_count: stored.proposal_count,
+            current_supply: stored.current_supply,
+            active_participation_supply: stored.active_participation_supply,
+            ..AppGovConfig::default()
         };
+        APPGOVCONFIG
+            .save(deps.as_mut().storage, 1, &legacy)
+            .unwrap();
+        let mut prop = PROPOSALS.load(deps.as_ref().storage, 1).unwrap();
+        prop.status = Status::Executed;
+        PROPOSALS.save(deps.as_mut().storage, 1, &prop).unwrap();
+        // nor the ballots by voter and the voter stats
+        for voter in &[OWNER, "voter"] {
+            let voter = Addr::unchecked(*voter);
+            VOTERBALLOTS.remove(deps.as_mut().storage, (&voter, 1));
+            VOTERAPPBALLOTS.remove(deps.as_mut().storage, (&voter, 1, 1));
+            VOTERSTATS.remove(deps.as_mut().storage, &voter);
+            VOTERAPPSTATS.remove(deps.as_mut().storage, (&voter, 1));
+        }
+        cw2::set_contract_version(deps.as_mut().storage, CONTRACT_NAME, CONTRACT_VERSION).unwrap();
 
-        // Execute refund on an open proposal
-        proposal.status = Status::Open;
-        PROPOSALS.save(deps.as_mut().storage, 1, &proposal).unwrap();
-        let result = execute_refund(deps.as_mut(), env.clone(), info.clone(), 1).unwrap_err();
-        match result {
-            ContractError::OpenProposal {} => {}
-            e => panic!("{:?}", e),
+        let querier = MockQuerier::<Empty>::new(&[]);
+        let deps_mut = DepsMut {
+            storage: &mut deps.storage,
+            api: &deps.api,
+            querier: cosmwasm_std::QuerierWrapper::new(&querier),
         };
+        migrate(deps_mut, mock_env(), MigrateMsg {}).unwrap();
+        assert_eq!(
+            APPGOVCONFIG.load(deps.as_ref().storage, 1).unwrap(),
+            AppGovConfig {
+                proposals_executed: 1,
+                votes_cast: 2,
+                unique_voters: 2,
+                deposits_locked: 100,
+                ..legacy
+            }
+        );
+        let votes = list_votes_by_voter(deps.as_ref(), "voter".to_string(), None, None, None)
+            .unwrap()
+            .votes;
+        assert_eq!(
+            votes
+                .iter()
+                .map(|vote| (vote.proposal_id, vote.vote))
+                .collect::<Vec<_>>(),
+            vec![(1, Vote::No)]
+        );
+        let votes = list_votes_by_voter(deps.as_ref(), "voter".to_string(), Some(1), None, None)
+            .unwrap()
+            .votes;
+        assert_eq!(votes.len(), 1);
+        let stats = query_voter_stats(deps.as_ref(), OWNER.to_string()).unwrap();
+        assert_eq!(stats.proposals_voted, 1);
+        assert_eq!(stats.proposals_created, 1);
+        let stats = query_voter_stats(deps.as_ref(), "voter".to_string()).unwrap();
+        assert_eq!(stats.participation_rate, Decimal::one());
     }
 
     #[test]
-    fn test_slash_invalid_requests() {
+    fn test_process_expired_execution_window_closed() {
+        let balances = [(OWNER, 100), ("voter1", 300)];
+        let mut deps = mock_dependencies_with_locking(1_000, &balances);
+        let settings = AppSettings {
+            execution_window: Some(500),
+            ..AppSettings::default()
+        };
+        APPSETTINGS
+            .save(deps.as_mut().storage, 1, &settings)
+            .unwrap();
+        let info = mock_info(OWNER, &coins(100, GOV_DENOM));
+        execute_propose(deps.as_mut(), mock_env(), info, mock_propose(1)).unwrap();
+        let voter1 = mock_info("voter1", &[]);
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            voter1,
+            1,
+            Vote::Yes,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+
+        // the queue is only processed after the execution window closed
         let mut env = mock_env();
-        let mut deps = mock_dependencies1();
-        env.block.time = Timestamp::from_seconds(100);
-        env.block.height = 10;
-        let DENOM: &str = "denom1";
+        env.block.time = env.block.time.plus_seconds(2000);
+        let res = sudo(
+            deps.as_mut(),
+            env.clone(),
+            SudoMsg::ProcessExpired { limit: 10 },
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: OWNER.to_string(),
+                amount: coins(100, GOV_DENOM),
+            })
+        );
+        assert_eq!(res.messages.len(), 1);
+        let prop = PROPOSALS.load(deps.as_ref().storage, 1).unwrap();
+        assert_eq!(prop.status, Status::ExecutionFailed);
+        assert_eq!(
+            prop.execution_error,
+            Some(ContractError::ExecutionWindowClosed {}.to_string())
+        );
+        assert!(!prop.is_active());
+        assert!(!ACTIVEPROPOSALS.has(deps.as_ref().storage, (1, 1)));
+    }
 
-        // Instantiate
-        let imsg = InstantiateMsg {
-            threshold: Threshold::ThresholdQuorum {
-                threshold: Decimal::one(),
-                quorum: Decimal::one(),
-            },
-            locking_contract: Addr::unchecked("locking-contract"),
+    #[test]
+    fn test_refund_fully_slashed_deposit() {
+        let mut deps = mock_dependencies_with_locking(1_000, &[(OWNER, 100), ("voter", 600)]);
+        let settings = AppSettings {
+            slashing: Some(SlashSettings {
+                vetoed: Decimal::one(),
+                quorum_missed: Decimal::one(),
+                deposit_missed: Decimal::zero(),
+            }),
+            ..AppSettings::default()
         };
-        let sender = Addr::unchecked("sender");
-        let info = mock_info(sender.as_str(), &[]);
-        instantiate(deps.as_mut(), env.clone(), info, imsg.clone()).unwrap();
+        APPSETTINGS
+            .save(deps.as_mut().storage, 1, &settings)
+            .unwrap();
+        let info = mock_info(OWNER, &coins(100, GOV_DENOM));
+        execute_propose(deps.as_mut(), mock_env(), info.clone(), mock_propose(1)).unwrap();
+        execute_propose(deps.as_mut(), mock_env(), info, mock_propose(1)).unwrap();
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("voter", &[]),
+            2,
+            Vote::Veto,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
 
-        // Save a proposal
-        let mut proposal = Proposal {
-            title: "title".to_string(),
-            start_time: Timestamp::from_seconds(1200),
-            description: "description".to_string(),
-            start_height: 10,
-            expires: Expiration::AtHeight(20),
-            msgs: vec![],
-            status: Status::Pending,
-            duration: Duration::Height(10),
-            threshold: imsg.threshold,
-            total_weight: 0,
-            votes: Votes {
-                yes: 0,
-                no: 0,
-                abstain: 0,
-                veto: 0,
-            },
-            deposit: vec![],
-            proposer: sender.to_string(),
-            token_denom: DENOM.to_string(),
-            min_deposit: Uint128::from(100_u64),
-            current_deposit: 0,
-            app_mapping_id: 1,
-            is_slashed: false,
+        // the proposal misses the quorum and the refund burns the whole deposit
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(1000);
+        let owner = mock_info(OWNER, &[]);
+        let res = execute_refund(deps.as_mut(), env.clone(), owner.clone(), 1).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(FuryMessages::MsgBurnGovTokensForApp {
+                app_id: 1,
+                amount: Coin::new(100, GOV_DENOM),
+                from: env.contract.address.to_string(),
+            })]
+        );
+        let returned = res
+            .attributes
+            .iter()
+            .find(|attr| attr.key == "returned")
+            .unwrap();
+        assert_eq!(returned.value, "0");
+        let prop = PROPOSALS.load(deps.as_ref().storage, 1).unwrap();
+        assert!(prop.is_slashed);
+
+        let err = execute_refund(deps.as_mut(), env.clone(), owner.clone(), 1);
+        assert_eq!(err, Err(ContractError::NoDeposit {}));
+
+        // a later change of the slash settings does not make a slashed deposit refundable
+        execute_slash(deps.as_mut(), env.clone(), owner.clone(), 2).unwrap();
+        let settings = AppSettings {
+            slashing: Some(SlashSettings {
+                vetoed: Decimal::percent(50),
+                quorum_missed: Decimal::zero(),
+                deposit_missed: Decimal::zero(),
+            }),
+            ..AppSettings::default()
         };
+        APPSETTINGS
+            .save(deps.as_mut().storage, 1, &settings)
+            .unwrap();
+        let err = execute_refund(deps.as_mut(), env, owner, 2);
+        assert_eq!(err, Err(ContractError::SlashedProposal {}));
+    }
 
-        PROPOSALS.save(deps.as_mut().storage, 1, &proposal).unwrap();
+    #[test]
+    fn test_slash_topped_up_deposit() {
+        let mut deps = mock_dependencies_with_locking(1_000, &[(OWNER, 100)]);
+        let settings = AppSettings {
+            slashing: Some(SlashSettings {
+                vetoed: Decimal::one(),
+                quorum_missed: Decimal::percent(20),
+                deposit_missed: Decimal::zero(),
+            }),
+            ..AppSettings::default()
+        };
+        APPSETTINGS
+            .save(deps.as_mut().storage, 1, &settings)
+            .unwrap();
+        let info = mock_info(OWNER, &coins(101, GOV_DENOM));
+        execute_propose(deps.as_mut(), mock_env(), info, mock_propose(1)).unwrap();
 
-        // Slash for pending proposal
-        let info = mock_info(sender.as_str(), &[]);
+        // a second deposit adds to the stored deposit of the depositor
+        for amount in &[33, 71] {
+            let depositor = mock_info("depositor", &coins(*amount, GOV_DENOM));
+            execute_deposit(deps.as_mut(), mock_env(), depositor, 1).unwrap();
+        }
+        let depositor = Addr::unchecked("depositor");
+        assert_eq!(
+            VOTERDEPOSIT
+                .load(deps.as_ref().storage, (1, &depositor))
+                .unwrap(),
+            coins(104, GOV_DENOM)
+        );
 
-        let result = execute_slash(deps.as_mut(), env.clone(), info.clone(), 1).unwrap_err();
-        match result {
-            ContractError::NotRejected {} => {}
-            e => panic!("{:?}", e),
-        };
+        // the proposal misses the quorum, both depositors lose 20% of everything they deposited
+        // and the rounding remainders are burned as well
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(1000);
+        let res =
+            execute_refund(deps.as_mut(), env.clone(), mock_info("depositor", &[]), 1).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![
+                SubMsg::new(BankMsg::Send {
+                    to_address: "depositor".to_string(),
+                    amount: coins(83, GOV_DENOM),
+                }),
+                SubMsg::new(FuryMessages::MsgBurnGovTokensForApp {
+                    app_id: 1,
+                    amount: Coin::new(42, GOV_DENOM),
+                    from: env.contract.address.to_string(),
+                }),
+            ]
+        );
+        let res = execute_refund(deps.as_mut(), env, mock_info(OWNER, &[]), 1).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: OWNER.to_string(),
+                amount: coins(80, GOV_DENOM),
+            })]
+        );
+    }
 
-        // Slash for rejected proposal but not vetoed
-        env.block.time = Timestamp::from_seconds(5000);
-        env.block.height = 30;
-        let info = mock_info(sender.as_str(), &[]);
+    #[test]
+    fn test_process_expired_waiting_for_execution() {
+        let balances = [(OWNER, 100), ("voter1", 300)];
+        let mut deps = mock_dependencies_with_locking(1_000, &balances);
+        let info = mock_info(OWNER, &coins(100, GOV_DENOM));
+        let mut propose = mock_propose(1);
+        propose.execute_at = Some(Scheduled::AtTime(mock_env().block.time.plus_seconds(1500)));
+        execute_propose(deps.as_mut(), mock_env(), info, propose).unwrap();
+        execute_vote(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("voter1", &[]),
+            1,
+            Vote::Yes,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
 
-        let result = execute_slash(deps.as_mut(), env.clone(), info.clone(), 1).unwrap_err();
-        match result {
-            ContractError::ProposalNotVetoed {} => {}
-            e => panic!("{:?}", e),
+        // the passed proposal waits for its schedule without counting as active
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(1000);
+        let res = sudo(
+            deps.as_mut(),
+            env.clone(),
+            SudoMsg::ProcessExpired { limit: 10 },
+        )
+        .unwrap();
+        assert!(res.messages.is_empty());
+        let prop = PROPOSALS.load(deps.as_ref().storage, 1).unwrap();
+        assert_eq!(prop.status, Status::Passed);
+        assert_eq!(prop.execution_window, Some(DEFAULT_EXECUTION_WINDOW));
+        assert!(!ACTIVEPROPOSALS.has(deps.as_ref().storage, (1, 1)));
+
+        // the messages fail their validation once the proposal can be executed
+        let querier = std::mem::replace(&mut deps.querier, MockQuerier::new(&[]));
+        deps.querier = querier.with_custom_handler(|_| {
+            SystemResult::Ok(ContractResult::Ok(
+                to_json_binary(&MessageValidateResponse {
+                    found: false,
+                    err: "app already whitelisted".to_string(),
+                })
+                .unwrap(),
+            ))
+        });
+        env.block.time = env.block.time.plus_seconds(500);
+        let res = sudo(deps.as_mut(), env, SudoMsg::ProcessExpired { limit: 10 }).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        let error = ContractError::ProposalError {
+            err: "app already whitelisted".to_string(),
         };
+        assert!(res
+            .attributes
+            .contains(&Attribute::new("error", error.to_string())));
+        assert!(res
+            .attributes
+            .contains(&Attribute::new("execute_error", format!("1: {}", error))));
+        assert!(!res.attributes.iter().any(|attr| attr.key == "executed"));
     }
-    
-
 }
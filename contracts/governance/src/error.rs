@@ -1,4 +1,4 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Timestamp};
 use cw_utils::ThresholdError;
 
 use thiserror::Error;
@@ -129,4 +129,160 @@ pub enum ContractError {
 
     #[error("Proposal is already slashed")]
     AlreadySlashed {},
+
+    #[error("Quadratic total weight is not configured for app")]
+    NoQuadraticSupply {},
+
+    #[error("Conviction voting is not enabled for this proposal")]
+    ConvictionNotEnabled {},
+
+    #[error("Conviction tier must be between 1 and {max_tier}")]
+    InvalidConvictionTier { max_tier: u8 },
+
+    #[error("Vtokens are not locked long enough for the requested conviction tier")]
+    InsufficientLockDuration {},
+
+    #[error("Multiple choice proposals need at least two options")]
+    NotEnoughOptions {},
+
+    #[error("Invalid option for this proposal")]
+    InvalidOption {},
+
+    #[error("Proposal can only be amended while pending")]
+    NotPending {},
+
+    #[error("Proposal has not been amended since your deposit")]
+    NotAmended {},
+
+    #[error("App already has the maximum of {max} active proposals")]
+    TooManyActiveProposals { max: u32 },
+
+    #[error("Proposer already has the maximum of {max} active proposals")]
+    TooManyProposerProposals { max: u32 },
+
+    #[error("Proposer was slashed and cannot propose before {until}")]
+    ProposerCooldown { until: Timestamp },
+
+    #[error("Proposer needs at least {min} vtokens to propose")]
+    InsufficientProposerBalance { min: u128 },
+
+    #[error("Invalid public key, expected a compressed secp256k1 key")]
+    InvalidPubkey {},
+
+    #[error("Invalid ballot signature")]
+    InvalidSignature {},
+
+    #[error("Invalid nonce, expected {expected}")]
+    InvalidNonce { expected: u64 },
+
+    #[error("No ballots provided")]
+    NoBallots {},
+
+    #[error("Proposal uses secret ballots, commit the vote instead")]
+    SecretBallot {},
+
+    #[error("Commit-reveal voting is not enabled for this proposal")]
+    CommitRevealNotEnabled {},
+
+    #[error("Proposal is not in its reveal phase")]
+    NotRevealing {},
+
+    #[error("No commitment found for this voter")]
+    NoCommitment {},
+
+    #[error("Revealed vote does not match the commitment")]
+    InvalidReveal {},
+
+    #[error("Optimistic proposals are not enabled for this app")]
+    OptimisticNotEnabled {},
+
+    #[error("Message {kind} is not allowed in optimistic proposals")]
+    MsgNotOptimistic { kind: String },
+
+    #[error("Guardian role must have an expiration")]
+    GuardianNeverExpires {},
+
+    #[error("Proposal is not passed")]
+    NotPassed {},
+
+    #[error("Review window of the proposal is over")]
+    ReviewWindowOver {},
+
+    #[error("Proposal is still in its review window")]
+    InReviewWindow {},
+
+    #[error("Guardian cannot cancel a proposal replacing the guardian")]
+    CannotCancelGuardianUpdate {},
+
+    #[error("Expedited proposals are not enabled for this app")]
+    ExpeditedNotEnabled {},
+
+    #[error("Optimistic and guardian proposals cannot be expedited")]
+    ExpeditedNotAllowed {},
+
+    #[error("Message {kind} is mapped to more than one category")]
+    DuplicateCategoryMsg { kind: String },
+
+    #[error("Deposit multiplier must be at least 1")]
+    InvalidDepositMultiplier {},
+
+    #[error("Amended messages belong to a different category")]
+    CategoryChanged {},
+
+    #[error("Proposal is still timelocked")]
+    Timelocked {},
+
+    #[error("Execution window of the proposal is closed")]
+    ExecutionWindowClosed {},
+
+    #[error("Memo must not exceed {max} characters")]
+    MemoTooLong { max: usize },
+
+    #[error("Proposal {proposal_id} does not exist")]
+    UnknownDependency { proposal_id: u64 },
+
+    #[error("Proposal {proposal_id} has to be executed first")]
+    DependencyNotExecuted { proposal_id: u64 },
+
+    #[error("Proposal is scheduled for a later execution")]
+    NotScheduledYet {},
+
+    #[error("App {app_id} is listed more than once")]
+    DuplicateApp { app_id: u64 },
+
+    #[error("Required apps must be at least 1")]
+    InvalidRequiredApps {},
+
+    #[error("App {app_id} does not participate in the proposal")]
+    AppNotParticipating { app_id: u64 },
+
+    #[error("Cross-app proposals must be regular yes/no proposals")]
+    CrossAppNotAllowed {},
+
+    #[error("Proposal does not need sponsors")]
+    SponsorshipNotRequired {},
+
+    #[error("Already sponsoring the proposal")]
+    AlreadySponsored {},
+
+    #[error("Not sponsoring the proposal")]
+    NotSponsor {},
+
+    #[error("Sponsors need a vtoken balance of at least {min}")]
+    InsufficientSponsorBalance { min: u128 },
+
+    #[error("Voter reward shares must not exceed 1")]
+    InvalidRewardShare {},
+
+    #[error("No voter rewards to claim")]
+    NoVoterRewards {},
+
+    #[error("Slash fractions must not exceed 1")]
+    InvalidSlashFraction {},
+
+    #[error("Submission fee not paid")]
+    InsufficientSubmissionFee {},
+
+    #[error("Submission fee needs at least one accepted amount")]
+    InvalidSubmissionFee {},
 }
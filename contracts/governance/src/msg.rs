@@ -1,10 +1,14 @@
-use crate::state::Votes;
-use fury_bindings::FuryMessages;
-use cosmwasm_std::{Addr, Decimal, Timestamp, Uint128};
+use crate::state::{
+    AppSettings, AppTally, Category, ConvictionSettings, ExpeditedPhase, Guardian, ProposalOption,
+    TallyMode, Votes,
+};
+use cosmwasm_std::{Addr, Binary, Coin, Decimal, Timestamp, Uint128};
 use cw3::{Status, Vote};
-use cw_utils::{Duration, Expiration, Threshold};
+use cw_utils::{Duration, Expiration, Scheduled, Threshold};
+use fury_bindings::FuryMessages;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct InstantiateMsg {
@@ -39,6 +43,22 @@ pub struct ProposalResponseTotal {
     pub proposer: String,
     pub token_denom: String,
     pub current_deposit: u128,
+    pub options: Vec<ProposalOption>,
+    pub option_votes: Vec<u128>,
+    pub revision: u32,
+    pub optimistic: Option<Decimal>,
+    pub escalated: bool,
+    pub expedited: Option<ExpeditedPhase>,
+    pub category: Option<String>,
+    pub timelock: u64,
+    pub execution_error: Option<String>,
+    pub depends_on: Vec<u64>,
+    pub execute_at: Option<Scheduled>,
+    pub participants: Vec<AppTally>,
+    pub required_apps: Option<u32>,
+    pub sponsors: Vec<Addr>,
+    pub sponsors_required: u32,
+    pub submission_fee: Option<Coin>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -49,6 +69,27 @@ pub struct Propose {
     // note: we ignore API-spec'd earliest if passed, always opens immediately
     pub latest: Option<Expiration>,
     pub app_id_param: u64,
+    // turns the proposal into a multiple choice proposal, `msgs` must then be empty
+    #[serde(default)]
+    pub options: Vec<ProposalOption>,
+    // passes at expiry unless objected, only for messages allowlisted by the app
+    #[serde(default)]
+    pub optimistic: bool,
+    // installs a new guardian for the app when executed, `msgs` must then be empty
+    #[serde(default)]
+    pub set_guardian: Option<Guardian>,
+    // shorter voting period and higher threshold, needs a higher deposit
+    #[serde(default)]
+    pub expedited: bool,
+    // proposals that have to be executed before this one
+    #[serde(default)]
+    pub depends_on: Vec<u64>,
+    // earliest execution of the proposal
+    #[serde(default)]
+    pub execute_at: Option<Scheduled>,
+    // other apps voting on the proposal, each with its own gov token and tally
+    #[serde(default)]
+    pub participating_apps: Vec<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -57,6 +98,121 @@ pub struct AppProposalResponse {
     pub proposal_count: u64,
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TallyResponse {
+    pub proposal_id: u64,
+    pub status: Status,
+    pub tally_mode: TallyMode,
+    pub total_weight: u128,
+    // tally using the effective ballot weights
+    pub votes: Votes,
+    // tally using the raw vtoken balances
+    pub raw_votes: Votes,
+    pub conviction: Option<ConvictionSettings>,
+    // yes votes per option of a multiple choice proposal
+    pub option_votes: Vec<u128>,
+    pub winning_option: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct EffectiveWeightResponse {
+    // vtoken balance at the proposal `start_height`
+    pub raw_weight: u128,
+    // conviction multiplier applied on top of the tally mode
+    pub multiplier: u64,
+    // weight the ballot would be counted with
+    pub effective_weight: u128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct GovStatsResponse {
+    pub app_id: u64,
+    pub proposals_created: u64,
+    pub proposals_decided: u64,
+    pub pass_rate: Decimal,
+    pub reject_rate: Decimal,
+    pub veto_rate: Decimal,
+    // average turnout of the decided proposals as a fraction of `total_weight`
+    pub average_turnout: Decimal,
+    // median seconds between creation and reaching the minimum deposit, estimated from a
+    // bucketed histogram
+    pub median_deposit_time: Option<u64>,
+    pub msg_counts: BTreeMap<String, u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct VoterStatsResponse {
+    pub voter: String,
+    pub proposals_voted: u64,
+    pub proposals_created: u64,
+    pub deposits_slashed: u128,
+    // share of the proposals of the apps the voter votes or proposes in that they voted on, their
+    // own proposals are left out
+    pub participation_rate: Decimal,
+}
+
+/// Problem found by `QueryMsg::ValidateProposal`
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ProposalProblem {
+    // option of a multiple choice proposal the offending message belongs to
+    pub option: Option<u32>,
+    // index of the offending message, None if the problem is not about a single message
+    pub msg_index: Option<u32>,
+    pub error: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct VoterRewardsResponse {
+    pub rewards: Vec<Coin>,
+    // last proposal looked at once the limit is reached, the `start_after` of the next page
+    pub last_proposal_id: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ValidateProposalResponse {
+    pub valid: bool,
+    pub problems: Vec<ProposalProblem>,
+    // deposit the proposal needs to open, category and expedited multipliers included,
+    // None if the app or its gov token is unknown
+    pub min_deposit: Option<Coin>,
+    // accepted submission fees, one of them is paid on top of the deposit
+    pub submission_fees: Vec<Coin>,
+}
+
+/// Lock information for an address as reported by the locking contract
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct LockEndResponse {
+    // latest unlock time of the vtokens held at the queried height, None if nothing is locked
+    pub end_time: Option<Timestamp>,
+}
+
+/// Ballot signed off-chain by the voter and submitted by a relayer
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SignedBallot {
+    pub proposal_id: u64,
+    pub vote: Vote,
+    pub conviction: Option<u8>,
+    pub option: Option<u32>,
+    // must match the next nonce of the voter, see `QueryMsg::VoteNonce`
+    pub nonce: u64,
+    // compressed secp256k1 public key of the voter
+    pub pubkey: Binary,
+    // 64 byte signature over the sha256 hash of the JSON encoded `SignedVotePayload`
+    pub signature: Binary,
+}
+
+/// Canonical payload signed by the voter of a `SignedBallot`
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SignedVotePayload {
+    pub chain_id: String,
+    pub contract: String,
+    pub proposal_id: u64,
+    pub vote: Vote,
+    pub conviction: Option<u8>,
+    pub option: Option<u32>,
+    pub nonce: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct ExtendedPair {
     pub app_mapping_id_param: u64,
@@ -72,13 +228,75 @@ pub struct ExtendedPair {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
-    Propose { propose: Propose },
-    Vote { proposal_id: u64, vote: Vote },
-    Execute { proposal_id: u64 },
-    Refund { proposal_id: u64 },
+    Propose {
+        propose: Propose,
+    },
+    Vote {
+        proposal_id: u64,
+        vote: Vote,
+        conviction: Option<u8>,
+        option: Option<u32>,
+        memo: Option<String>,
+    },
+    Execute {
+        proposal_id: u64,
+    },
+    Refund {
+        proposal_id: u64,
+    },
 
-    Deposit { proposal_id: u64 },
-    Slash { proposal_id: u64 },
+    Deposit {
+        proposal_id: u64,
+    },
+    Slash {
+        proposal_id: u64,
+    },
+    /// Proposer only, while the proposal is still pending
+    AmendProposal {
+        proposal_id: u64,
+        title: Option<String>,
+        description: Option<String>,
+        msgs: Option<Vec<FuryMessages>>,
+    },
+    /// Returns the deposit of a depositor who objects to an amendment made after their deposit
+    WithdrawDeposit {
+        proposal_id: u64,
+    },
+    /// Guardian only, cancels a passed proposal during its review window and returns deposits
+    CancelProposal {
+        proposal_id: u64,
+        reason: String,
+    },
+    /// Applies ballots signed off-chain, anyone can relay them
+    SubmitSignedVotes {
+        ballots: Vec<SignedBallot>,
+    },
+    /// Secret ballot on a commit-reveal proposal, `commitment` is the hex encoded
+    /// sha256 of the lowercase vote name, the salt and the voter address
+    CommitVote {
+        proposal_id: u64,
+        commitment: String,
+    },
+    /// Reveals a committed vote once the proposal has expired
+    RevealVote {
+        proposal_id: u64,
+        vote: Vote,
+        salt: String,
+    },
+    /// Backs a pending proposal of an app that requires sponsors
+    Sponsor {
+        proposal_id: u64,
+    },
+    /// Withdraws the sponsorship while the proposal is still pending
+    Unsponsor {
+        proposal_id: u64,
+    },
+    /// Pays out the voter rewards of the rewarded proposals the sender voted on, looking at
+    /// no more than `limit` ballots after `start_after`
+    ClaimVoterRewards {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 }
 
 // We can also add this as a cw3 extension
@@ -132,13 +350,105 @@ pub enum QueryMsg {
         denom: String,
         height: Option<u64>,
     },
+    LockEnd {
+        address: Addr,
+        denom: String,
+        height: Option<u64>,
+    },
+    /// Returns TallyResponse
+    Tally {
+        proposal_id: u64,
+    },
+    /// Returns AppSettings
+    AppSettings {
+        app_id: u64,
+    },
+    /// Returns Vec<ProposalRevision>
+    ProposalRevisions {
+        proposal_id: u64,
+    },
+    /// Returns EffectiveWeightResponse
+    EffectiveWeight {
+        proposal_id: u64,
+        voter: String,
+        conviction: Option<u8>,
+    },
+    /// Returns Vec<Category>
+    Categories {},
+    /// Returns Option<Cancellation>
+    Cancellation {
+        proposal_id: u64,
+    },
+    /// Returns u64, the nonce expected in the next signed ballot of `voter`
+    VoteNonce {
+        voter: String,
+    },
+    /// Returns VoteListResponse with the ballots of `voter`, optionally for a single app
+    ListVotesByVoter {
+        voter: String,
+        app_id: Option<u64>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns VoterStatsResponse
+    VoterStats {
+        voter: String,
+    },
+    /// Returns GovStatsResponse aggregated over the stats periods between `from` and `to`
+    GovStats {
+        app_id: u64,
+        from: Timestamp,
+        to: Timestamp,
+    },
+    /// Returns Vec<VoteRecord>, every ballot cast by `voter` on the proposal
+    VoteHistory {
+        proposal_id: u64,
+        voter: String,
+    },
+    /// Returns Vec<MessageResult> of the last execution attempt
+    ExecutionResults {
+        proposal_id: u64,
+    },
+    /// Returns VoterRewardsResponse, the voter rewards `voter` can claim from no more than
+    /// `limit` ballots after `start_after`
+    PendingVoterRewards {
+        voter: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns ValidateProposalResponse, runs the checks of `ExecuteMsg::Propose` that do not
+    /// need funds
+    ValidateProposal {
+        propose: Box<Propose>,
+        proposer: String,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum SudoMsg {
-    UpdateLockingContract { address: Addr },
-    UpdateThreshold { threshold: Threshold },
+    UpdateLockingContract {
+        address: Addr,
+    },
+    UpdateThreshold {
+        threshold: Threshold,
+    },
+    UpdateAppSettings {
+        app_id: u64,
+        settings: Box<AppSettings>,
+    },
+    UpdateGuardian {
+        app_id: u64,
+        guardian: Option<Guardian>,
+    },
+    /// Replaces the category table
+    UpdateCategories {
+        categories: Vec<Category>,
+    },
+    /// Settles up to `limit` proposals from the expiry queue, called by the end blocker
+    ProcessExpired {
+        limit: u32,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -1,12 +1,20 @@
+use std::collections::BTreeMap;
 use std::ops::Mul;
 
-use fury_bindings::FuryMessages;
-use cosmwasm_std::{Addr, BlockInfo, Coin, Decimal, StdResult, Storage, Timestamp, Uint128};
+use cosmwasm_std::{
+    to_json_binary, Addr, Binary, BlockInfo, Coin, Decimal, Empty, HexBinary, Isqrt, StdResult,
+    Storage, Timestamp, Uint128,
+};
 use cw3::{Status, Vote};
 use cw_storage_plus::{Item, Map};
-use cw_utils::{Duration, Expiration, Threshold};
+use cw_utils::{Duration, Expiration, Scheduled, Threshold};
+use fury_bindings::FuryMessages;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::coin_helpers::assert_sent_sufficient_coin_deposit;
+use crate::validation::msg_app_id;
 
 // we multiply by this when calculating needed_votes in order to round up properly
 // Note: `10u128.pow(9)` fails as "u128::pow` is not yet stable as a const fn"
@@ -18,13 +26,175 @@ pub struct Config {
     pub locking_contract: Addr,
 }
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Eq)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Eq, Default)]
 pub struct AppGovConfig {
     pub proposal_count: u64,
 
     pub current_supply: u128,
 
     pub active_participation_supply: u128,
+
+    // proposals by outcome, counted once the outcome is acted upon
+    #[serde(default)]
+    pub proposals_executed: u64,
+    #[serde(default)]
+    pub proposals_failed: u64,
+    #[serde(default)]
+    pub proposals_rejected: u64,
+    #[serde(default)]
+    pub proposals_vetoed: u64,
+    #[serde(default)]
+    pub proposals_cancelled: u64,
+
+    // ballots cast, a changed vote is not counted again
+    #[serde(default)]
+    pub votes_cast: u64,
+    #[serde(default)]
+    pub unique_voters: u64,
+
+    // gov token deposits
+    #[serde(default)]
+    pub deposits_locked: u128,
+    #[serde(default)]
+    pub deposits_refunded: u128,
+    #[serde(default)]
+    pub deposits_slashed: u128,
+}
+
+impl AppGovConfig {
+    /// moves a proposal from its previous outcome, if any, to `outcome`
+    pub fn record_outcome(&mut self, previous: Option<ProposalOutcome>, outcome: ProposalOutcome) {
+        if let Some(previous) = previous {
+            *self.outcome_count(previous) -= 1;
+        }
+        *self.outcome_count(outcome) += 1;
+    }
+
+    fn outcome_count(&mut self, outcome: ProposalOutcome) -> &mut u64 {
+        match outcome {
+            ProposalOutcome::Executed => &mut self.proposals_executed,
+            ProposalOutcome::ExecutionFailed => &mut self.proposals_failed,
+            ProposalOutcome::Rejected => &mut self.proposals_rejected,
+            ProposalOutcome::Vetoed => &mut self.proposals_vetoed,
+            ProposalOutcome::Cancelled => &mut self.proposals_cancelled,
+        }
+    }
+
+    pub fn lock_deposit(&mut self, amount: u128) {
+        self.deposits_locked += amount;
+    }
+
+    pub fn refund_deposit(&mut self, amount: u128) {
+        self.deposits_locked = self.deposits_locked.saturating_sub(amount);
+        self.deposits_refunded += amount;
+    }
+
+    pub fn slash_deposit(&mut self, amount: u128) {
+        self.deposits_locked = self.deposits_locked.saturating_sub(amount);
+        self.deposits_slashed += amount;
+    }
+}
+
+// length in seconds of the periods the governance analytics are aggregated over
+pub const STATS_PERIOD: u64 = 86_400;
+
+// upper bounds in seconds of the deposit time histogram buckets, longer times go to a last bucket
+pub const DEPOSIT_TIME_BUCKETS: [u64; 12] = [
+    0, 60, 600, 3_600, 21_600, 43_200, 86_400, 172_800, 345_600, 604_800, 1_209_600, 2_592_000,
+];
+
+/// Governance activity of an app within one stats period
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct PeriodStats {
+    pub proposals_created: u64,
+    // proposals whose outcome was decided in the period, vetoed ones are also rejected
+    pub proposals_passed: u64,
+    pub proposals_rejected: u64,
+    pub proposals_vetoed: u64,
+    // sum of the turnout of the decided proposals as a fraction of `total_weight`
+    pub turnout_sum: Decimal,
+    // number of proposals opened in the period per `DEPOSIT_TIME_BUCKETS` bucket of the seconds
+    // it took them to reach their minimum deposit
+    pub deposit_time_buckets: Vec<u64>,
+    // messages of the proposals created in the period by FuryMessages variant
+    pub msg_counts: BTreeMap<String, u64>,
+}
+
+impl PeriodStats {
+    pub fn record_deposit_time(&mut self, seconds: u64) {
+        let index = DEPOSIT_TIME_BUCKETS
+            .iter()
+            .position(|bound| seconds <= *bound)
+            .unwrap_or(DEPOSIT_TIME_BUCKETS.len());
+        self.deposit_time_buckets
+            .resize(DEPOSIT_TIME_BUCKETS.len() + 1, 0);
+        self.deposit_time_buckets[index] += 1;
+    }
+
+    /// adds the deposit time histogram of `other` to this one
+    pub fn merge_deposit_times(&mut self, other: &PeriodStats) {
+        self.deposit_time_buckets
+            .resize(DEPOSIT_TIME_BUCKETS.len() + 1, 0);
+        for (count, other) in self
+            .deposit_time_buckets
+            .iter_mut()
+            .zip(&other.deposit_time_buckets)
+        {
+            *count += other;
+        }
+    }
+
+    /// median deposit time, interpolated within the histogram bucket holding it. Times in the
+    /// last, unbounded bucket are reported as its lower bound.
+    pub fn median_deposit_time(&self) -> Option<u64> {
+        let count: u64 = self.deposit_time_buckets.iter().sum();
+        let mut below = 0;
+        for (index, bucket_count) in self.deposit_time_buckets.iter().enumerate() {
+            if *bucket_count > 0 && 2 * (below + bucket_count) >= count {
+                let lower = match index {
+                    0 => 0,
+                    index => DEPOSIT_TIME_BUCKETS[index - 1],
+                };
+                let upper = match DEPOSIT_TIME_BUCKETS.get(index) {
+                    Some(upper) => *upper,
+                    None => return Some(lower),
+                };
+                return Some(lower + (upper - lower) * (count - 2 * below) / (2 * bucket_count));
+            }
+            below += bucket_count;
+        }
+        None
+    }
+
+    pub fn record_decision(&mut self, outcome: ProposalOutcome, turnout: Decimal) {
+        match outcome {
+            ProposalOutcome::Executed
+            | ProposalOutcome::ExecutionFailed
+            | ProposalOutcome::Cancelled => self.proposals_passed += 1,
+            ProposalOutcome::Rejected => self.proposals_rejected += 1,
+            ProposalOutcome::Vetoed => {
+                self.proposals_rejected += 1;
+                self.proposals_vetoed += 1;
+            }
+        }
+        self.turnout_sum += turnout;
+    }
+}
+
+/// stats period containing `time`
+pub fn stats_period(time: Timestamp) -> u64 {
+    time.seconds() / STATS_PERIOD
+}
+
+/// Final outcome of a proposal as counted in the app stats
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalOutcome {
+    Executed,
+    ExecutionFailed,
+    Rejected,
+    Vetoed,
+    Cancelled,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -34,6 +204,270 @@ pub struct AppProposalConfig {
     pub proposal: Proposal,
 }
 
+/// How a voter's vtoken balance at `start_height` is turned into ballot weight
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TallyMode {
+    /// one vtoken, one vote
+    #[default]
+    Linear,
+    /// ballot weight is the integer square root of the vtoken balance
+    Quadratic,
+}
+
+impl TallyMode {
+    /// weight counted in the tally for a raw vtoken balance
+    pub fn effective_weight(&self, raw_weight: u128) -> u128 {
+        match self {
+            TallyMode::Linear => raw_weight,
+            TallyMode::Quadratic => Uint128::new(raw_weight).isqrt().u128(),
+        }
+    }
+}
+
+/// Conviction voting: a ballot committed with tier `n` counts `n` times its weight, provided the
+/// voter's vtokens stay locked for at least `(n - 1) * lock_period_per_tier` seconds after the
+/// proposal started
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug, Eq)]
+pub struct ConvictionSettings {
+    pub lock_period_per_tier: u64,
+    pub max_tier: u8,
+}
+
+impl ConvictionSettings {
+    /// minimum lock end time for a ballot of the given tier
+    pub fn required_lock_end(&self, start_time: Timestamp, tier: u8) -> Timestamp {
+        start_time.plus_seconds(self.lock_period_per_tier * (tier.saturating_sub(1) as u64))
+    }
+}
+
+/// Secret ballot mode, voters commit `sha256(vote || salt || voter)` while the proposal is open
+/// and reveal their vote during `reveal_period` after it expires
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug)]
+pub struct CommitRevealSettings {
+    pub reveal_period: Duration,
+    // whether commitments that are never revealed still count toward the quorum
+    pub count_unrevealed_in_quorum: bool,
+}
+
+/// Optimistic proposals pass at expiry unless the No and Veto votes exceed
+/// `objection_threshold` of the snapshot supply, in which case they escalate into a normal vote
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct OptimisticSettings {
+    // snake_case names of the `FuryMessages` variants allowed in optimistic proposals
+    pub allowed_msgs: Vec<String>,
+    pub objection_threshold: Decimal,
+}
+
+/// Security council of an app, can cancel passed proposals during `review_period` after
+/// voting ends. The role always expires so it cannot become permanent control.
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Guardian {
+    pub address: Addr,
+    pub expires: Expiration,
+    pub review_period: Duration,
+}
+
+/// Expedited proposals need `deposit_multiplier` times the minimum deposit and pass early if
+/// `threshold` is met at the end of `voting_period` seconds, otherwise they continue as regular
+/// proposals
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ExpeditedSettings {
+    pub voting_period: u64,
+    pub threshold: Decimal,
+    pub deposit_multiplier: u64,
+}
+
+/// Sponsors a pending proposal has to collect, besides the minimum deposit, before it opens
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug, Eq)]
+pub struct SponsorSettings {
+    pub sponsors_required: u32,
+    // vtoken balance a sponsor needs in the gov token of the app
+    pub min_balance: u128,
+}
+
+/// Split of slashed deposits between burning and the voter rewards pool of the app
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug, Eq)]
+pub struct VoterRewardSettings {
+    // share of a slashed deposit paid into the pool, the rest is burned
+    pub pool_share: Decimal,
+    // turnout, as a fraction of `total_weight`, from which the voters of a decided proposal
+    // are rewarded, voters of a vetoed proposal are always rewarded
+    pub min_turnout: Decimal,
+    // share of the pool paid out to the voters of a rewarded proposal
+    pub payout_share: Decimal,
+}
+
+/// Rewards assigned to the voters of a proposal, paid pro rata to the ballot weights
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Eq)]
+pub struct ProposalRewards {
+    pub amount: u128,
+    pub denom: String,
+    // weight of all ballots when the rewards were assigned
+    pub total_weight: u128,
+}
+
+/// Fractions of the deposits slashed when a proposal is rejected, every depositor can get the
+/// remainder of their deposit refunded
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, JsonSchema, Debug, Eq)]
+pub struct SlashSettings {
+    pub vetoed: Decimal,
+    // rejected without the ballots reaching the quorum
+    pub quorum_missed: Decimal,
+    // rejected without the deposit ever reaching `min_deposit`
+    pub deposit_missed: Decimal,
+}
+
+impl Default for SlashSettings {
+    fn default() -> Self {
+        SlashSettings {
+            vetoed: Decimal::one(),
+            quorum_missed: Decimal::zero(),
+            deposit_missed: Decimal::zero(),
+        }
+    }
+}
+
+/// Non-refundable fee charged on top of the deposit of a new proposal
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Eq)]
+pub struct SubmissionFee {
+    // accepted fees, the proposer pays one of them
+    pub amounts: Vec<Coin>,
+    // receives the fees, they stay in the contract treasury when None
+    pub collector: Option<Addr>,
+}
+
+/// Short voting phase of an expedited proposal
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ExpeditedPhase {
+    pub threshold: Threshold,
+    pub expires: Expiration,
+}
+
+/// Pass requirements for a group of message kinds, the `msgs` are snake_case `FuryMessages`
+/// variant names or any future generic message type
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Category {
+    pub name: String,
+    pub msgs: Vec<String>,
+    pub threshold: Decimal,
+    pub quorum: Decimal,
+    pub deposit_multiplier: u64,
+    // seconds between the end of voting and execution
+    pub timelock: u64,
+}
+
+impl Category {
+    /// categories are ranked by threshold, then quorum, deposit and timelock
+    pub fn strictness(&self) -> (Decimal, Decimal, u64, u64) {
+        (
+            self.threshold,
+            self.quorum,
+            self.deposit_multiplier,
+            self.timelock,
+        )
+    }
+
+    /// requirements meeting both categories, named after the higher ranked one
+    pub fn strictest_with(&self, other: &Category) -> Category {
+        let mut strictest = if other.strictness() > self.strictness() {
+            other.clone()
+        } else {
+            self.clone()
+        };
+        strictest.threshold = self.threshold.max(other.threshold);
+        strictest.quorum = self.quorum.max(other.quorum);
+        strictest.deposit_multiplier = self.deposit_multiplier.max(other.deposit_multiplier);
+        strictest.timelock = self.timelock.max(other.timelock);
+        strictest
+    }
+}
+
+/// One of the choices of a multiple choice proposal
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ProposalOption {
+    pub title: String,
+    pub msgs: Vec<FuryMessages>,
+}
+
+/// Separate tally of an additional app taking part in a cross-app proposal, counted with the
+/// raw vtoken balances of the app's gov token at the proposal `start_height`
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AppTally {
+    pub app_id: u64,
+    pub token_denom: String,
+    pub total_weight: u128,
+    pub threshold: Threshold,
+    pub votes: Votes,
+}
+
+impl AppTally {
+    pub fn is_passed(&self) -> bool {
+        match self.threshold {
+            Threshold::AbsoluteCount { weight } => self.votes.yes >= weight,
+            Threshold::AbsolutePercentage { percentage } => {
+                self.votes.yes >= votes_needed(self.total_weight - self.votes.abstain, percentage)
+            }
+            Threshold::ThresholdQuorum { threshold, quorum } => {
+                let opinions = self.votes.total() - self.votes.abstain;
+                self.votes.total() >= votes_needed(self.total_weight, quorum)
+                    && opinions > 0
+                    && !self.is_vetoed()
+                    && self.votes.yes >= votes_needed(opinions, threshold)
+            }
+        }
+    }
+
+    pub fn is_vetoed(&self) -> bool {
+        match self.threshold {
+            Threshold::ThresholdQuorum { quorum, .. } => {
+                self.votes.total() > votes_needed(self.total_weight, quorum)
+                    && self.votes.veto
+                        > (Decimal::percent(33) * Uint128::from(self.votes.total())).u128()
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Per app governance settings, updated through sudo
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+#[serde(default)]
+pub struct AppSettings {
+    pub tally_mode: TallyMode,
+    // snapshot total weight for quadratic proposals, i.e. the sum of the square roots of
+    // all vtoken balances as reported by the locking contract or a configured approximation
+    pub quadratic_total_weight: Option<u128>,
+    // lock-duration multiplier on ballots, disabled when None
+    pub conviction: Option<ConvictionSettings>,
+    // maximum number of Pending/Open proposals for the app, unlimited when None
+    pub max_active_proposals: Option<u32>,
+    // maximum number of Pending/Open proposals a single proposer may have in the app
+    pub max_proposals_per_proposer: Option<u32>,
+    // seconds a proposer has to wait before proposing again after being slashed
+    pub slash_cooldown: Option<u64>,
+    // minimum vtoken balance required to create a proposal
+    pub min_propose_balance: Option<u128>,
+    // secret ballots for yes/no proposals, multiple choice proposals always use open ballots
+    pub commit_reveal: Option<CommitRevealSettings>,
+    pub optimistic: Option<OptimisticSettings>,
+    pub guardian: Option<Guardian>,
+    pub expedited: Option<ExpeditedSettings>,
+    // seconds after the end of voting during which a failed execution may be retried
+    pub execution_window: Option<u64>,
+    pub sponsorship: Option<SponsorSettings>,
+    pub voter_rewards: Option<VoterRewardSettings>,
+    // vetoed proposals lose their whole deposit when None
+    pub slashing: Option<SlashSettings>,
+    pub submission_fee: Option<SubmissionFee>,
+    // pass requirements of the app's tally in cross-app proposals of other apps,
+    // the requirements of the proposal apply when None
+    pub threshold: Option<Threshold>,
+    // number of apps, the app included, that have to pass its cross-app proposals, all of
+    // them when None; apps whose state the messages change always have to pass
+    pub required_apps: Option<u32>,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Eq)]
 #[serde(rename_all = "snake_case")]
 pub struct TokenSupply {
@@ -66,6 +500,83 @@ pub struct Proposal {
     pub current_deposit: u128,
     pub app_mapping_id: u64,
     pub is_slashed: bool,
+    #[serde(default)]
+    pub tally_mode: TallyMode,
+    // summary of existing votes using the raw vtoken balances
+    #[serde(default)]
+    pub raw_votes: Votes,
+    #[serde(default)]
+    pub conviction: Option<ConvictionSettings>,
+    // choices of a multiple choice proposal, empty for a yes/no proposal
+    #[serde(default)]
+    pub options: Vec<ProposalOption>,
+    // weight of the yes votes cast for each option, "none of the above" is tallied as no
+    #[serde(default)]
+    pub option_votes: Vec<u128>,
+    // number of amendments made while the proposal was pending
+    #[serde(default)]
+    pub revision: u32,
+    #[serde(default)]
+    pub commit_reveal: Option<CommitRevealSettings>,
+    // end of the reveal phase of a commit-reveal proposal
+    #[serde(default)]
+    pub reveal_expires: Option<Expiration>,
+    // weight of the commitments that have not been revealed yet
+    #[serde(default)]
+    pub unrevealed_weight: u128,
+    // objection threshold of an optimistic proposal
+    #[serde(default)]
+    pub optimistic: Option<Decimal>,
+    // set once the objections on an optimistic proposal crossed its threshold
+    #[serde(default)]
+    pub escalated: bool,
+    // guardian review window between the end of voting and execution
+    #[serde(default)]
+    pub review_period: Option<Duration>,
+    // guardian installed when the proposal is executed, such proposals carry no messages
+    #[serde(default)]
+    pub set_guardian: Option<Guardian>,
+    // short phase of an expedited proposal, cleared when it falls back to a regular proposal
+    #[serde(default)]
+    pub expedited: Option<ExpeditedPhase>,
+    // strictest category among the messages, None if the default requirements apply
+    #[serde(default)]
+    pub category: Option<String>,
+    // seconds between the end of voting and execution
+    #[serde(default)]
+    pub timelock: u64,
+    // error of the last failed execution attempt
+    #[serde(default)]
+    pub execution_error: Option<String>,
+    // seconds after the end of voting during which the proposal may be executed
+    #[serde(default)]
+    pub execution_window: Option<u64>,
+    // outcome counted in the app stats
+    #[serde(default)]
+    pub outcome: Option<ProposalOutcome>,
+    // proposals that have to be executed before this one
+    #[serde(default)]
+    pub depends_on: Vec<u64>,
+    // earliest execution of the proposal
+    #[serde(default)]
+    pub execute_at: Option<Scheduled>,
+    // tallies of the other apps taking part in a cross-app proposal
+    #[serde(default)]
+    pub participants: Vec<AppTally>,
+    // number of apps, this one included, that have to pass, all of them when None
+    #[serde(default)]
+    pub required_apps: Option<u32>,
+    #[serde(default)]
+    pub sponsorship: Option<SponsorSettings>,
+    #[serde(default)]
+    pub sponsors: Vec<Addr>,
+    // fee paid by the proposer, it is not part of the deposit
+    #[serde(default)]
+    pub submission_fee: Option<Coin>,
+    // vtoken supply at `start_height`, the quorum is measured against it. 0 for proposals created
+    // before it was recorded, these use `total_weight`
+    #[serde(default)]
+    pub raw_total_weight: u128,
 }
 
 impl Proposal {
@@ -74,19 +585,166 @@ impl Proposal {
     pub fn current_status(&self, block: &BlockInfo) -> Status {
         let mut status = self.status;
 
-        if status == Status::Executed {
-            status = Status::Executed;
+        if [Status::Executed, Status::Cancelled, Status::ExecutionFailed].contains(&status) {
+            // final states
         } else if status == Status::Pending && self.expires.is_expired(block) {
             status = Status::Rejected;
-        } else if self.expires.is_expired(block) && self.is_passed(block) {
+        } else if self.is_revealing(block) {
+            status = Status::Revealing;
+        } else if self.voting_ends().is_expired(block) && self.is_passed(block) {
             status = Status::Passed;
-        } else if self.expires.is_expired(block) && self.is_rejected(block) {
+        } else if self.voting_ends().is_expired(block) && self.is_rejected(block) {
             status = Status::Rejected;
         }
 
         status
     }
 
+    /// end of the tally, i.e. the end of the reveal phase for a commit-reveal proposal
+    pub fn voting_ends(&self) -> Expiration {
+        // an expedited proposal meeting its stricter threshold is decided at the short expiry
+        if let Some(phase) = &self.expedited {
+            if self.threshold_met(phase.threshold.clone()) {
+                return phase.expires;
+            }
+        }
+        self.reveal_expires.unwrap_or(self.expires)
+    }
+
+    /// drops the expedited phase once it is over, the proposal then continues as a regular one.
+    /// Has to be called before the tally changes after the short expiry.
+    pub fn end_expedited_phase(&mut self, block: &BlockInfo) {
+        if let Some(phase) = &self.expedited {
+            if phase.expires.is_expired(block) {
+                self.expedited = None;
+            }
+        }
+    }
+
+    /// true between the expiration of a commit-reveal proposal and the end of its reveal phase
+    pub fn is_revealing(&self, block: &BlockInfo) -> bool {
+        self.status != Status::Pending
+            && self.expires.is_expired(block)
+            && !self.voting_ends().is_expired(block)
+    }
+
+    /// end of the guardian review window, None if the proposal has none
+    pub fn review_ends(&self) -> StdResult<Option<Expiration>> {
+        self.review_period
+            .map(|period| self.voting_ends() + period)
+            .transpose()
+    }
+
+    /// end of the category timelock, None if the proposal has none
+    pub fn timelock_ends(&self) -> StdResult<Option<Expiration>> {
+        if self.timelock == 0 {
+            return Ok(None);
+        }
+        (self.voting_ends() + Duration::Time(self.timelock)).map(Some)
+    }
+
+    /// end of the execution window, None if the proposal can be executed at any time
+    pub fn execution_ends(&self) -> StdResult<Option<Expiration>> {
+        self.execution_window
+            .map(|window| self.voting_ends() + Duration::Time(window))
+            .transpose()
+    }
+
+    /// true for an optimistic proposal that has not been escalated into a normal vote
+    pub fn is_optimistic(&self) -> bool {
+        self.optimistic.is_some() && !self.escalated
+    }
+
+    /// true if the No and Veto votes exceed the objection threshold of an optimistic proposal
+    pub fn objections_exceeded(&self) -> bool {
+        match self.optimistic {
+            Some(threshold) => {
+                self.votes.no + self.votes.veto
+                    > (threshold * Uint128::from(self.total_weight)).u128()
+            }
+            None => false,
+        }
+    }
+
+    /// raw weight counted toward the quorum, including unrevealed commitments if the app allows it
+    pub fn quorum_weight(&self) -> u128 {
+        match self.commit_reveal {
+            Some(settings) if settings.count_unrevealed_in_quorum => {
+                self.raw_votes.total() + self.unrevealed_weight
+            }
+            _ => self.raw_votes.total(),
+        }
+    }
+
+    /// true while the stored status counts toward the active proposal limits of the app
+    pub fn is_active(&self) -> bool {
+        [Status::Pending, Status::Open, Status::Revealing].contains(&self.status)
+    }
+
+    /// true while the proposal is Pending, Open or Revealing at `block`
+    pub fn is_active_at(&self, block: &BlockInfo) -> bool {
+        [Status::Pending, Status::Open, Status::Revealing].contains(&self.current_status(block))
+    }
+
+    /// raw supply the quorum is measured against
+    pub fn quorum_supply(&self) -> u128 {
+        if self.raw_total_weight == 0 {
+            self.total_weight
+        } else {
+            self.raw_total_weight
+        }
+    }
+
+    /// highest tally weight the proposal can reach, i.e. all of `total_weight` voting with the
+    /// highest conviction tier
+    pub fn max_tally_weight(&self) -> u128 {
+        let max_tier = self
+            .conviction
+            .map(|conviction| conviction.max_tier.max(1))
+            .unwrap_or(1);
+        self.total_weight * max_tier as u128
+    }
+
+    pub fn is_multiple_choice(&self) -> bool {
+        !self.options.is_empty()
+    }
+
+    /// Returns the option with the most support, None if no option leads outright.
+    pub fn winning_option(&self) -> Option<usize> {
+        let max = *self.option_votes.iter().max()?;
+        if max == 0 {
+            return None;
+        }
+        let mut leaders = self
+            .option_votes
+            .iter()
+            .enumerate()
+            .filter(|(_, weight)| **weight == max);
+        let (index, _) = leaders.next()?;
+        match leaders.next() {
+            Some(_) => None,
+            None => Some(index),
+        }
+    }
+
+    /// Messages dispatched when the proposal is executed, for a multiple choice proposal
+    /// these are the messages of the winning option.
+    pub fn passed_msgs(&self) -> Vec<FuryMessages> {
+        if self.is_multiple_choice() {
+            self.winning_option()
+                .map(|index| self.options[index].msgs.clone())
+                .unwrap_or_default()
+        } else {
+            self.msgs.clone()
+        }
+    }
+
+    /// Returns the hex encoded sha256 of the amendable content (title, description, messages).
+    pub fn content_hash(&self) -> StdResult<String> {
+        let content = to_json_binary(&(&self.title, &self.description, &self.msgs, &self.options))?;
+        Ok(HexBinary::from(Sha256::digest(content.as_slice()).to_vec()).to_hex())
+    }
+
     /// update_status sets the status of the proposal to current_status.
     /// (designed for handler logic)
     pub fn update_status(&mut self, block: &BlockInfo) {
@@ -96,7 +754,103 @@ impl Proposal {
     /// Returns true if this proposal is sure to pass (even before expiration, if no future
     /// sequence of possible votes could cause it to fail).
     pub fn is_passed(&self, _block: &BlockInfo) -> bool {
+        if self.is_optimistic() {
+            return !self.objections_exceeded();
+        }
+        let passed = self.threshold_met(self.threshold.clone());
+        if self.participants.is_empty() {
+            return passed;
+        }
+        let passed_apps: Vec<u64> = self
+            .participants
+            .iter()
+            .filter(|tally| tally.is_passed())
+            .map(|tally| tally.app_id)
+            .chain(passed.then_some(self.app_mapping_id))
+            .collect();
+        // every app whose state the messages change has to pass
+        passed_apps.len() >= self.required_apps()
+            && self
+                .target_apps()
+                .iter()
+                .all(|app_id| passed_apps.contains(app_id))
+    }
+
+    /// raw weight of the ballots as a fraction of the snapshot supply
+    pub fn turnout(&self) -> Decimal {
+        if self.quorum_supply() == 0 {
+            Decimal::zero()
+        } else {
+            Decimal::from_ratio(self.raw_votes.total(), self.quorum_supply())
+        }
+    }
+
+    /// true if the ballots of a `ThresholdQuorum` proposal did not reach the quorum
+    pub fn quorum_missed(&self) -> bool {
         match self.threshold {
+            Threshold::ThresholdQuorum { quorum, .. } => {
+                self.quorum_weight() < votes_needed(self.quorum_supply(), quorum)
+            }
+            _ => false,
+        }
+    }
+
+    pub fn min_deposit_reached(&self) -> bool {
+        let deposit = [Coin {
+            amount: Uint128::from(self.current_deposit),
+            denom: self.token_denom.clone(),
+        }];
+        let min_deposit = Coin {
+            amount: self.min_deposit,
+            denom: self.token_denom.clone(),
+        };
+        assert_sent_sufficient_coin_deposit(&deposit, Some(min_deposit)) == Ok(Status::Open)
+    }
+
+    /// true once a pending proposal has both the minimum deposit and the sponsors required by
+    /// its app, the same check applies to proposing, depositing and sponsoring
+    pub fn can_open(&self) -> bool {
+        self.min_deposit_reached() && self.is_sponsored()
+    }
+
+    /// true once the proposal collected the sponsors required by its app
+    pub fn is_sponsored(&self) -> bool {
+        match self.sponsorship {
+            Some(sponsorship) => self.sponsors.len() >= sponsorship.sponsors_required as usize,
+            None => true,
+        }
+    }
+
+    /// apps taking part in the proposal, the app of the proposal first
+    pub fn app_ids(&self) -> Vec<u64> {
+        let mut app_ids = vec![self.app_mapping_id];
+        app_ids.extend(self.participants.iter().map(|tally| tally.app_id));
+        app_ids
+    }
+
+    /// number of apps that have to pass a cross-app proposal
+    pub fn required_apps(&self) -> usize {
+        let apps = self.participants.len() + 1;
+        self.required_apps
+            .map_or(apps, |required| (required as usize).min(apps))
+    }
+
+    /// apps whose state the messages of the proposal change
+    pub fn target_apps(&self) -> Vec<u64> {
+        self.msgs
+            .iter()
+            .map(|msg| {
+                msg_app_id(msg)
+                    .ok()
+                    .flatten()
+                    .unwrap_or(self.app_mapping_id)
+            })
+            .collect()
+    }
+
+    /// Returns true if the current tally meets the given pass requirements.
+    pub fn threshold_met(&self, threshold: Threshold) -> bool {
+        match threshold {
             Threshold::AbsoluteCount {
                 weight: weight_needed,
             } => self.votes.yes >= weight_needed,
@@ -104,16 +858,28 @@ impl Proposal {
                 percentage: percentage_needed,
             } => {
                 self.votes.yes
-                    >= votes_needed(self.total_weight - self.votes.abstain, percentage_needed)
+                    >= votes_needed(
+                        self.max_tally_weight() - self.votes.abstain,
+                        percentage_needed,
+                    )
             }
             Threshold::ThresholdQuorum { threshold, quorum } => {
                 // we always require the quorum
-                if self.votes.total() < votes_needed(self.total_weight, quorum)
+                if self.quorum_weight() < votes_needed(self.quorum_supply(), quorum)
                     || self.votes.total() == self.votes.abstain
                     || self.votes.veto
                         > (Decimal::percent(33) * Uint128::from(self.votes.total())).u128()
                 {
                     false
+                } else if self.is_multiple_choice() {
+                    // the leading option has to beat "none of the above" by the threshold
+                    match self.winning_option() {
+                        Some(index) => {
+                            let support = self.option_votes[index];
+                            support >= votes_needed(support + self.votes.no, threshold)
+                        }
+                        None => false,
+                    }
                 } else {
                     // If expired, we compare vote_count against the total number of votes (minus abstain).
                     let opinions = self.votes.total() - self.votes.abstain;
@@ -124,11 +890,17 @@ impl Proposal {
     }
 
     pub fn is_rejected(&self, block: &BlockInfo) -> bool {
+        if self.is_optimistic() {
+            return self.objections_exceeded();
+        }
+        if self.is_multiple_choice() || !self.participants.is_empty() {
+            return !self.is_passed(block);
+        }
         match self.threshold {
             Threshold::AbsoluteCount {
                 weight: weight_needed,
             } => {
-                let weight = self.total_weight - weight_needed;
+                let weight = self.max_tally_weight() - weight_needed;
                 self.votes.no > weight
             }
             Threshold::AbsolutePercentage {
@@ -136,14 +908,14 @@ impl Proposal {
             } => {
                 self.votes.no
                     > votes_needed(
-                        self.total_weight - self.votes.abstain,
+                        self.max_tally_weight() - self.votes.abstain,
                         Decimal::one() - percentage_needed,
                     )
             }
             Threshold::ThresholdQuorum { threshold, quorum } => {
                 let opinions = self.votes.total() - self.votes.abstain;
 
-                if self.votes.total() < votes_needed(self.total_weight, quorum)
+                if self.quorum_weight() < votes_needed(self.quorum_supply(), quorum)
                     || self.votes.total() == self.votes.abstain
                     || self.votes.veto
                         > (Decimal::percent(33) * Uint128::from(self.votes.total())).u128()
@@ -156,7 +928,7 @@ impl Proposal {
                     self.votes.no > votes_needed(opinions, Decimal::one() - threshold)
                 } else {
                     // If not expired, we must assume all non-votes will be cast for
-                    let possible_opinions = self.total_weight - self.votes.abstain;
+                    let possible_opinions = self.max_tally_weight() - self.votes.abstain;
                     self.votes.no > votes_needed(possible_opinions, Decimal::one() - threshold)
                 }
             }
@@ -164,11 +936,15 @@ impl Proposal {
     }
 
     pub fn check_vetoed(&self, _block: &BlockInfo) -> bool {
+        // a veto by the holders of any participating app counts
+        if self.participants.iter().any(AppTally::is_vetoed) {
+            return true;
+        }
         match self.threshold {
             Threshold::AbsoluteCount {
                 weight: weight_needed,
             } => {
-                let weight = self.total_weight - weight_needed;
+                let weight = self.max_tally_weight() - weight_needed;
                 self.votes.no > weight
             }
             Threshold::AbsolutePercentage {
@@ -176,7 +952,7 @@ impl Proposal {
             } => {
                 self.votes.no
                     > votes_needed(
-                        self.total_weight - self.votes.abstain,
+                        self.max_tally_weight() - self.votes.abstain,
                         Decimal::one() - percentage_needed,
                     )
             }
@@ -184,7 +960,7 @@ impl Proposal {
                 threshold: _,
                 quorum,
             } => {
-                self.votes.total() > votes_needed(self.total_weight, quorum)
+                self.quorum_weight() > votes_needed(self.quorum_supply(), quorum)
                     && self.votes.veto
                         > (Decimal::percent(33).mul(Uint128::from(self.votes.total()))).u128()
             }
@@ -193,7 +969,7 @@ impl Proposal {
 }
 
 // weight of votes for each option
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Eq)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Eq, Default)]
 pub struct Votes {
     pub yes: u128,
     pub no: u128,
@@ -248,8 +1024,90 @@ pub fn votes_needed(weight: u128, percentage: Decimal) -> u128 {
 // stored under the key that voted
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct Ballot {
+    // weight counted in the tally
     pub weight: u128,
     pub vote: Vote,
+    // vtoken balance of the voter at `start_height`
+    #[serde(default)]
+    pub raw_weight: u128,
+    // conviction tier the ballot was committed with, if any
+    #[serde(default)]
+    pub conviction: Option<u8>,
+    // option picked on a multiple choice proposal
+    #[serde(default)]
+    pub option: Option<u32>,
+    // rationale published by the voter
+    #[serde(default)]
+    pub memo: Option<String>,
+    // weights counted in the tallies of the participating apps of a cross-app proposal
+    #[serde(default)]
+    pub app_weights: Vec<u128>,
+}
+
+// participation of an address across all proposals
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+pub struct VoterStats {
+    pub proposals_voted: u64,
+    pub proposals_created: u64,
+    // gov tokens deposited by the address on proposals that got slashed
+    pub deposits_slashed: u128,
+}
+
+// entry of the append-only vote history of a voter on a proposal
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct VoteRecord {
+    pub ballot: Ballot,
+    pub height: u64,
+    pub time: Timestamp,
+}
+
+// record of a guardian cancelling a passed proposal
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Cancellation {
+    pub guardian: Addr,
+    pub reason: String,
+    pub height: u64,
+    pub time: Timestamp,
+}
+
+// outcome of a single proposal message dispatched as a submessage
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MessageResult {
+    pub index: u32,
+    pub success: bool,
+    pub error: Option<String>,
+    // data returned by a contract execution
+    pub data: Option<Binary>,
+}
+
+// secret ballot of a commit-reveal proposal, replaced by a Ballot once revealed
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct Commitment {
+    // hex encoded sha256 of `vote || salt || voter`
+    pub commitment: String,
+    // snapshot weight counted toward the quorum while unrevealed, if enabled
+    pub weight: u128,
+}
+
+/// hex encoded sha256 of the lowercase vote name, the salt and the voter address
+pub fn commitment_hash(vote: Vote, salt: &str, voter: &Addr) -> String {
+    let vote = match vote {
+        Vote::Yes => "yes",
+        Vote::No => "no",
+        Vote::Abstain => "abstain",
+        Vote::Veto => "veto",
+    };
+    let preimage = format!("{}{}{}", vote, salt, voter);
+    HexBinary::from(Sha256::digest(preimage.as_bytes()).to_vec()).to_hex()
+}
+
+// content hash of a proposal after its creation or an amendment
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ProposalRevision {
+    pub revision: u32,
+    pub content_hash: String,
+    pub height: u64,
+    pub time: Timestamp,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -266,11 +1124,103 @@ pub const PROPOSAL_COUNT: Item<u64> = Item::new("proposal_count");
 
 // multiple-item map
 pub const BALLOTS: Map<(u64, &Addr), Ballot> = Map::new("ballots");
+pub const VOTEHISTORY: Map<(u64, &Addr), Vec<VoteRecord>> = Map::new("vote_history");
+// BALLOTS mirrored under the voter
+pub const VOTERBALLOTS: Map<(&Addr, u64), Ballot> = Map::new("voter_ballots");
+// proposals a voter cast a ballot on, by app
+pub const VOTERAPPBALLOTS: Map<(&Addr, u64, u64), Empty> = Map::new("voter_app_ballots");
+pub const VOTERSTATS: Map<&Addr, VoterStats> = Map::new("voter_stats");
+// proposals voted and created per voter and app, deposits are only slashed in VOTERSTATS
+pub const VOTERAPPSTATS: Map<(&Addr, u64), VoterStats> = Map::new("voter_app_stats");
+pub const PERIODSTATS: Map<(u64, u64), PeriodStats> = Map::new("period_stats");
+// proposals by the time in seconds `SudoMsg::ProcessExpired` has to look at them again
+pub const EXPIRYQUEUE: Map<(u64, u64), Empty> = Map::new("expiry_queue");
+// addresses that voted on a proposal of the app
+pub const APPVOTERS: Map<(u64, &Addr), Empty> = Map::new("app_voters");
 pub const PROPOSALSBYAPP: Map<u64, Vec<u64>> = Map::new("proposals_by_app");
 pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
 pub const VOTERDEPOSIT: Map<(u64, &Addr), Vec<Coin>> = Map::new("voter_deposit");
 pub const APPPROPOSALS: Map<u64, Vec<AppProposalConfig>> = Map::new("app_proposals");
 pub const APPGOVCONFIG: Map<u64, AppGovConfig> = Map::new("app_gov_config");
+pub const APPSETTINGS: Map<u64, AppSettings> = Map::new("app_settings");
+pub const PROPOSALREVISIONS: Map<u64, Vec<ProposalRevision>> = Map::new("proposal_revisions");
+// proposal revision the depositor last deposited against
+pub const DEPOSITREVISION: Map<(u64, &Addr), u32> = Map::new("deposit_revision");
+// proposals per app with a Pending/Open/Revealing stored status, the proposal limits only count
+// the ones that are still active by their current status
+pub const ACTIVEPROPOSALS: Map<(u64, u64), Empty> = Map::new("active_proposal_ids");
+// time of the last slash of a proposer, per app
+pub const PROPOSERSLASHED: Map<(u64, &Addr), Timestamp> = Map::new("proposer_slashed");
+// next expected nonce of signed ballots per voter
+pub const VOTENONCES: Map<&Addr, u64> = Map::new("vote_nonces");
+pub const COMMITMENTS: Map<(u64, &Addr), Commitment> = Map::new("commitments");
+pub const CANCELLATIONS: Map<u64, Cancellation> = Map::new("cancellations");
+pub const CATEGORIES: Item<Vec<Category>> = Item::new("categories");
+// results of the last execution attempt of a proposal
+pub const EXECUTIONRESULTS: Map<u64, Vec<MessageResult>> = Map::new("execution_results");
+// slashed gov tokens kept for voter rewards, per app
+pub const VOTERREWARDPOOL: Map<u64, u128> = Map::new("voter_reward_pool");
+pub const PROPOSALREWARDS: Map<u64, ProposalRewards> = Map::new("proposal_rewards");
+pub const REWARDSCLAIMED: Map<(u64, &Addr), Empty> = Map::new("rewards_claimed");
+
+/// saves the latest ballot of `voter` and appends it to the vote history
+pub fn save_ballot(
+    store: &mut dyn Storage,
+    app_id: u64,
+    proposal_id: u64,
+    voter: &Addr,
+    ballot: &Ballot,
+    block: &BlockInfo,
+) -> StdResult<()> {
+    let previous = BALLOTS.may_load(store, (proposal_id, voter))?;
+    let mut app_stats = APPGOVCONFIG.may_load(store, app_id)?.unwrap_or_default();
+    app_stats.active_participation_supply += ballot.weight;
+    match previous {
+        Some(previous) => {
+            app_stats.active_participation_supply -= previous.weight;
+        }
+        None => {
+            app_stats.votes_cast += 1;
+            let mut stats = VOTERSTATS.may_load(store, voter)?.unwrap_or_default();
+            stats.proposals_voted += 1;
+            VOTERSTATS.save(store, voter, &stats)?;
+            let mut stats = VOTERAPPSTATS
+                .may_load(store, (voter, app_id))?
+                .unwrap_or_default();
+            stats.proposals_voted += 1;
+            VOTERAPPSTATS.save(store, (voter, app_id), &stats)?;
+        }
+    }
+    if !APPVOTERS.has(store, (app_id, voter)) {
+        app_stats.unique_voters += 1;
+        APPVOTERS.save(store, (app_id, voter), &Empty {})?;
+    }
+    APPGOVCONFIG.save(store, app_id, &app_stats)?;
+    BALLOTS.save(store, (proposal_id, voter), ballot)?;
+    VOTERBALLOTS.save(store, (voter, proposal_id), ballot)?;
+    VOTERAPPBALLOTS.save(store, (voter, app_id, proposal_id), &Empty {})?;
+    let mut history = VOTEHISTORY
+        .may_load(store, (proposal_id, voter))?
+        .unwrap_or_default();
+    history.push(VoteRecord {
+        ballot: ballot.clone(),
+        height: block.height,
+        time: block.time,
+    });
+    VOTEHISTORY.save(store, (proposal_id, voter), &history)
+}
+
+/// queues a proposal for `SudoMsg::ProcessExpired` at `expiration`, expirations are time based
+pub fn queue_proposal(
+    store: &mut dyn Storage,
+    proposal_id: u64,
+    expiration: Expiration,
+) -> StdResult<()> {
+    if let Expiration::AtTime(time) = expiration {
+        EXPIRYQUEUE.save(store, (time.seconds(), proposal_id), &Empty {})?;
+    }
+    Ok(())
+}
 
 pub fn next_id(store: &mut dyn Storage) -> StdResult<u64> {
     let id: u64 = PROPOSAL_COUNT.may_load(store)?.unwrap_or_default() + 1;
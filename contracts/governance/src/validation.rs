@@ -1,13 +1,23 @@
 use crate::error::ContractError;
 use fury_bindings::{
-    FuryQuery, GetAppResponse, GetAssetDataResponse, MessageValidateResponse, StateResponse,
-    TotalSupplyResponse,
+    FuryMessages, FuryQuery, GetAppResponse, GetAssetDataResponse, MessageValidateResponse,
+    StateResponse, TotalSupplyResponse,
 };
 
-use crate::msg::ExtendedPair;
+use crate::msg::{ExtendedPair, SignedBallot, SignedVotePayload};
+use bech32::{ToBase32, Variant};
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+
+use crate::state::OptimisticSettings;
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use std::collections::BTreeMap;
 
 #[cfg(not(feature = "library"))]
-use cosmwasm_std::{Coin, Decimal, Deps, QueryRequest, StdResult};
+use cosmwasm_std::{
+    from_json, to_json_binary, Addr, Coin, Decimal, Deps, Env, QueryRequest, StdError, StdResult,
+};
 
 pub fn validate_threshold(threshold: &Decimal, quorum: &Decimal) -> Result<(), ContractError> {
     if *threshold > Decimal::percent(100) || *threshold < Decimal::percent(50) {
@@ -21,6 +31,84 @@ pub fn validate_threshold(threshold: &Decimal, quorum: &Decimal) -> Result<(), C
     }
 }
 
+/// snake_case name of the `FuryMessages` variant, e.g. `msg_whitelist_app_id_locker_rewards`
+pub fn msg_kind(msg: &FuryMessages) -> StdResult<String> {
+    let msg: BTreeMap<String, IgnoredAny> = from_json(&to_json_binary(msg)?)?;
+    msg.into_keys()
+        .next()
+        .ok_or_else(|| StdError::generic_err("Empty message"))
+}
+
+#[derive(Deserialize)]
+struct MsgTarget {
+    app_id: Option<u64>,
+}
+
+/// app whose state a `FuryMessages` message changes, None if the message names no app
+pub fn msg_app_id(msg: &FuryMessages) -> StdResult<Option<u64>> {
+    let msg: BTreeMap<String, MsgTarget> = from_json(&to_json_binary(msg)?)?;
+    Ok(msg.into_values().next().and_then(|target| target.app_id))
+}
+
+/// checks that all messages of an optimistic proposal are allowlisted by the app
+pub fn validate_optimistic_msgs(
+    settings: &OptimisticSettings,
+    msgs: &[FuryMessages],
+) -> Result<(), ContractError> {
+    for msg in msgs {
+        let kind = msg_kind(msg)?;
+        if !settings.allowed_msgs.contains(&kind) {
+            return Err(ContractError::MsgNotOptimistic { kind });
+        }
+    }
+    Ok(())
+}
+
+/// verifies the signature of a relayed ballot and returns the bech32 address of its signer,
+/// using the address prefix of this contract
+pub fn verify_signed_ballot(
+    deps: Deps<FuryQuery>,
+    env: &Env,
+    ballot: &SignedBallot,
+) -> Result<Addr, ContractError> {
+    if ballot.pubkey.len() != 33 {
+        return Err(ContractError::InvalidPubkey {});
+    }
+    let payload = SignedVotePayload {
+        chain_id: env.block.chain_id.clone(),
+        contract: env.contract.address.to_string(),
+        proposal_id: ballot.proposal_id,
+        vote: ballot.vote,
+        conviction: ballot.conviction,
+        option: ballot.option,
+        nonce: ballot.nonce,
+    };
+    let hash = Sha256::digest(to_json_binary(&payload)?.as_slice());
+    let verified = deps
+        .api
+        .secp256k1_verify(&hash, &ballot.signature, &ballot.pubkey)
+        .map_err(|_| ContractError::InvalidSignature {})?;
+    if !verified {
+        return Err(ContractError::InvalidSignature {});
+    }
+
+    let prefix = match env.contract.address.as_str().rsplit_once('1') {
+        Some((prefix, _)) if !prefix.is_empty() => prefix,
+        _ => {
+            return Err(ContractError::CustomError {
+                val: "Contract address is not bech32".to_string(),
+            })
+        }
+    };
+    let account = Ripemd160::digest(Sha256::digest(ballot.pubkey.as_slice()));
+    let address = bech32::encode(prefix, account.to_base32(), Variant::Bech32).map_err(|err| {
+        ContractError::CustomError {
+            val: err.to_string(),
+        }
+    })?;
+    Ok(Addr::unchecked(address))
+}
+
 /// validate checks to update vault stability fee
 pub fn update_pairvault_stability(
     deps: Deps<FuryQuery>,
@@ -351,6 +439,140 @@ pub fn set_esm_params(
     }
 }
 
+/// eligibility checks of a proposal message against the Fury modules for the proposing app
+pub fn validate_proposal_msg(
+    deps: Deps<FuryQuery>,
+    msg: &FuryMessages,
+    proposal_app_id: u64,
+) -> Result<(), ContractError> {
+    match msg.clone() {
+        FuryMessages::MsgWhiteListAssetLocker { app_id, asset_id } => {
+            whitelist_asset_locker_eligible(deps, app_id, asset_id, proposal_app_id)?
+        }
+        FuryMessages::MsgWhitelistAppIdLockerRewards { app_id, asset_id } => {
+            whitelist_asset_locker_rewards(deps, app_id, asset_id, proposal_app_id)?
+        }
+        FuryMessages::MsgWhitelistAppIdVaultInterest { app_id } => {
+            whitelist_app_id_vault_interest(deps, app_id, proposal_app_id)?
+        }
+        FuryMessages::MsgAddExtendedPairsVault {
+            app_id,
+            pair_id,
+            stability_fee,
+            closing_fee,
+            liquidation_penalty: _,
+            draw_down_fee,
+            is_vault_active: _,
+            debt_ceiling,
+            debt_floor,
+            is_stable_mint_vault: _,
+            min_cr: _,
+            pair_name,
+            asset_out_oracle_price: _,
+            asset_out_price: _,
+            min_usd_value_left: _,
+        } => add_extended_pair_vault(
+            deps,
+            proposal_app_id,
+            ExtendedPair {
+                app_mapping_id_param: app_id,
+                pair_id_param: pair_id,
+                stability_fee_param: stability_fee,
+                closing_fee_param: closing_fee,
+                draw_down_fee_param: draw_down_fee,
+                debt_ceiling_param: debt_ceiling,
+                debt_floor_param: debt_floor,
+                pair_name_param: pair_name,
+            },
+        )?,
+        FuryMessages::MsgSetCollectorLookupTable {
+            app_id,
+            collector_asset_id,
+            secondary_asset_id,
+            surplus_threshold: _,
+            debt_threshold: _,
+            locker_saving_rate: _,
+            lot_size: _,
+            bid_factor: _,
+            debt_lot_size: _,
+        } => collector_lookup_table(
+            deps,
+            app_id,
+            collector_asset_id,
+            secondary_asset_id,
+            proposal_app_id,
+        )?,
+
+        FuryMessages::MsgUpdatePairsVault {
+            app_id,
+            ext_pair_id,
+            stability_fee: _,
+            closing_fee: _,
+            liquidation_penalty: _,
+            draw_down_fee: _,
+            min_cr: _,
+            debt_ceiling: _,
+            debt_floor: _,
+            min_usd_value_left: _,
+            is_vault_active: _,
+        } => update_pairvault_stability(deps, app_id, ext_pair_id, proposal_app_id)?,
+
+        FuryMessages::MsgSetAuctionMappingForApp {
+            app_id,
+            asset_id: _,
+            is_surplus_auction: _,
+            is_debt_auction: _,
+            asset_out_oracle_price: _,
+            asset_out_price: _,
+            is_distributor: _,
+        } => auction_mapping_for_app(deps, app_id, proposal_app_id)?,
+
+        FuryMessages::MsgUpdateCollectorLookupTable {
+            app_id,
+            asset_id,
+            lsr: _,
+            debt_threshold: _,
+            surplus_threshold: _,
+            lot_size: _,
+            debt_lot_size: _,
+            bid_factor: _,
+        } => update_locker_lsr(deps, app_id, asset_id, proposal_app_id)?,
+        FuryMessages::MsgRemoveWhitelistAssetLocker { app_id, asset_id } => {
+            remove_whitelist_asset_locker(deps, app_id, asset_id, proposal_app_id)?
+        }
+        FuryMessages::MsgRemoveWhitelistAppIdVaultInterest { app_id } => {
+            remove_whitelist_app_id_vault_interest(deps, app_id, proposal_app_id)?
+        }
+        FuryMessages::MsgWhitelistAppIdLiquidation { app_id } => {
+            whitelist_app_id_liquidation(deps, app_id, proposal_app_id)?
+        }
+        FuryMessages::MsgRemoveWhitelistAppIdLiquidation { app_id } => {
+            remove_whitelist_app_id_liquidation(deps, app_id, proposal_app_id)?
+        }
+        FuryMessages::MsgAddAuctionParams {
+            app_id: _,
+            auction_duration_seconds: _,
+            buffer: _,
+            cusp: _,
+            step: _,
+            price_function_type: _,
+            surplus_id: _,
+            debt_id: _,
+            dutch_id: _,
+            bid_duration_seconds: _,
+        } => (),
+        FuryMessages::MsgAddESMTriggerParams {
+            app_id,
+            target_value: _,
+            cool_off_period: _,
+            asset_id: _,
+            rates: _,
+        } => set_esm_params(deps, app_id, proposal_app_id)?,
+        _ => return Err(ContractError::ProposalNotEligible {}),
+    }
+    Ok(())
+}
+
 /// query token balance of a user for a denom at a specific height
 pub fn query_owner_token_at_height(
     deps: Deps<FuryQuery>,
@@ -413,6 +635,30 @@ pub fn get_token_supply(
     Ok(total_token_supply.current_supply)
 }
 
+/// validates a message of a cross-app proposal for the app whose state it changes, which has
+/// to be one of the participating apps, and a message of a single-app proposal for its app
+pub fn validate_participating_msg(
+    deps: Deps<FuryQuery>,
+    msg: &FuryMessages,
+    app_ids: &[u64],
+) -> Result<(), ContractError> {
+    if app_ids.len() == 1 {
+        return validate_proposal_msg(deps, msg, app_ids[0]);
+    }
+    let app_id = msg_target_app(msg, app_ids)?;
+    validate_proposal_msg(deps, msg, app_id)
+}
+
+/// participating app whose state a message changes, the app of the proposal if the message
+/// names no app
+pub fn msg_target_app(msg: &FuryMessages, app_ids: &[u64]) -> Result<u64, ContractError> {
+    let app_id = msg_app_id(msg)?.unwrap_or(app_ids[0]);
+    if !app_ids.contains(&app_id) {
+        return Err(ContractError::AppNotParticipating { app_id });
+    }
+    Ok(app_id)
+}
+
 #[cfg(test)]
 mod validation_tests {
     use super::*;
@@ -78,6 +78,12 @@ pub enum Status {
     Passed = 4,
     /// voting is over it passed, and the proposal was executed
     Executed = 5,
+    /// voting is over, committed secret ballots can still be revealed
+    Revealing = 6,
+    /// proposal passed but was cancelled before execution
+    Cancelled = 7,
+    /// proposal passed but its messages were no longer valid at execution
+    ExecutionFailed = 8,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -97,7 +103,17 @@ pub struct VoteInfo {
     pub proposal_id: u64,
     pub voter: String,
     pub vote: Vote,
+    /// weight counted in the tally
     pub weight: u128,
+    /// voting power before any tally mode (e.g. quadratic) was applied
+    #[serde(default)]
+    pub raw_weight: u128,
+    /// option picked on a multiple choice proposal
+    #[serde(default)]
+    pub option: Option<u32>,
+    /// rationale published by the voter
+    #[serde(default)]
+    pub memo: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]